@@ -0,0 +1,134 @@
+use crate::types::ScrapeResponse;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+/// Bounds and starting point for the adaptive scrape-cache TTL
+#[derive(Debug, Clone, Copy)]
+pub struct TtlPolicy {
+    pub initial: Duration,
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl TtlPolicy {
+    /// Read `SCRAPE_CACHE_TTL_{INITIAL,MIN,MAX}_SECS`, defaulting to 30m within [5m, 24h]
+    pub fn from_env() -> Self {
+        let secs = |name: &str, default: u64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(Duration::from_secs(default))
+        };
+        let min = secs("SCRAPE_CACHE_TTL_MIN_SECS", 60 * 5);
+        let max = secs("SCRAPE_CACHE_TTL_MAX_SECS", 60 * 60 * 24).max(min);
+        let initial = secs("SCRAPE_CACHE_TTL_INITIAL_SECS", 60 * 30).clamp(min, max);
+        Self { initial, min, max }
+    }
+}
+
+/// Observed change history of a page across refetches
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentHistory {
+    pub content_hash: u64,
+    pub ttl: Duration,
+    pub fetches: u32,
+    pub changes: u32,
+}
+
+/// A scrape-cache entry together with the history that decided its TTL
+#[derive(Debug, Clone)]
+pub struct CachedScrape {
+    pub response: ScrapeResponse,
+    pub history: ContentHistory,
+}
+
+/// Stable hash of the extracted text, used to detect content changes between fetches
+pub fn content_hash(response: &ScrapeResponse) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    response.clean_content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Compute the next history entry: double the TTL while content is unchanged, halve it on change
+pub fn next_history(previous: Option<&ContentHistory>, content_hash: u64, policy: &TtlPolicy) -> ContentHistory {
+    match previous {
+        None => ContentHistory {
+            content_hash,
+            ttl: policy.initial,
+            fetches: 1,
+            changes: 0,
+        },
+        Some(prev) => {
+            let changed = prev.content_hash != content_hash;
+            let ttl = if changed { prev.ttl / 2 } else { prev.ttl.saturating_mul(2) };
+            ContentHistory {
+                content_hash,
+                ttl: ttl.clamp(policy.min, policy.max),
+                fetches: prev.fetches.saturating_add(1),
+                changes: prev.changes + changed as u32,
+            }
+        }
+    }
+}
+
+/// moka expiry that reads each entry's TTL from its history
+pub struct AdaptiveExpiry;
+
+impl moka::Expiry<String, CachedScrape> for AdaptiveExpiry {
+    fn expire_after_create(&self, _key: &String, value: &CachedScrape, _created_at: Instant) -> Option<Duration> {
+        Some(value.history.ttl)
+    }
+
+    fn expire_after_update(
+        &self,
+        _key: &String,
+        value: &CachedScrape,
+        _updated_at: Instant,
+        _duration_until_expiry: Option<Duration>,
+    ) -> Option<Duration> {
+        Some(value.history.ttl)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> TtlPolicy {
+        TtlPolicy {
+            initial: Duration::from_secs(600),
+            min: Duration::from_secs(300),
+            max: Duration::from_secs(2400),
+        }
+    }
+
+    #[test]
+    fn test_stable_content_extends_ttl() {
+        let policy = policy();
+        let first = next_history(None, 42, &policy);
+        assert_eq!(first.ttl, Duration::from_secs(600));
+
+        let second = next_history(Some(&first), 42, &policy);
+        assert_eq!(second.ttl, Duration::from_secs(1200));
+        let third = next_history(Some(&second), 42, &policy);
+        assert_eq!(third.ttl, Duration::from_secs(2400));
+        // Capped at max
+        let fourth = next_history(Some(&third), 42, &policy);
+        assert_eq!(fourth.ttl, Duration::from_secs(2400));
+        assert_eq!((fourth.fetches, fourth.changes), (4, 0));
+    }
+
+    #[test]
+    fn test_changing_content_shortens_ttl() {
+        let policy = policy();
+        let first = next_history(None, 1, &policy);
+        let second = next_history(Some(&first), 2, &policy);
+        assert_eq!(second.ttl, Duration::from_secs(300));
+        // Floored at min
+        let third = next_history(Some(&second), 3, &policy);
+        assert_eq!(third.ttl, Duration::from_secs(300));
+        assert_eq!(third.changes, 2);
+    }
+}
\ No newline at end of file
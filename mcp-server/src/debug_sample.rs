@@ -0,0 +1,100 @@
+//! Sampled dumps of raw HTML and the chosen extraction, for collecting real extraction failures.
+
+use crate::types::ScrapeResponse;
+use rand::Rng;
+use std::path::PathBuf;
+use tracing::{debug, warn};
+
+/// Default cap on each dumped section (raw HTML, extracted text)
+const DEFAULT_MAX_DUMP_BYTES: usize = 256 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct DebugSampler {
+    pub rate: f64,
+    pub dir: Option<PathBuf>,
+    pub max_bytes: usize,
+}
+
+impl DebugSampler {
+    /// Read `SCRAPE_DEBUG_SAMPLE_RATE` (0.0-1.0, default 0), `SCRAPE_DEBUG_DUMP_DIR` and `SCRAPE_DEBUG_MAX_DUMP_BYTES`
+    pub fn from_env() -> Self {
+        Self {
+            rate: std::env::var("SCRAPE_DEBUG_SAMPLE_RATE")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(0.0)
+                .clamp(0.0, 1.0),
+            dir: std::env::var("SCRAPE_DEBUG_DUMP_DIR").ok().filter(|v| !v.is_empty()).map(PathBuf::from),
+            max_bytes: std::env::var("SCRAPE_DEBUG_MAX_DUMP_BYTES")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(DEFAULT_MAX_DUMP_BYTES),
+        }
+    }
+
+    pub fn should_sample(&self) -> bool {
+        self.rate >= 1.0 || (self.rate > 0.0 && rand::thread_rng().gen::<f64>() < self.rate)
+    }
+
+    /// Dump a sampled scrape at debug level and, if configured, to a file. Returns whether it was sampled.
+    pub fn maybe_dump(&self, response: &ScrapeResponse) -> bool {
+        if !self.should_sample() {
+            return false;
+        }
+        let dump = format!(
+            "URL: {}\nStatus: {}\nWords: {}\n\n=== extracted ===\n{}\n\n=== raw html ===\n{}\n",
+            response.url,
+            response.status_code,
+            response.word_count,
+            truncate_bytes(&response.clean_content, self.max_bytes),
+            truncate_bytes(&response.content, self.max_bytes),
+        );
+        debug!(target: "scrape_debug_sample", "{}", dump);
+        if let Some(dir) = &self.dir {
+            let host = url::Url::parse(&response.url)
+                .ok()
+                .and_then(|u| u.host_str().map(|h| h.replace(|c: char| !c.is_ascii_alphanumeric() && c != '.', "_")))
+                .unwrap_or_else(|| "unknown".to_string());
+            let path = dir.join(format!("{}-{}.txt", chrono::Utc::now().format("%Y%m%dT%H%M%S%.3f"), host));
+            if let Err(e) = std::fs::create_dir_all(dir).and_then(|_| std::fs::write(&path, &dump)) {
+                warn!("Failed to write scrape debug dump {}: {}", path.display(), e);
+            }
+        }
+        true
+    }
+}
+
+/// Cut `text` to at most `max` bytes on a char boundary
+fn truncate_bytes(text: &str, max: usize) -> &str {
+    if text.len() <= max {
+        return text;
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::empty_scrape_response;
+
+    #[test]
+    fn test_sample_rate_bounds() {
+        let dir = std::env::temp_dir().join(format!("scrape-debug-{}", std::process::id()));
+        let mut page = empty_scrape_response("https://example.com/a");
+        page.content = "<p>héllo wörld</p>".repeat(10);
+
+        let always = DebugSampler { rate: 1.0, dir: Some(dir.clone()), max_bytes: 12 };
+        assert!((0..100).all(|_| always.maybe_dump(&page)));
+        let dumped = std::fs::read_dir(&dir).unwrap().next().unwrap().unwrap().path();
+        let text = std::fs::read_to_string(dumped).unwrap();
+        assert!(text.contains("=== raw html ===\n<p>héllo w\n"));
+
+        let never = DebugSampler { rate: 0.0, dir: None, max_bytes: 16 };
+        assert!((0..100).all(|_| !never.maybe_dump(&page)));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
\ No newline at end of file
@@ -0,0 +1,93 @@
+//! Near-duplicate detection for batches of scrapes (print vs normal view, mirrors) using SimHash
+//! over word shingles of `clean_content`.
+
+use crate::types::ScrapeResponse;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Words per shingle; short texts fall back to single words
+const SHINGLE_WORDS: usize = 3;
+
+/// Minimum similarity when `CHAT_DEDUP_SIMILARITY` enables dedup without a 0-1 value (e.g. `true`)
+pub const DEFAULT_MIN_SIMILARITY: f64 = 0.9;
+
+/// Minimum similarity for dedup in batch scrapes from `CHAT_DEDUP_SIMILARITY` (unset: no dedup)
+pub fn min_similarity_from_env() -> Option<f64> {
+    let value = std::env::var("CHAT_DEDUP_SIMILARITY").ok()?;
+    Some(value.trim().parse::<f64>().ok().filter(|s| (0.0..=1.0).contains(s)).unwrap_or(DEFAULT_MIN_SIMILARITY))
+}
+
+/// 64-bit SimHash of the text's lowercase word shingles
+pub fn simhash(text: &str) -> u64 {
+    let words: Vec<String> = text.split_whitespace().map(|w| w.to_lowercase()).collect();
+    let width = SHINGLE_WORDS.min(words.len()).max(1);
+    let mut weights = [0i32; 64];
+    for shingle in words.windows(width) {
+        let mut hasher = DefaultHasher::new();
+        shingle.hash(&mut hasher);
+        let h = hasher.finish();
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            *weight += if h >> bit & 1 == 1 { 1 } else { -1 };
+        }
+    }
+    weights.iter().enumerate().filter(|(_, w)| **w > 0).fold(0, |acc, (bit, _)| acc | 1 << bit)
+}
+
+/// Fraction of matching SimHash bits, 1.0 for identical fingerprints
+pub fn similarity(a: u64, b: u64) -> f64 {
+    1.0 - (a ^ b).count_ones() as f64 / 64.0
+}
+
+/// Collapse scrapes whose content is at least `min_similarity` alike, keeping the longest of each
+/// group at the position of its first member. Scrapes without content are never merged.
+pub fn dedup_scrapes(scrapes: Vec<ScrapeResponse>, min_similarity: f64) -> Vec<ScrapeResponse> {
+    let mut kept: Vec<(Option<u64>, ScrapeResponse)> = Vec::with_capacity(scrapes.len());
+    for scrape in scrapes {
+        let fingerprint = (!scrape.clean_content.trim().is_empty()).then(|| simhash(&scrape.clean_content));
+        let duplicate = fingerprint.and_then(|fp| {
+            kept.iter().position(|(other, _)| other.is_some_and(|o| similarity(fp, o) >= min_similarity))
+        });
+        match duplicate {
+            Some(i) if scrape.clean_content.len() > kept[i].1.clean_content.len() => kept[i] = (fingerprint, scrape),
+            Some(_) => {}
+            None => kept.push((fingerprint, scrape)),
+        }
+    }
+    kept.into_iter().map(|(_, scrape)| scrape).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::empty_scrape_response;
+
+    fn scrape(url: &str, content: &str) -> ScrapeResponse {
+        ScrapeResponse { clean_content: content.to_string(), ..empty_scrape_response(url) }
+    }
+
+    #[test]
+    fn test_near_identical_scrapes_collapse_to_longest() {
+        let article = "The city council approved the new transit plan on Tuesday after months of debate. \
+            The plan adds three bus routes, extends light rail service to the airport and lowers fares for students. \
+            Construction on the first route is expected to begin next spring, with service starting the following year. "
+            .repeat(4);
+        let print_view = format!("{}Printed from example.com", article);
+        let other = "A recipe for sourdough bread needs flour, water, salt and an active starter. \
+            Mix the dough, let it rest overnight, shape the loaf and bake it in a hot oven until the crust is dark."
+            .to_string();
+        let batch = vec![
+            scrape("https://example.com/news/transit", &article),
+            scrape("https://example.com/recipes/sourdough", &other),
+            scrape("https://example.com/news/transit?print=1", &print_view),
+        ];
+
+        let deduped = dedup_scrapes(batch.clone(), DEFAULT_MIN_SIMILARITY);
+        let urls: Vec<&str> = deduped.iter().map(|s| s.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://example.com/news/transit?print=1", "https://example.com/recipes/sourdough"]);
+
+        // The threshold decides what counts as a duplicate: at 0 every page with content collapses
+        let merged = dedup_scrapes(batch, 0.0);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].url, "https://example.com/news/transit?print=1");
+    }
+}
\ No newline at end of file
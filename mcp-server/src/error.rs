@@ -0,0 +1,153 @@
+//! Typed errors for failures that callers should handle differently from a generic 500.
+
+use std::time::Duration;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SearchError {
+    #[error("server busy: no outbound slot available, retry after {}s", retry_after.as_secs())]
+    Overloaded { retry_after: Duration },
+    #[error("SearXNG is rate limiting requests, retry after {}s", retry_after.as_secs())]
+    RateLimited { retry_after: Duration },
+    #[error("Failed to send request to SearXNG: {source}")]
+    Upstream { source: reqwest::Error },
+    #[error("invalid {field} date '{value}': expected YYYY-MM-DD or RFC 3339")]
+    InvalidDate { field: &'static str, value: String },
+    #[error("invalid time_range '{value}': expected one of day, week, month, year")]
+    InvalidTimeRange { value: String },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ScrapeError {
+    #[error("server busy: no outbound slot available, retry after {}s", retry_after.as_secs())]
+    Overloaded { retry_after: Duration },
+    #[error("{0}")]
+    InvalidUrl(String),
+    #[error("Failed to fetch URL: {source}")]
+    Fetch { source: reqwest::Error },
+    #[error("server sent response headers too large to accept (over 100 headers or ~400KB)")]
+    HeadersTooLarge { source: reqwest::Error },
+    #[error("access blocked by the site (HTTP {status})")]
+    Blocked { status: u16 },
+    #[error("page not found (HTTP {status})")]
+    NotFound { status: u16 },
+    #[error("login at {login_url} failed: {reason}")]
+    LoginFailed { login_url: String, reason: String },
+    #[error("Blocked by robots.txt: {url}")]
+    RobotsDisallowed { url: String },
+    #[error("Refusing to scrape private address: {url}")]
+    PrivateAddress { url: String },
+    #[error("response body exceeds the limit of {limit} bytes; download aborted")]
+    BodyTooLarge { limit: usize },
+    #[error("{origin} is paced by its robots.txt Crawl-delay, retry after {}s", retry_after.as_secs())]
+    RateLimited { origin: String, retry_after: Duration },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WatchError {
+    #[error("watch limit of {max} reached; remove a watch first")]
+    LimitReached { max: usize },
+}
+
+impl ScrapeError {
+    /// Classify a failed request, singling out responses whose header block the HTTP client rejects
+    /// and hops the SSRF guard refused
+    pub fn from_fetch(source: reqwest::Error) -> Self {
+        let mut cause: Option<&(dyn std::error::Error + 'static)> = Some(&source);
+        while let Some(err) = cause {
+            if err.downcast_ref::<hyper::Error>().is_some_and(|e| e.is_parse_too_large()) {
+                return Self::HeadersTooLarge { source };
+            }
+            if let Some(Self::PrivateAddress { url }) = err.downcast_ref::<Self>() {
+                return Self::PrivateAddress { url: url.clone() };
+            }
+            cause = err.source();
+        }
+        Self::Fetch { source }
+    }
+
+    /// Classify a final HTTP status that leaves nothing useful to extract
+    pub fn from_status(status: u16) -> Option<Self> {
+        match status {
+            404 | 410 => Some(Self::NotFound { status }),
+            401 | 403 | 451 => Some(Self::Blocked { status }),
+            _ => None,
+        }
+    }
+}
+
+/// The `Retry-After` hint carried by a backpressure error, if `err` is one
+pub fn retry_after(err: &anyhow::Error) -> Option<Duration> {
+    match (err.downcast_ref::<SearchError>(), err.downcast_ref::<ScrapeError>()) {
+        (Some(SearchError::Overloaded { retry_after } | SearchError::RateLimited { retry_after }), _) => Some(*retry_after),
+        (_, Some(ScrapeError::Overloaded { retry_after } | ScrapeError::RateLimited { retry_after, .. })) => Some(*retry_after),
+        _ => None,
+    }
+}
+
+/// Whether `err` was caused by bad caller input (HTTP 400) rather than a server-side failure
+pub fn is_invalid_input(err: &anyhow::Error) -> bool {
+    matches!(error_code(err), "invalid_argument" | "invalid_url" | "private_address")
+}
+
+/// Stable machine-readable code for an error, for clients that branch on the failure kind:
+/// `overloaded`, `rate_limited`, `invalid_argument`, `invalid_url`, `blocked`, `not_found`, `login_failed`,
+/// `robots_disallowed`, `private_address`, `body_too_large`, `timeout`, `unreachable`, `fetch_failed` or `internal`
+pub fn error_code(err: &anyhow::Error) -> &'static str {
+    for cause in err.chain() {
+        if let Some(e) = cause.downcast_ref::<SearchError>() {
+            match e {
+                SearchError::Overloaded { .. } => return "overloaded",
+                SearchError::RateLimited { .. } => return "rate_limited",
+                SearchError::InvalidDate { .. } | SearchError::InvalidTimeRange { .. } => return "invalid_argument",
+                SearchError::Upstream { .. } => {}
+            }
+        }
+        if let Some(e) = cause.downcast_ref::<ScrapeError>() {
+            match e {
+                ScrapeError::Overloaded { .. } => return "overloaded",
+                ScrapeError::InvalidUrl(_) => return "invalid_url",
+                ScrapeError::Blocked { .. } => return "blocked",
+                ScrapeError::NotFound { .. } => return "not_found",
+                ScrapeError::LoginFailed { .. } => return "login_failed",
+                ScrapeError::RobotsDisallowed { .. } => return "robots_disallowed",
+                ScrapeError::PrivateAddress { .. } => return "private_address",
+                ScrapeError::BodyTooLarge { .. } => return "body_too_large",
+                ScrapeError::RateLimited { .. } => return "rate_limited",
+                ScrapeError::Fetch { .. } | ScrapeError::HeadersTooLarge { .. } => {}
+            }
+        }
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            return if e.is_timeout() {
+                "timeout"
+            } else if e.is_connect() {
+                "unreachable"
+            } else {
+                "fetch_failed"
+            };
+        }
+    }
+    "internal"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+
+    #[tokio::test]
+    async fn test_timeout_maps_to_stable_code() {
+        let app = Router::new().route("/slow", get(|| async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            "late"
+        }));
+        let base = crate::test_support::serve(app).await;
+        let client = reqwest::Client::builder().timeout(Duration::from_millis(50)).build().unwrap();
+
+        let source = client.get(format!("{}/slow", base)).send().await.unwrap_err();
+        let err = anyhow::Error::new(ScrapeError::Fetch { source }).context("giving up after 4 attempts");
+        assert_eq!(error_code(&err), "timeout");
+
+        assert_eq!(error_code(&ScrapeError::from_status(404).unwrap().into()), "not_found");
+        assert_eq!(error_code(&anyhow::anyhow!("boom")), "internal");
+    }
+}
\ No newline at end of file
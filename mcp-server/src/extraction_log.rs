@@ -0,0 +1,80 @@
+//! Append-only JSONL log of extraction quality per scrape (`EXTRACTION_LOG_PATH`): the winning strategy,
+//! every candidate's word count and timing, for evaluating heuristics over time. A background thread
+//! does the writing so scrapes never wait on disk.
+
+use crate::types::ScrapeResponse;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Records buffered for the writer; beyond this they are dropped rather than slowing scrapes down
+const CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Debug, Serialize)]
+struct ExtractionRecord<'a> {
+    timestamp: String,
+    url: &'a str,
+    strategy: &'a str,
+    candidate_words: BTreeMap<&'a str, usize>,
+    word_count: usize,
+    elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExtractionLog {
+    sender: SyncSender<String>,
+}
+
+impl ExtractionLog {
+    /// Log to `EXTRACTION_LOG_PATH` when set
+    pub fn from_env() -> Option<Self> {
+        let path = std::env::var("EXTRACTION_LOG_PATH").ok().filter(|p| !p.is_empty())?;
+        Some(Self::open(PathBuf::from(path)))
+    }
+
+    /// Start the writer thread appending to `path`; it exits when the last handle is dropped
+    pub fn open(path: PathBuf) -> Self {
+        let (sender, receiver) = sync_channel::<String>(CHANNEL_CAPACITY);
+        std::thread::spawn(move || {
+            let mut file = match std::fs::OpenOptions::new().create(true).append(true).open(&path) {
+                Ok(file) => file,
+                Err(e) => {
+                    warn!("Cannot open extraction log {}: {}", path.display(), e);
+                    return;
+                }
+            };
+            for line in receiver {
+                if let Err(e) = file.write_all(line.as_bytes()) {
+                    warn!("Failed to write extraction log {}: {}", path.display(), e);
+                }
+            }
+        });
+        Self { sender }
+    }
+
+    /// Queue one line for `response` without blocking; custom extractors are logged as `custom`
+    pub fn record(&self, response: &ScrapeResponse, elapsed: Duration) {
+        let (strategy, candidate_words) = match &response.extraction {
+            Some(report) => (report.strategy, report.candidate_words.iter().copied().collect()),
+            None => ("custom", BTreeMap::new()),
+        };
+        let record = ExtractionRecord {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            url: &response.url,
+            strategy,
+            candidate_words,
+            word_count: response.word_count,
+            elapsed_ms: elapsed.as_millis() as u64,
+        };
+        let Ok(mut line) = serde_json::to_string(&record) else { return };
+        line.push('\n');
+        match self.sender.try_send(line) {
+            Ok(()) | Err(TrySendError::Disconnected(_)) => {}
+            Err(TrySendError::Full(_)) => debug!("Extraction log is behind, dropping record for {}", response.url),
+        }
+    }
+}
\ No newline at end of file
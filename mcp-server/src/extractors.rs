@@ -0,0 +1,89 @@
+//! Site-specific content extractors, consulted before the generic extraction pipeline.
+
+use crate::types::ScrapeResponse;
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use std::sync::Arc;
+use url::Url;
+
+/// A custom extractor for pages of particular hosts. Returning `None` defers to the generic pipeline.
+pub trait ContentExtractor: Send + Sync {
+    fn extract(&self, url: &Url, html: &str, status_code: u16, content_type: &str) -> Option<ScrapeResponse>;
+}
+
+impl<F> ContentExtractor for F
+where
+    F: Fn(&Url, &str, u16, &str) -> Option<ScrapeResponse> + Send + Sync,
+{
+    fn extract(&self, url: &Url, html: &str, status_code: u16, content_type: &str) -> Option<ScrapeResponse> {
+        self(url, html, status_code, content_type)
+    }
+}
+
+/// Extractors keyed by host regex; the first registered pattern matching the host wins
+#[derive(Clone, Default)]
+pub struct ExtractorRegistry {
+    entries: Vec<(Regex, Arc<dyn ContentExtractor>)>,
+}
+
+impl ExtractorRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `extractor` for hosts matching `host_pattern` (e.g. `^(www\.)?github\.com$`)
+    pub fn register(&mut self, host_pattern: &str, extractor: impl ContentExtractor + 'static) -> Result<()> {
+        let pattern = Regex::new(host_pattern).map_err(|e| anyhow!("Invalid extractor host pattern '{}': {}", host_pattern, e))?;
+        self.entries.push((pattern, Arc::new(extractor)));
+        Ok(())
+    }
+
+    /// Run the first extractor whose pattern matches the URL's host
+    pub fn extract(&self, url: &Url, html: &str, status_code: u16, content_type: &str) -> Option<ScrapeResponse> {
+        let host = url.host_str()?;
+        let (_, extractor) = self.entries.iter().find(|(pattern, _)| pattern.is_match(host))?;
+        extractor.extract(url, html, status_code, content_type)
+    }
+}
+
+impl std::fmt::Debug for ExtractorRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.entries.iter().map(|(pattern, _)| pattern.as_str())).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{response::Html, routing::get, Router};
+
+    #[tokio::test]
+    async fn test_registered_extractor_runs_before_generic_pipeline() {
+        let page = "<html><head><title>Generic title</title></head><body><p>Body text here.</p></body></html>";
+        let app = Router::new()
+            .route("/readme", get(move || async move { Html(page) }))
+            .route("/other", get(move || async move { Html(page) }));
+        let base = crate::test_support::serve(app).await;
+
+        let mut registry = ExtractorRegistry::new();
+        registry
+            .register(r"^127\.0\.0\.1$", |url: &Url, html: &str, _status: u16, _content_type: &str| {
+                if url.path() != "/readme" {
+                    return None;
+                }
+                let mut response = crate::test_support::empty_scrape_response(url.as_str());
+                response.title = "Custom README".to_string();
+                response.clean_content = format!("{} bytes of HTML", html.len());
+                Some(response)
+            })
+            .unwrap();
+        let scraper = crate::test_support::local_scraper().with_extractors(Arc::new(registry));
+
+        let custom = scraper.scrape_url(&format!("{}/readme", base)).await.unwrap();
+        assert_eq!(custom.title, "Custom README");
+        assert_eq!(custom.clean_content, format!("{} bytes of HTML", page.len()));
+
+        let generic = scraper.scrape_url(&format!("{}/other", base)).await.unwrap();
+        assert_eq!(generic.title, "Generic title");
+    }
+}
\ No newline at end of file
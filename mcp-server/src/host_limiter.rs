@@ -0,0 +1,81 @@
+//! Per-host share of the outbound permits. A request takes its host's permit before the global one,
+//! so a burst of URLs on one slow host queues behind that host's share instead of holding every
+//! global permit while requests to other hosts starve.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Concurrent outbound requests per host when `SCRAPE_PER_HOST_CONCURRENCY` is unset
+pub const DEFAULT_PER_HOST_CONCURRENCY: usize = 2;
+
+/// Idle hosts are dropped from the map once it tracks more hosts than this
+const PRUNE_ABOVE_HOSTS: usize = 1024;
+
+#[derive(Debug)]
+pub struct HostLimiter {
+    per_host: usize,
+    hosts: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl HostLimiter {
+    /// At most `per_host` concurrent outbound requests per host (at least 1)
+    pub fn new(per_host: usize) -> Self {
+        Self { per_host: per_host.max(1), hosts: Mutex::new(HashMap::new()) }
+    }
+
+    /// `SCRAPE_PER_HOST_CONCURRENCY` (or the older `OUTBOUND_PER_HOST_PERMITS`), defaulting to
+    /// [`DEFAULT_PER_HOST_CONCURRENCY`]
+    pub fn from_env() -> Self {
+        Self::new(
+            ["SCRAPE_PER_HOST_CONCURRENCY", "OUTBOUND_PER_HOST_PERMITS"]
+                .iter()
+                .find_map(|name| std::env::var(name).ok().and_then(|v| v.parse::<usize>().ok()))
+                .unwrap_or(DEFAULT_PER_HOST_CONCURRENCY),
+        )
+    }
+
+    /// Wait for a slot on `host`; held until the permit is dropped
+    pub async fn acquire(&self, host: &str) -> OwnedSemaphorePermit {
+        let semaphore = {
+            let mut hosts = self.hosts.lock().unwrap();
+            if hosts.len() > PRUNE_ABOVE_HOSTS {
+                // Nobody else holds or waits on an idle host's semaphore
+                hosts.retain(|_, s| Arc::strong_count(s) > 1 || s.available_permits() < self.per_host);
+            }
+            Arc::clone(hosts.entry(host.to_ascii_lowercase()).or_insert_with(|| Arc::new(Semaphore::new(self.per_host))))
+        };
+        semaphore.acquire_owned().await.expect("semaphore closed")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppState;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_other_hosts_proceed_while_one_is_saturated() {
+        let mut state = AppState::new("http://127.0.0.1:9".to_string(), reqwest::Client::new());
+        state.outbound_limit = Arc::new(Semaphore::new(4));
+        state.host_limiter = Arc::new(HostLimiter::new(2));
+        state.outbound_acquire_timeout = Duration::from_secs(5);
+        let state = Arc::new(state);
+
+        // Six requests to a slow host A that never finish: two hold permits, four queue on A's share
+        for i in 0..6 {
+            let state = Arc::clone(&state);
+            tokio::spawn(async move {
+                let _permit = state.acquire_outbound(Some(&format!("https://a.example/page/{}", i))).await;
+                std::future::pending::<()>().await;
+            });
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(state.outbound_limit.available_permits(), 2);
+
+        // Host B gets a global permit right away
+        let b = tokio::time::timeout(Duration::from_millis(200), state.acquire_outbound(Some("https://b.example/"))).await;
+        assert!(matches!(b, Ok(Ok(_))));
+    }
+}
\ No newline at end of file
@@ -4,16 +4,150 @@ pub mod types;
 pub mod mcp;
 pub mod rust_scraper;
 pub mod stdio_service;
+pub mod output;
+pub mod local_index;
+pub mod cache_ttl;
+pub mod retry;
+pub mod markdown;
+pub mod error;
+pub mod debug_sample;
+pub mod extractors;
+pub mod pagination;
+pub mod url_utils;
+pub mod dedup;
+pub mod extraction_log;
+pub mod host_limiter;
+pub mod validation;
+pub mod rerank;
+pub mod robots;
+pub mod watch;
+pub mod ssrf;
+
+#[cfg(test)]
+mod test_support;
 
 #[derive(Clone, Debug)]
 pub struct AppState {
     pub searxng_url: String,
     pub http_client: reqwest::Client,
     // Caches for performance
-    pub search_cache: moka::future::Cache<String, Vec<types::SearchResult>>, // key: query
-    pub scrape_cache: moka::future::Cache<String, types::ScrapeResponse>,     // key: url
+    pub search_cache: moka::future::Cache<String, types::SearchOutcome>,    // key: query + overrides
+    pub scrape_cache: moka::future::Cache<String, cache_ttl::CachedScrape>,   // key: url, adaptive TTL
+    pub cache_evictions: std::sync::Arc<CacheEvictions>, // entries dropped to stay within the byte budgets
+    pub scrape_history: moka::future::Cache<String, cache_ttl::ContentHistory>, // outlives scrape_cache entries
+    pub scrape_ttl: cache_ttl::TtlPolicy,
+    pub cache_enabled: bool, // DISABLE_CACHE=1 skips search_cache/scrape_cache reads and writes
     // Concurrency control for external calls
     pub outbound_limit: std::sync::Arc<tokio::sync::Semaphore>,
+    pub outbound_acquire_timeout: std::time::Duration, // OUTBOUND_ACQUIRE_TIMEOUT_MS; past it callers get 429
+    pub outbound_waiting: std::sync::Arc<std::sync::atomic::AtomicUsize>, // callers currently queued for a permit
+    pub host_limiter: std::sync::Arc<host_limiter::HostLimiter>, // per-host share taken before outbound_limit
+    pub allow_private_addresses: bool, // SCRAPER_ALLOW_PRIVATE_ADDRESSES; otherwise scrapes refuse internal addresses
+    // Inverted index over scraped pages, kept in sync with scrape_cache evictions
+    pub local_index: std::sync::Arc<local_index::LocalIndex>,
+    // Site-specific extractors tried before the generic pipeline; register before sharing the state
+    pub extractors: std::sync::Arc<extractors::ExtractorRegistry>,
+    // Per-domain JSON "load more" APIs (SCRAPE_PAGINATION_RULES) merged into feed-style pages
+    pub pagination_rules: std::sync::Arc<pagination::PaginationRules>,
+    // Rewrites queries before they reach SearXNG (SEARCH_QUERY_SUFFIX, or a custom hook)
+    pub query_rewriter: Option<search::QueryRewriter>,
+    // Per-scrape extraction metrics appended to EXTRACTION_LOG_PATH as JSONL
+    pub extraction_log: Option<extraction_log::ExtractionLog>,
+    // URLs re-scraped in the background by /watch (WATCH_MAX_WATCHES, WATCH_MIN_INTERVAL_SECS)
+    pub watches: std::sync::Arc<watch::WatchRegistry>,
+    pub started_at: std::time::Instant,
+}
+
+/// Default byte budgets of the caches (`SEARCH_CACHE_MAX_BYTES`, `SCRAPE_CACHE_MAX_BYTES`)
+pub const DEFAULT_SEARCH_CACHE_BYTES: u64 = 32 * 1024 * 1024;
+pub const DEFAULT_SCRAPE_CACHE_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Entries each cache evicted to stay within its byte budget
+#[derive(Debug, Default)]
+pub struct CacheEvictions {
+    pub search: std::sync::atomic::AtomicU64,
+    pub scrape: std::sync::atomic::AtomicU64,
+}
+
+fn env_bytes(name: &str, default: u64) -> u64 {
+    std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok()).filter(|b| *b > 0).unwrap_or(default)
+}
+
+/// Cache weight of an entry: the key plus the value's serialized JSON size, in bytes
+fn cache_weight<T: serde::Serialize>(key: &str, value: &T) -> u32 {
+    let value_bytes = serde_json::to_vec(value).map_or(0, |v| v.len());
+    u32::try_from(key.len() + value_bytes).unwrap_or(u32::MAX)
+}
+
+/// Search cache holding at most `max_bytes` of serialized outcomes for 10 minutes
+pub fn build_search_cache(max_bytes: u64, evictions: std::sync::Arc<CacheEvictions>) -> moka::future::Cache<String, types::SearchOutcome> {
+    moka::future::Cache::builder()
+        .weigher(|key: &String, outcome: &types::SearchOutcome| cache_weight(key, outcome))
+        .max_capacity(max_bytes)
+        .time_to_live(std::time::Duration::from_secs(60 * 10))
+        .eviction_listener(move |_, _, cause| {
+            if cause == moka::notification::RemovalCause::Size {
+                evictions.search.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            }
+        })
+        .build()
+}
+
+/// Scrape cache holding at most `max_bytes` of serialized responses (raw HTML included), with adaptive
+/// TTLs. LRU admission, since frequency-based admission would turn away large fresh pages for good.
+pub fn build_scrape_cache(
+    max_bytes: u64,
+    local_index: std::sync::Arc<local_index::LocalIndex>,
+    evictions: std::sync::Arc<CacheEvictions>,
+) -> moka::future::Cache<String, cache_ttl::CachedScrape> {
+    moka::future::Cache::builder()
+        .weigher(|key: &String, entry: &cache_ttl::CachedScrape| cache_weight(key, &entry.response))
+        .max_capacity(max_bytes)
+        .eviction_policy(moka::policy::EvictionPolicy::lru())
+        .expire_after(cache_ttl::AdaptiveExpiry)
+        .eviction_listener(move |url: std::sync::Arc<String>, _, cause| {
+            if cause == moka::notification::RemovalCause::Size {
+                evictions.scrape.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            }
+            // A replaced entry is re-indexed by the scrape that replaced it
+            if cause != moka::notification::RemovalCause::Replaced {
+                local_index.remove(&url);
+            }
+        })
+        .build()
+}
+
+/// Permits in `AppState::outbound_limit`
+pub const OUTBOUND_PERMITS: usize = 32;
+
+/// Per-request timeout shared by every outbound client (`HTTP_TIMEOUT_MS`, default 30s).
+/// Read once so all clients built during the process agree.
+pub fn http_timeout() -> std::time::Duration {
+    static TIMEOUT: std::sync::OnceLock<std::time::Duration> = std::sync::OnceLock::new();
+    *TIMEOUT.get_or_init(|| {
+        std::time::Duration::from_millis(
+            std::env::var("HTTP_TIMEOUT_MS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|ms| *ms > 0)
+                .unwrap_or(30_000),
+        )
+    })
+}
+
+/// Client builder with the shared `http_timeout` applied. Responses with malformed but readable
+/// header lines (obsolete line folding, stray spaces, invalid values) are accepted rather than failed.
+pub fn http_client_builder() -> reqwest::ClientBuilder {
+    http_client_builder_with_timeout(http_timeout())
+}
+
+/// [`http_client_builder`] with an explicit per-request timeout
+pub fn http_client_builder_with_timeout(timeout: std::time::Duration) -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .http1_allow_obsolete_multiline_headers_in_responses(true)
+        .http1_allow_spaces_after_header_name_in_responses(true)
+        .http1_ignore_invalid_headers_in_responses(true)
 }
 
 // Re-export AppState for easy access
@@ -21,18 +155,111 @@ pub use types::*;
 
 impl AppState {
     pub fn new(searxng_url: String, http_client: reqwest::Client) -> Self {
+        let local_index_max = std::env::var("LOCAL_INDEX_MAX_DOCS")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(1_000);
+        let local_index = std::sync::Arc::new(local_index::LocalIndex::new(local_index_max));
+        let index_for_evictions = std::sync::Arc::clone(&local_index);
+        let cache_evictions = std::sync::Arc::new(CacheEvictions::default());
         Self {
             searxng_url,
             http_client,
-            search_cache: moka::future::Cache::builder()
-                .max_capacity(10_000)
-                .time_to_live(std::time::Duration::from_secs(60 * 10))
-                .build(),
-            scrape_cache: moka::future::Cache::builder()
-                .max_capacity(10_000)
-                .time_to_live(std::time::Duration::from_secs(60 * 30))
+            search_cache: build_search_cache(
+                env_bytes("SEARCH_CACHE_MAX_BYTES", DEFAULT_SEARCH_CACHE_BYTES),
+                std::sync::Arc::clone(&cache_evictions),
+            ),
+            scrape_cache: build_scrape_cache(
+                env_bytes("SCRAPE_CACHE_MAX_BYTES", DEFAULT_SCRAPE_CACHE_BYTES),
+                index_for_evictions,
+                std::sync::Arc::clone(&cache_evictions),
+            ),
+            cache_evictions,
+            scrape_history: moka::future::Cache::builder()
+                .max_capacity(50_000)
+                .time_to_idle(std::time::Duration::from_secs(60 * 60 * 48))
                 .build(),
-            outbound_limit: std::sync::Arc::new(tokio::sync::Semaphore::new(32)),
+            scrape_ttl: cache_ttl::TtlPolicy::from_env(),
+            cache_enabled: !matches!(std::env::var("DISABLE_CACHE").as_deref(), Ok("1") | Ok("true")),
+            outbound_limit: std::sync::Arc::new(tokio::sync::Semaphore::new(OUTBOUND_PERMITS)),
+            outbound_acquire_timeout: std::time::Duration::from_millis(
+                std::env::var("OUTBOUND_ACQUIRE_TIMEOUT_MS")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(10_000),
+            ),
+            outbound_waiting: std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+            host_limiter: std::sync::Arc::new(host_limiter::HostLimiter::from_env()),
+            allow_private_addresses: ssrf::allow_private_addresses(),
+            local_index,
+            extractors: std::sync::Arc::new(extractors::ExtractorRegistry::new()),
+            pagination_rules: std::sync::Arc::new(pagination::PaginationRules::from_env()),
+            query_rewriter: search::QueryRewriter::from_env(),
+            extraction_log: extraction_log::ExtractionLog::from_env(),
+            watches: std::sync::Arc::new(watch::WatchRegistry::from_env()),
+            started_at: std::time::Instant::now(),
         }
     }
+
+    /// Wait a bounded time for an outbound slot: first the share of `url`'s host, then a global permit.
+    /// `None` (SearXNG, our own backend) only takes the global permit. On timeout returns the suggested
+    /// `Retry-After` delay.
+    pub async fn acquire_outbound(&self, url: Option<&str>) -> Result<OutboundPermit<'_>, std::time::Duration> {
+        use std::sync::atomic::Ordering;
+        let host = url.and_then(|u| url::Url::parse(u).ok()).and_then(|u| u.host_str().map(str::to_string));
+        self.outbound_waiting.fetch_add(1, Ordering::Relaxed);
+        let acquired = tokio::time::timeout(self.outbound_acquire_timeout, async {
+            let host_permit = match &host {
+                Some(host) => Some(self.host_limiter.acquire(host).await),
+                None => None,
+            };
+            let global = self.outbound_limit.acquire().await.expect("semaphore closed");
+            OutboundPermit { _global: global, _host: host_permit }
+        })
+        .await;
+        self.outbound_waiting.fetch_sub(1, Ordering::Relaxed);
+        acquired.map_err(|_| std::time::Duration::from_secs(self.outbound_acquire_timeout.as_secs().max(1)))
+    }
+}
+
+/// Global and per-host outbound slots, released together on drop
+#[derive(Debug)]
+pub struct OutboundPermit<'a> {
+    _global: tokio::sync::SemaphorePermit<'a>,
+    _host: Option<tokio::sync::OwnedSemaphorePermit>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_large_entry_evicts_small_ones_under_byte_budget() {
+        use crate::cache_ttl::{next_history, CachedScrape, TtlPolicy};
+        use std::sync::{atomic::Ordering, Arc};
+        let evictions = Arc::new(CacheEvictions::default());
+        let index = Arc::new(crate::local_index::LocalIndex::new(10));
+        let cache = build_scrape_cache(20_000, index, Arc::clone(&evictions));
+        let entry = |url: &str, html_bytes: usize| {
+            let mut response = crate::test_support::empty_scrape_response(url);
+            response.content = "x".repeat(html_bytes);
+            CachedScrape { response, history: next_history(None, 1, &TtlPolicy::from_env()) }
+        };
+
+        for i in 0..4 {
+            let url = format!("https://example.com/small/{}", i);
+            cache.insert(url.clone(), entry(&url, 2_000)).await;
+        }
+        cache.run_pending_tasks().await;
+        assert_eq!(cache.entry_count(), 4);
+        assert_eq!(evictions.scrape.load(Ordering::Relaxed), 0);
+
+        let big = "https://example.com/big".to_string();
+        cache.insert(big.clone(), entry(&big, 15_000)).await;
+        cache.run_pending_tasks().await;
+        assert!(cache.get(&big).await.is_some());
+        assert!(cache.weighted_size() <= 20_000, "{}", cache.weighted_size());
+        assert!(cache.entry_count() < 5);
+        assert!(evictions.scrape.load(Ordering::Relaxed) >= 2);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,203 @@
+use crate::types::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::RwLock;
+
+/// A previously scraped page as stored in the local index
+#[derive(Debug, Clone)]
+struct IndexedPage {
+    title: String,
+    snippet: String,
+    term_freqs: HashMap<String, u32>,
+}
+
+#[derive(Debug, Default)]
+struct IndexInner {
+    pages: HashMap<String, IndexedPage>,
+    postings: HashMap<String, HashSet<String>>, // term -> urls
+    order: VecDeque<String>,                    // insertion order for size-bound eviction
+}
+
+/// Bounded in-memory inverted index over successfully scraped pages
+#[derive(Debug)]
+pub struct LocalIndex {
+    inner: RwLock<IndexInner>,
+    max_pages: usize,
+}
+
+impl LocalIndex {
+    pub fn new(max_pages: usize) -> Self {
+        Self {
+            inner: RwLock::new(IndexInner::default()),
+            max_pages,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.read().map(|i| i.pages.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Index (or re-index) a scraped page; oldest pages are dropped once `max_pages` is reached
+    pub fn insert(&self, page: &ScrapeResponse) {
+        if self.max_pages == 0 {
+            return;
+        }
+        let mut term_freqs: HashMap<String, u32> = HashMap::new();
+        for text in [&page.title, &page.meta_description, &page.clean_content] {
+            for term in tokenize(text) {
+                *term_freqs.entry(term).or_insert(0) += 1;
+            }
+        }
+        if term_freqs.is_empty() {
+            return;
+        }
+        let snippet_source = if page.meta_description.trim().is_empty() {
+            &page.clean_content
+        } else {
+            &page.meta_description
+        };
+        let indexed = IndexedPage {
+            title: page.title.clone(),
+            snippet: snippet_source.chars().take(200).collect(),
+            term_freqs,
+        };
+
+        let Ok(mut inner) = self.inner.write() else { return };
+        remove_locked(&mut inner, &page.url);
+        while inner.pages.len() >= self.max_pages {
+            match inner.order.pop_front() {
+                Some(oldest) => remove_locked(&mut inner, &oldest),
+                None => break,
+            }
+        }
+        for term in indexed.term_freqs.keys() {
+            inner.postings.entry(term.clone()).or_default().insert(page.url.clone());
+        }
+        inner.order.push_back(page.url.clone());
+        inner.pages.insert(page.url.clone(), indexed);
+    }
+
+    /// Drop a page from the index (e.g. when it is evicted from the scrape cache)
+    pub fn remove(&self, url: &str) {
+        if let Ok(mut inner) = self.inner.write() {
+            remove_locked(&mut inner, url);
+        }
+    }
+
+    /// Return indexed pages matching any query term, best matches first
+    pub fn search(&self, query: &str, limit: usize) -> Vec<LocalSearchHit> {
+        let terms: HashSet<String> = tokenize(query).collect();
+        let Ok(inner) = self.inner.read() else { return Vec::new() };
+
+        let mut candidates: HashSet<&String> = HashSet::new();
+        for term in &terms {
+            if let Some(urls) = inner.postings.get(term) {
+                candidates.extend(urls.iter());
+            }
+        }
+
+        let mut hits: Vec<LocalSearchHit> = candidates
+            .into_iter()
+            .filter_map(|url| {
+                let page = inner.pages.get(url)?;
+                let matched = terms.iter().filter(|t| page.term_freqs.contains_key(*t)).count();
+                let tf: u32 = terms.iter().filter_map(|t| page.term_freqs.get(t)).sum();
+                // Pages matching more distinct terms always rank first; frequency breaks ties
+                let score = matched as f64 + (1.0 + tf as f64).ln() / 100.0;
+                Some(LocalSearchHit {
+                    url: url.clone(),
+                    title: page.title.clone(),
+                    snippet: page.snippet.clone(),
+                    score,
+                })
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.url.cmp(&b.url)));
+        hits.truncate(limit);
+        hits
+    }
+}
+
+fn remove_locked(inner: &mut IndexInner, url: &str) {
+    if let Some(page) = inner.pages.remove(url) {
+        for term in page.term_freqs.keys() {
+            if let Some(urls) = inner.postings.get_mut(term) {
+                urls.remove(url);
+                if urls.is_empty() {
+                    inner.postings.remove(term);
+                }
+            }
+        }
+        inner.order.retain(|u| u != url);
+    }
+}
+
+/// Lowercased alphanumeric terms of at least two characters
+pub(crate) fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.chars().count() >= 2)
+        .map(|t| t.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{scrape, test_support};
+    use axum::{response::Html, routing::get, Router};
+    use std::sync::Arc;
+
+    fn page(url: &str, title: &str, body: &str) -> ScrapeResponse {
+        let mut p = test_support::empty_scrape_response(url);
+        p.title = title.to_string();
+        p.clean_content = body.to_string();
+        p.word_count = body.split_whitespace().count();
+        p
+    }
+
+    #[test]
+    fn test_index_bounded_and_removable() {
+        let index = LocalIndex::new(2);
+        index.insert(&page("https://a.test/", "Alpha", "rust borrow checker"));
+        index.insert(&page("https://b.test/", "Beta", "python generators"));
+        index.insert(&page("https://c.test/", "Gamma", "rust async runtimes"));
+        assert_eq!(index.len(), 2);
+        // Oldest page was evicted to respect the bound
+        let hits = index.search("rust", 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].url, "https://c.test/");
+
+        index.remove("https://c.test/");
+        assert!(index.search("rust", 10).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_local_search_after_scrape() {
+        let app = Router::new()
+            .route("/ferris", get(|| async {
+                Html("<html><head><title>Ferris the crab</title></head><body><article><h1>Ferris</h1>\
+                <p>Ferris is the unofficial mascot of the Rust programming language community. \
+                The crab appears on stickers, shirts and countless conference slides around the world, \
+                and people love the friendly orange colour and the small waving claws that it has.</p></article></body></html>")
+            }))
+            .route("/gopher", get(|| async {
+                Html("<html><head><title>The Go gopher</title></head><body><article><h1>Gopher</h1>\
+                <p>The gopher is the mascot of the Go programming language, drawn by Renee French. \
+                It shows up on stickers, plush toys and conference slides, and it is known for \
+                its big round eyes and the cheerful blue colour used in most of its drawings.</p></article></body></html>")
+            }));
+        let base = test_support::serve(app).await;
+        let state = Arc::new(test_support::local_state("http://localhost:8888"));
+
+        scrape::scrape_url(&state, &format!("{}/ferris", base)).await.expect("scrape ferris");
+        scrape::scrape_url(&state, &format!("{}/gopher", base)).await.expect("scrape gopher");
+
+        let hits = state.local_index.search("crab", 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].url, format!("{}/ferris", base));
+        assert_eq!(hits[0].title, "Ferris the crab");
+        assert_eq!(state.local_index.search("mascot", 10).len(), 2);
+    }
+}
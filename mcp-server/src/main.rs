@@ -1,17 +1,18 @@
 use axum::{
-    extract::State,
-    http::StatusCode,
-    response::Json,
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
 use std::env;
 use std::sync::Arc;
+use tower_http::compression::CompressionLayer;
 use tower_http::cors::CorsLayer;
 use tower_http::trace::TraceLayer;
 use tracing::{info, warn, error};
 
-use mcp_server::{search, scrape, types::*, mcp, AppState};
+use mcp_server::{search, scrape, types::*, mcp, output::{self, ResponseFormat}, error, url_utils, dedup, rerank, watch, validation::Validated, AppState};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -28,39 +29,52 @@ async fn main() -> anyhow::Result<()> {
     info!("SearXNG URL: {}", searxng_url);
 
     // Create HTTP client
-    let http_client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()?;
+    let http_client = mcp_server::http_client_builder().build()?;
 
     // Create application state
-    let state = Arc::new(AppState {
-        searxng_url,
-        http_client,
-        search_cache: moka::future::Cache::builder().max_capacity(10_000).time_to_live(std::time::Duration::from_secs(60 * 10)).build(),
-        scrape_cache: moka::future::Cache::builder().max_capacity(10_000).time_to_live(std::time::Duration::from_secs(60 * 30)).build(),
-        outbound_limit: Arc::new(tokio::sync::Semaphore::new(32)),
-    });
+    let state = Arc::new(AppState::new(searxng_url, http_client));
+
+    let app = build_router(state, compression_enabled());
 
-    // Build router
+    // Start server
+    let listener = tokio::net::TcpListener::bind("0.0.0.0:5000").await?;
+    info!("MCP Server listening on http://0.0.0.0:5000");
+    
+    axum::serve(listener, app).await?;
+    
+    Ok(())
+}
+
+/// gzip/br response compression for clients that send `Accept-Encoding`; `HTTP_COMPRESSION=0` turns it off
+fn compression_enabled() -> bool {
+    !matches!(env::var("HTTP_COMPRESSION").as_deref(), Ok("0") | Ok("false"))
+}
+
+fn build_router(state: Arc<AppState>, compression: bool) -> Router {
     let app = Router::new()
         .route("/", get(health_check))
         .route("/health", get(health_check))
+        .route("/status", get(status_handler))
         .route("/search", post(search_web_handler))
         .route("/scrape", post(scrape_url_handler))
+        .route("/scrape/diff", post(scrape_diff_handler))
         .route("/chat", post(chat_handler))
+        .route("/local_search", post(local_search_handler))
+        .route("/rerank", post(rerank_handler))
+        .route("/watch", post(create_watch_handler))
+        .route("/watch/:id", get(get_watch_handler).delete(delete_watch_handler))
+        .route("/batch_metadata", post(batch_metadata_handler))
+        .route("/normalize_url", post(normalize_url_handler))
         .route("/mcp/tools", get(mcp::list_tools))
         .route("/mcp/call", post(mcp::call_tool))
         .layer(CorsLayer::permissive())
         .layer(TraceLayer::new_for_http())
         .with_state(state);
-
-    // Start server
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:5000").await?;
-    info!("MCP Server listening on http://0.0.0.0:5000");
-    
-    axum::serve(listener, app).await?;
-    
-    Ok(())
+    if compression {
+        app.layer(CompressionLayer::new())
+    } else {
+        app
+    }
 }
 
 async fn health_check() -> Json<serde_json::Value> {
@@ -71,51 +85,259 @@ async fn health_check() -> Json<serde_json::Value> {
     }))
 }
 
+async fn status_handler(State(state): State<Arc<AppState>>) -> Json<StatusResponse> {
+    let available = state.outbound_limit.available_permits();
+    Json(StatusResponse {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        uptime_ms: state.started_at.elapsed().as_millis() as u64,
+        outbound_available: available,
+        outbound_in_flight: mcp_server::OUTBOUND_PERMITS.saturating_sub(available),
+        outbound_queued: state.outbound_waiting.load(std::sync::atomic::Ordering::Relaxed),
+        search_cache_entries: state.search_cache.entry_count(),
+        scrape_cache_entries: state.scrape_cache.entry_count(),
+        search_cache_bytes: state.search_cache.weighted_size(),
+        scrape_cache_bytes: state.scrape_cache.weighted_size(),
+        search_cache_evictions: state.cache_evictions.search.load(std::sync::atomic::Ordering::Relaxed),
+        scrape_cache_evictions: state.cache_evictions.scrape.load(std::sync::atomic::Ordering::Relaxed),
+        local_index_docs: state.local_index.len(),
+    })
+}
+
 async fn search_web_handler(
     State(state): State<Arc<AppState>>,
-    Json(request): Json<SearchRequest>,
-) -> Result<Json<SearchResponse>, (StatusCode, Json<ErrorResponse>)> {
-    match search::search_web(&state, &request.query).await {
-        Ok(results) => Ok(Json(SearchResponse { results })),
+    format: ResponseFormat,
+    Validated(request): Validated<SearchRequest>,
+) -> Result<Response, Response> {
+    let overrides = search::SearchParamOverrides {
+        normalize_urls: request.normalize_urls,
+        published_after: request.published_after.clone(),
+        published_before: request.published_before.clone(),
+        drop_undated: request.drop_undated,
+        template_filter: request.template_filter.clone(),
+        max_pages: request.max_pages,
+        follow_up_below: request.follow_up_below,
+        ..Default::default()
+    };
+    if let Some(fields) = &request.fields {
+        output::validate_fields::<SearchResult>(fields, OPTIONAL_RESULT_FIELDS).map_err(bad_request)?;
+    }
+    if request.debug_request.unwrap_or(false) {
+        return Ok(Json(search::debug_search_request(&state, &request.query, Some(overrides))).into_response());
+    }
+    let outcome = search::search_web_outcome(&state, &request.query, Some(overrides)).await;
+    let outcome = match outcome {
+        Ok(mut outcome) if !request.urls_only.unwrap_or(false) => {
+            if request.resolve_titles.unwrap_or(false) {
+                let top = request.resolve_top.unwrap_or(scrape::DEFAULT_RESOLVED_TITLES);
+                scrape::resolve_result_titles(&state, &mut outcome.results, top).await;
+            }
+            if request.excerpts.unwrap_or(false) {
+                let top = request.excerpt_top.unwrap_or(scrape::DEFAULT_EXCERPTS);
+                scrape::attach_excerpts(&state, &request.query, &mut outcome.results, top).await;
+            }
+            Ok(outcome)
+        }
+        other => other,
+    };
+    match outcome {
+        Ok(outcome) if request.urls_only.unwrap_or(false) => {
+            let urls: Vec<String> = outcome.results.into_iter().map(|r| r.url).collect();
+            Ok(Json(urls).into_response())
+        }
+        Ok(outcome) => match format {
+            ResponseFormat::Csv => Ok((
+                [(header::CONTENT_TYPE, "text/csv; charset=utf-8")],
+                output::search_results_to_csv(&outcome.results),
+            )
+                .into_response()),
+            ResponseFormat::Json | ResponseFormat::MessagePack => {
+                let response = search::build_search_response(outcome, request.suggestions_on_empty.unwrap_or(true));
+                let Some(fields) = &request.fields else { return output::encode(format, &response).map_err(error_response) };
+                let mut value = serde_json::to_value(&response).map_err(|e| error_response(e.into()))?;
+                if let Some(results) = value.get_mut("results").and_then(|r| r.as_array_mut()) {
+                    for result in results.iter_mut() {
+                        *result = output::project_fields(result.take(), fields);
+                    }
+                }
+                output::encode(format, &value).map_err(error_response)
+            }
+        },
         Err(e) => {
             error!("Search error: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: e.to_string(),
-                }),
-            ))
+            Err(error_response(e))
         }
     }
 }
 
+/// Map a handler error to a response: backpressure becomes 429 with `Retry-After`, bad input 400, anything else 500
+fn error_response(e: anyhow::Error) -> Response {
+    let body = Json(ErrorResponse { error: e.to_string() });
+    match error::retry_after(&e) {
+        Some(retry_after) => (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, retry_after.as_secs().to_string())],
+            body,
+        )
+            .into_response(),
+        None if error::is_invalid_input(&e) => (StatusCode::BAD_REQUEST, body).into_response(),
+        None => (StatusCode::INTERNAL_SERVER_ERROR, body).into_response(),
+    }
+}
+
+/// `ScrapeResponse` fields left out of a default response but valid in a `fields` projection
+const OPTIONAL_SCRAPE_FIELDS: &[&str] = &["document", "chapter_urls", "language_segments", "author_profile", "inline_state", "blocks", "vcard", "structured_data", "markdown_content"];
+/// `SearchResult` fields likewise omitted when unset
+const OPTIONAL_RESULT_FIELDS: &[&str] = &["raw_url", "published_date", "template", "resolved_title", "favicon_url", "excerpt", "follow_up_query"];
+
+fn bad_request(error: String) -> Response {
+    (StatusCode::BAD_REQUEST, Json(ErrorResponse { error })).into_response()
+}
+
 async fn scrape_url_handler(
     State(state): State<Arc<AppState>>,
-    Json(request): Json<ScrapeRequest>,
-) -> Result<Json<ScrapeResponse>, (StatusCode, Json<ErrorResponse>)> {
-    match scrape::scrape_url(&state, &request.url).await {
-        Ok(content) => Ok(Json(content)),
+    format: ResponseFormat,
+    Validated(request): Validated<ScrapeRequest>,
+) -> Result<Response, Response> {
+    if let Some(fields) = &request.fields {
+        output::validate_fields::<ScrapeResponse>(fields, OPTIONAL_SCRAPE_FIELDS).map_err(bad_request)?;
+    }
+    let as_article = request.as_article.unwrap_or(false);
+    if as_article && request.fields.is_some() {
+        return Err(bad_request("as_article cannot be combined with fields".to_string()));
+    }
+    let overrides = scrape::ScrapeParamOverrides {
+        preserve_whitespace: request.preserve_whitespace,
+        as_document: request.as_document,
+        max_paragraphs: request.max_paragraphs,
+        follow_next: request
+            .follow_next
+            .unwrap_or(false)
+            .then(|| request.max_pages.unwrap_or(scrape::DEFAULT_BOOK_PAGES)),
+        language_segments: request.language_segments,
+        raw_urls: request.raw_urls,
+        scraper_order: request.scraper_order,
+        inline_state: request.inline_state,
+        blocks: request.blocks,
+        vcard: request.vcard,
+        structured_data: request.structured_data,
+        markdown: request.format.map(|content_format| content_format == ContentFormat::Markdown),
+        login: request.login.clone(),
+        ..Default::default()
+    };
+    match scrape::scrape_url_with_params(&state, &request.url, Some(overrides)).await {
+        Ok(content) if as_article => Ok((
+            [(header::CONTENT_TYPE, "application/ld+json")],
+            Json(output::scrape_to_article(&content)),
+        )
+            .into_response()),
+        Ok(content) => match &request.fields {
+            Some(fields) => {
+                let value = serde_json::to_value(&content).map_err(|e| error_response(e.into()))?;
+                output::encode(format, &output::project_fields(value, fields)).map_err(error_response)
+            }
+            None => output::encode(format, &content).map_err(error_response),
+        },
         Err(e) => {
             error!("Scrape error: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: e.to_string(),
-                }),
-            ))
+            Err(error_response(e))
+        }
+    }
+}
+
+async fn scrape_diff_handler(
+    State(state): State<Arc<AppState>>,
+    Validated(request): Validated<ScrapeDiffRequest>,
+) -> Result<Json<ScrapeDiffResponse>, Response> {
+    match scrape::scrape_diff(&state, &request.url, &request.previous_content).await {
+        Ok(diff) => Ok(Json(diff)),
+        Err(e) => {
+            error!("Scrape diff error: {}", e);
+            Err(error_response(e))
         }
     }
 }
 
+async fn rerank_handler(Validated(request): Validated<RerankRequest>) -> Json<RerankResponse> {
+    Json(RerankResponse { results: rerank::rerank(&request.query, request.results) })
+}
+
+async fn local_search_handler(
+    State(state): State<Arc<AppState>>,
+    Validated(request): Validated<LocalSearchRequest>,
+) -> Json<LocalSearchResponse> {
+    let limit = request.limit.unwrap_or(10).min(100);
+    Json(LocalSearchResponse {
+        results: state.local_index.search(&request.query, limit),
+    })
+}
+
+async fn create_watch_handler(
+    State(state): State<Arc<AppState>>,
+    Validated(request): Validated<WatchRequest>,
+) -> Result<(StatusCode, Json<WatchSnapshot>), Response> {
+    match watch::start_watch(&state, &request.url, request.interval_secs) {
+        Ok(snapshot) => Ok((StatusCode::CREATED, Json(snapshot))),
+        Err(e) => Err((StatusCode::TOO_MANY_REQUESTS, Json(ErrorResponse { error: e.to_string() })).into_response()),
+    }
+}
+
+async fn get_watch_handler(State(state): State<Arc<AppState>>, Path(id): Path<String>) -> Result<Json<WatchSnapshot>, Response> {
+    state.watches.get(&id).map(Json).ok_or_else(|| watch_not_found(&id))
+}
+
+async fn delete_watch_handler(State(state): State<Arc<AppState>>, Path(id): Path<String>) -> Result<StatusCode, Response> {
+    if state.watches.remove(&id) {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(watch_not_found(&id))
+    }
+}
+
+fn watch_not_found(id: &str) -> Response {
+    (StatusCode::NOT_FOUND, Json(ErrorResponse { error: format!("No watch with id '{}'", id) })).into_response()
+}
+
+async fn normalize_url_handler(Json(request): Json<NormalizeUrlRequest>) -> Result<Json<NormalizeUrlResponse>, Response> {
+    let normalized = match request.base.as_deref().map(url::Url::parse) {
+        Some(Ok(base)) => url_utils::normalize_url_with_base(&base, &request.url),
+        Some(Err(e)) => Err(error::ScrapeError::InvalidUrl(format!("Invalid base URL: {}", e)).into()),
+        None => url_utils::normalize_url(&request.url),
+    };
+    match normalized {
+        Ok(normalized) => Ok(Json(NormalizeUrlResponse { url: request.url, normalized })),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Upper bound on URLs per `/batch_metadata` request
+const MAX_BATCH_METADATA_URLS: usize = 50;
+
+async fn batch_metadata_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<BatchMetadataRequest>,
+) -> Result<Json<BatchMetadataResponse>, Response> {
+    if request.urls.len() > MAX_BATCH_METADATA_URLS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!("At most {} URLs per request", MAX_BATCH_METADATA_URLS),
+            }),
+        )
+            .into_response());
+    }
+    let results = scrape::fetch_metadata_batch(&state, request.urls).await;
+    Ok(Json(BatchMetadataResponse { results }))
+}
+
 async fn chat_handler(
     State(state): State<Arc<AppState>>,
-    Json(request): Json<ChatRequest>,
+    Validated(request): Validated<ChatRequest>,
 ) -> Result<Json<ChatResponse>, (StatusCode, Json<ErrorResponse>)> {
     info!("Processing chat request: {}", request.query);
     
     // Step 1: Search for relevant URLs
-    let search_results = match search::search_web(&state, &request.query).await {
-        Ok(results) => results,
+    let outcome = match search::search_web_outcome(&state, &request.query, None).await {
+        Ok(outcome) => outcome,
         Err(e) => {
             error!("Search failed: {}", e);
             return Err((
@@ -127,7 +349,22 @@ async fn chat_handler(
         }
     };
     
+    let search_results = outcome.results;
     info!("Found {} search results", search_results.len());
+
+    // A calculator/conversion/infobox answer is the response itself; no need to scrape
+    let use_direct_answer = request.direct_answer.unwrap_or_else(|| {
+        matches!(env::var("CHAT_DIRECT_ANSWERS").as_deref(), Ok("1") | Ok("true"))
+    });
+    if let Some(answer) = outcome.direct_answer.filter(|_| use_direct_answer) {
+        info!("Answering '{}' from SearXNG's direct answer", request.query);
+        return Ok(Json(ChatResponse {
+            response: answer.clone(),
+            search_results,
+            scraped_content: Vec::new(),
+            direct_answer: Some(answer),
+        }));
+    }
     
     // Step 2: Scrape top results concurrently (limit to 5)
     let top_n = std::env::var("CHAT_SCRAPE_TOP_N").ok().and_then(|v| v.parse::<usize>().ok()).unwrap_or(5);
@@ -152,6 +389,9 @@ async fn chat_handler(
             Err(e) => warn!("Scrape task join error: {}", e),
         }
     }
+    if let Some(min_similarity) = request.dedup_similarity.or_else(dedup::min_similarity_from_env) {
+        scraped_content = dedup::dedup_scrapes(scraped_content, min_similarity);
+    }
     
     // Step 3: Generate response based on scraped content
     let response_text = if scraped_content.is_empty() {
@@ -181,5 +421,196 @@ async fn chat_handler(
         response: response_text,
         search_results,
         scraped_content,
+        direct_answer: None,
     }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serve `app` on an ephemeral localhost port and return its base URL
+    async fn serve(app: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.ok() });
+        format!("http://{}", addr)
+    }
+
+    /// State whose scrapes may reach the fixtures `serve` starts on 127.0.0.1
+    fn local_state(searxng_url: String) -> Arc<AppState> {
+        let mut state = AppState::new(searxng_url, reqwest::Client::new());
+        state.allow_private_addresses = true;
+        Arc::new(state)
+    }
+
+    #[tokio::test]
+    async fn test_saturated_outbound_limit_returns_429() {
+        let mut state = AppState::new("http://127.0.0.1:9".to_string(), reqwest::Client::new());
+        state.outbound_limit = Arc::new(tokio::sync::Semaphore::new(0));
+        state.outbound_acquire_timeout = std::time::Duration::from_millis(20);
+        let state = Arc::new(state);
+
+        let request = ScrapeRequest {
+            url: "http://127.0.0.1:9/page".to_string(),
+            ..Default::default()
+        };
+        let response = scrape_url_handler(State(Arc::clone(&state)), ResponseFormat::Json, Validated(request)).await.unwrap_err();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+
+        let request = SearchRequest { query: "rust".to_string(), ..Default::default() };
+        let response = search_web_handler(State(state), ResponseFormat::Json, Validated(request)).await.unwrap_err();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn test_status_reports_runtime_stats() {
+        let state = Arc::new(AppState::new("http://127.0.0.1:9".to_string(), reqwest::Client::new()));
+        let _held = state.outbound_limit.acquire().await.unwrap();
+
+        let Json(first) = status_handler(State(Arc::clone(&state))).await;
+        let value = serde_json::to_value(&first).unwrap();
+        for field in ["version", "uptime_ms", "outbound_available", "outbound_in_flight", "outbound_queued", "search_cache_entries", "scrape_cache_entries", "local_index_docs"] {
+            assert!(value.get(field).is_some(), "missing {}", field);
+        }
+        assert_eq!(first.outbound_in_flight, 1);
+        assert_eq!(first.outbound_available, mcp_server::OUTBOUND_PERMITS - 1);
+
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        let Json(second) = status_handler(State(Arc::clone(&state))).await;
+        assert!(second.uptime_ms > first.uptime_ms);
+    }
+
+    #[tokio::test]
+    async fn test_batch_metadata_isolates_failures() {
+        let page = r#"<html><head><title>Fallback</title>
+            <meta property="og:title" content="Card title">
+            <meta name="description" content="Short summary">
+            <meta property="og:image" content="/img/card.png">
+            <meta property="og:site_name" content="Example News">
+            <link rel="shortcut icon" href="/static/icon.png">
+            </head><body><p>Body is never needed</p></body></html>"#;
+        let app = Router::new().route("/article", get(move || async move { axum::response::Html(page) }));
+        let base = serve(app).await;
+        let state = local_state("http://127.0.0.1:9".to_string());
+
+        let request = BatchMetadataRequest {
+            urls: vec![format!("{}/article", base), "ftp://example.com/file".to_string()],
+        };
+        let Json(response) = batch_metadata_handler(State(state), Json(request)).await.unwrap();
+        assert_eq!(response.results.len(), 2);
+
+        let good = response.results[0].metadata.as_ref().expect("metadata for good URL");
+        assert_eq!(good.title, "Card title");
+        assert_eq!(good.description.as_deref(), Some("Short summary"));
+        assert_eq!(good.og_image, Some(format!("{}/img/card.png", base)));
+        assert_eq!(good.favicon, Some(format!("{}/static/icon.png", base)));
+        assert_eq!(good.publisher.as_deref(), Some("Example News"));
+
+        assert!(response.results[1].metadata.is_none());
+        assert!(response.results[1].error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_responses_compressed_when_client_accepts_gzip() {
+        use std::io::Read;
+        let state = Arc::new(AppState::new("http://127.0.0.1:9".to_string(), reqwest::Client::new()));
+        let base = serve(build_router(state, true)).await;
+        // Decompress by hand so the Content-Encoding header stays visible
+        let client = reqwest::Client::builder().no_gzip().no_brotli().build().unwrap();
+
+        let response = client.get(format!("{}/status", base)).header(reqwest::header::ACCEPT_ENCODING, "gzip").send().await.unwrap();
+        assert_eq!(response.headers()[reqwest::header::CONTENT_ENCODING], "gzip");
+        let mut json = String::new();
+        flate2::read::GzDecoder::new(&response.bytes().await.unwrap()[..]).read_to_string(&mut json).unwrap();
+        assert!(json.contains("\"uptime_ms\""), "{}", json);
+
+        let plain = client.get(format!("{}/status", base)).send().await.unwrap();
+        assert!(plain.headers().get(reqwest::header::CONTENT_ENCODING).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_urls_only_returns_bare_url_array() {
+        let searxng = Router::new().route("/search", get(|| async {
+            Json(serde_json::json!({
+                "query": "rust",
+                "number_of_results": 2,
+                "results": [
+                    {"url": "https://www.rust-lang.org/", "title": "Rust", "content": "A language", "engine": "duckduckgo"},
+                    {"url": "https://doc.rust-lang.org/book/", "title": "The Book", "content": "Learn Rust", "engine": "google"}
+                ]
+            }))
+        }));
+        let state = Arc::new(AppState::new(serve(searxng).await, reqwest::Client::new()));
+
+        let request = SearchRequest { query: "rust".to_string(), urls_only: Some(true), ..Default::default() };
+        let response = search_web_handler(State(state), ResponseFormat::Json, Validated(request)).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let urls: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(urls, serde_json::json!(["https://www.rust-lang.org/", "https://doc.rust-lang.org/book/"]));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_titles_enriches_top_results() {
+        let site = Router::new()
+            .route("/a", get(|| async { axum::response::Html("<html><head><title>Alpha, the full title</title><link rel=\"icon\" href=\"/a.png\"></head></html>") }))
+            .route("/b", get(|| async { axum::response::Html("<html><head><title>Bravo</title></head></html>") }))
+            .route("/c", get(|| async { axum::response::Html("<html><head><title>Charlie</title></head></html>") }));
+        let site = serve(site).await;
+        let results: Vec<serde_json::Value> = ["a", "b", "c"]
+            .iter()
+            .map(|p| serde_json::json!({"url": format!("{}/{}", site, p), "title": "Alpha, the fu...", "content": "", "engine": "google"}))
+            .collect();
+        let payload = serde_json::json!({"query": "rust", "number_of_results": 3, "results": results});
+        let searxng = Router::new().route("/search", get(move || async move { Json(payload) }));
+        let state = local_state(serve(searxng).await);
+
+        let request = SearchRequest { query: "rust".to_string(), resolve_titles: Some(true), resolve_top: Some(2), ..Default::default() };
+        let response = search_web_handler(State(state), ResponseFormat::Json, Validated(request)).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let results = value["results"].as_array().unwrap();
+        assert_eq!(results[0]["resolved_title"], "Alpha, the full title");
+        assert_eq!(results[0]["favicon_url"], format!("{}/a.png", site));
+        assert_eq!(results[1]["resolved_title"], "Bravo");
+        assert_eq!(results[1]["favicon_url"], format!("{}/favicon.ico", site));
+        assert!(results[2].get("resolved_title").is_none());
+        assert!(results[2].get("favicon_url").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fields_projection_keeps_only_requested_fields() {
+        let page = "<html><head><title>Projected</title></head><body><p>Only some fields are returned here.</p></body></html>";
+        let site = Router::new().route("/page", get(move || async move { axum::response::Html(page) }));
+        let base = serve(site).await;
+        let state = local_state("http://127.0.0.1:9".to_string());
+
+        let fields = vec!["title".to_string(), "clean_content".to_string()];
+        let request = ScrapeRequest { url: format!("{}/page", base), fields: Some(fields), ..Default::default() };
+        let response = scrape_url_handler(State(Arc::clone(&state)), ResponseFormat::Json, Validated(request)).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let mut keys: Vec<&String> = value.as_object().unwrap().keys().collect();
+        keys.sort();
+        assert_eq!(keys, ["clean_content", "title"]);
+        assert_eq!(value["title"], "Projected");
+
+        let request = ScrapeRequest { url: format!("{}/page", base), fields: Some(vec!["titel".to_string()]), ..Default::default() };
+        let response = scrape_url_handler(State(state), ResponseFormat::Json, Validated(request)).await.unwrap_err();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+    #[tokio::test]
+    async fn test_local_search_rejects_long_queries() {
+        let state = Arc::new(AppState::new("http://127.0.0.1:9".to_string(), reqwest::Client::new()));
+        let base = serve(build_router(state, false)).await;
+        let client = reqwest::Client::new();
+
+        let response = client.post(format!("{}/local_search", base)).json(&serde_json::json!({ "query": "rust ".repeat(1_000) })).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+        assert!(response.text().await.unwrap().contains("the limit is 2000"));
+
+        let response = client.post(format!("{}/local_search", base)).json(&serde_json::json!({ "query": "rust" })).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+    }
 }
\ No newline at end of file
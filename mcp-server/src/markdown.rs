@@ -0,0 +1,446 @@
+//! Minimal HTML-to-markdown rendering that keeps document structure (headings, lists, code, quotes).
+
+use crate::types::{Block, BlockKind};
+use scraper::{ElementRef, Node};
+use url::Url;
+
+/// Elements that never contribute readable content
+const SKIPPED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "svg", "canvas", "iframe", "form", "button", "nav", "footer", "aside", "template",
+];
+
+/// Render an element subtree as markdown. `skip` can drop additional boilerplate elements.
+pub fn html_to_markdown(root: ElementRef, skip: &dyn Fn(&ElementRef) -> bool) -> String {
+    render(root, skip, None)
+}
+
+/// Like [`html_to_markdown`], but links become `[text](url)` and images `![alt](url)`, with relative
+/// URLs resolved against `base`
+pub fn html_to_markdown_with_links(root: ElementRef, skip: &dyn Fn(&ElementRef) -> bool, base: &Url) -> String {
+    render(root, skip, Some(base))
+}
+
+fn render(root: ElementRef, skip: &dyn Fn(&ElementRef) -> bool, base: Option<&Url>) -> String {
+    let mut writer = MarkdownWriter {
+        out: String::new(),
+        prefix: Vec::new(),
+        blank_line: None,
+        pending_space: false,
+        fresh_item: false,
+        list_depth: 0,
+        skip,
+        base,
+    };
+    writer.children(root);
+    writer.out.trim().to_string()
+}
+
+struct MarkdownWriter<'a> {
+    out: String,
+    /// Line prefixes for nested blockquotes and list continuation indentation
+    prefix: Vec<&'static str>,
+    /// Blank separator owed before the next line, carrying the prefix in effect when the block ended
+    blank_line: Option<String>,
+    pending_space: bool,
+    /// A list marker was just written; the item's first block continues on the marker line
+    fresh_item: bool,
+    list_depth: usize,
+    skip: &'a dyn Fn(&ElementRef) -> bool,
+    /// Render links and images against this base; without one only their text is kept
+    base: Option<&'a Url>,
+}
+
+impl MarkdownWriter<'_> {
+    fn at_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with('\n')
+    }
+
+    /// Begin a new output line, emitting a pending blank separator and the current prefix
+    fn start_line(&mut self) {
+        if let Some(blank) = self.blank_line.take() {
+            if !self.out.is_empty() {
+                self.out.push_str(blank.trim_end());
+                self.out.push('\n');
+            }
+        }
+        self.pending_space = false;
+        self.out.push_str(&self.prefix.concat());
+    }
+
+    fn end_line(&mut self) {
+        if self.fresh_item {
+            return;
+        }
+        let trimmed = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed);
+        if !self.at_line_start() {
+            self.out.push('\n');
+        }
+        self.pending_space = false;
+    }
+
+    /// End the current block; the next block is separated by a blank line (a plain line break inside lists)
+    fn block_break(&mut self) {
+        self.end_line();
+        if self.list_depth == 0 {
+            // Use the outermost prefix seen, so entering or leaving a quote yields an unprefixed separator
+            let prefix = self.prefix.concat();
+            if self.blank_line.as_ref().is_none_or(|b| b.len() > prefix.len()) {
+                self.blank_line = Some(prefix);
+            }
+        }
+    }
+
+    fn text(&mut self, raw: &str) {
+        let words: Vec<&str> = raw.split_whitespace().collect();
+        if words.is_empty() {
+            if !raw.is_empty() {
+                self.pending_space = true;
+            }
+            return;
+        }
+        if self.at_line_start() {
+            self.start_line();
+        } else if self.fresh_item {
+            self.fresh_item = false;
+        } else if self.pending_space || raw.starts_with(char::is_whitespace) {
+            self.out.push(' ');
+        }
+        self.out.push_str(&words.join(" "));
+        self.pending_space = raw.ends_with(char::is_whitespace);
+    }
+
+    /// `raw` resolved against the base, with parentheses and spaces escaped for a link destination.
+    /// `None` for fragment-only, `javascript:` and `data:` URLs, which make no sense outside the page.
+    fn resolve(&self, raw: &str) -> Option<String> {
+        let base = self.base?;
+        let raw = raw.trim();
+        let lower = raw.to_ascii_lowercase();
+        if raw.is_empty() || raw.starts_with('#') || lower.starts_with("javascript:") || lower.starts_with("data:") {
+            return None;
+        }
+        let url = base.join(raw).map_or_else(|_| raw.to_string(), |u| u.to_string());
+        Some(url.replace(' ', "%20").replace('(', "%28").replace(')', "%29"))
+    }
+
+    fn children(&mut self, el: ElementRef) {
+        for child in el.children() {
+            match child.value() {
+                Node::Text(t) => self.text(t),
+                Node::Element(_) => {
+                    if let Some(child_el) = ElementRef::wrap(child) {
+                        self.element(child_el);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn element(&mut self, el: ElementRef) {
+        let tag = el.value().name();
+        if SKIPPED_TAGS.contains(&tag) || (self.skip)(&el) {
+            return;
+        }
+        match tag {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let text = collapse(&el.text().collect::<String>());
+                if text.is_empty() {
+                    return;
+                }
+                let level = tag[1..].parse::<usize>().unwrap_or(1);
+                self.block_break();
+                self.start_line();
+                self.out.push_str(&"#".repeat(level));
+                self.out.push(' ');
+                self.out.push_str(&text);
+                self.block_break();
+            }
+            "pre" => {
+                let code = el.text().collect::<String>();
+                let code = code.trim_matches('\n');
+                if code.trim().is_empty() {
+                    return;
+                }
+                let language = code_language(el).unwrap_or_default();
+                self.block_break();
+                self.start_line();
+                self.out.push_str("```");
+                self.out.push_str(&language);
+                self.out.push('\n');
+                for line in code.lines() {
+                    self.out.push_str(&self.prefix.concat());
+                    self.out.push_str(line.trim_end());
+                    self.out.push('\n');
+                }
+                self.out.push_str(&self.prefix.concat());
+                self.out.push_str("```");
+                self.block_break();
+            }
+            "code" => {
+                let code = collapse(&el.text().collect::<String>());
+                if !code.is_empty() {
+                    self.text(&format!("`{}`", code));
+                }
+            }
+            "blockquote" => {
+                self.block_break();
+                self.prefix.push("> ");
+                self.children(el);
+                self.end_line();
+                self.prefix.pop();
+                self.block_break();
+            }
+            "ul" | "ol" => {
+                self.block_break();
+                self.list_depth += 1;
+                let mut index = 0;
+                for item in el.children().filter_map(ElementRef::wrap) {
+                    if item.value().name() != "li" {
+                        self.element(item);
+                        continue;
+                    }
+                    index += 1;
+                    self.end_line();
+                    self.start_line();
+                    if tag == "ol" {
+                        self.out.push_str(&format!("{}. ", index));
+                    } else {
+                        self.out.push_str("- ");
+                    }
+                    self.fresh_item = true;
+                    self.prefix.push("  ");
+                    self.children(item);
+                    self.prefix.pop();
+                    self.fresh_item = false;
+                    self.end_line();
+                }
+                self.list_depth -= 1;
+                self.block_break();
+            }
+            "table" => {
+                self.block_break();
+                let Ok(row_sel) = scraper::Selector::parse("tr") else { return };
+                let Ok(cell_sel) = scraper::Selector::parse("th, td") else { return };
+                for (i, row) in el.select(&row_sel).enumerate() {
+                    let cells: Vec<String> = row.select(&cell_sel).map(|c| collapse(&c.text().collect::<String>())).collect();
+                    if cells.is_empty() {
+                        continue;
+                    }
+                    self.start_line();
+                    self.out.push_str(&format!("| {} |\n", cells.join(" | ")));
+                    if i == 0 {
+                        self.start_line();
+                        self.out.push_str(&format!("|{}\n", " --- |".repeat(cells.len())));
+                    }
+                }
+                self.block_break();
+            }
+            "hr" => {
+                self.block_break();
+                self.start_line();
+                self.out.push_str("---");
+                self.block_break();
+            }
+            "br" => self.end_line(),
+            "a" => {
+                let text = collapse(&el.text().collect::<String>());
+                match el.value().attr("href").and_then(|href| self.resolve(href)) {
+                    Some(url) if !text.is_empty() => self.text(&format!("[{}]({})", escape_brackets(&text), url)),
+                    _ => self.children(el),
+                }
+            }
+            "img" => {
+                if let Some(url) = el.value().attr("src").and_then(|src| self.resolve(src)) {
+                    let alt = collapse(el.value().attr("alt").unwrap_or(""));
+                    self.text(&format!("![{}]({})", escape_brackets(&alt), url));
+                }
+            }
+            "p" | "div" | "section" | "article" | "main" | "header" | "figure" | "figcaption" | "dl" | "dt"
+            | "dd" | "details" | "summary" => {
+                self.block_break();
+                self.children(el);
+                self.block_break();
+            }
+            _ => self.children(el),
+        }
+    }
+}
+
+fn escape_brackets(text: &str) -> String {
+    text.replace('[', "\\[").replace(']', "\\]")
+}
+
+/// Language hint for a code block from `language-*`/`lang-*`/`highlight-*`/`hljs` classes or
+/// `data-lang` on the `<pre>` or its `<code>`
+fn code_language(pre: ElementRef) -> Option<String> {
+    let code = pre.children().filter_map(ElementRef::wrap).find(|c| c.value().name() == "code");
+    let candidates = [Some(pre), code]
+        .into_iter()
+        .flatten()
+        .chain(pre.ancestors().filter_map(ElementRef::wrap).take(2));
+    for el in candidates {
+        let v = el.value();
+        if let Some(lang) = v.attr("data-lang").or_else(|| v.attr("data-language")) {
+            return valid_language(lang);
+        }
+        let classes: Vec<&str> = v.classes().collect();
+        for class in &classes {
+            for prefix in ["language-", "lang-", "highlight-source-", "highlight-"] {
+                if let Some(lang) = class.strip_prefix(prefix).and_then(valid_language) {
+                    return Some(lang);
+                }
+            }
+        }
+        // highlight.js also emits `class="hljs rust"`
+        if classes.contains(&"hljs") {
+            if let Some(lang) = classes.iter().filter(|c| **c != "hljs").find_map(|c| valid_language(c)) {
+                return Some(lang);
+            }
+        }
+    }
+    None
+}
+
+fn valid_language(raw: &str) -> Option<String> {
+    let lang = raw.trim().to_ascii_lowercase();
+    let ok = !lang.is_empty()
+        && lang.len() <= 20
+        && lang.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '#' | '-' | '_'));
+    ok.then_some(lang)
+}
+
+/// Phrasing elements whose text belongs to the surrounding block
+const INLINE_TAGS: &[&str] = &[
+    "a", "abbr", "b", "bdi", "cite", "code", "data", "del", "dfn", "em", "i", "ins", "kbd", "mark", "q", "s", "samp",
+    "small", "span", "strong", "sub", "sup", "time", "u", "var",
+];
+
+/// Flatten an element subtree into typed blocks in document order. Text outside any block element
+/// becomes a paragraph; nested list items follow their parent item.
+pub fn html_to_blocks(root: ElementRef) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    collect_blocks(root, &mut blocks);
+    blocks
+}
+
+fn collect_blocks(el: ElementRef, blocks: &mut Vec<Block>) {
+    let mut loose = String::new();
+    let flush = |loose: &mut String, blocks: &mut Vec<Block>| {
+        push_block(blocks, BlockKind::Paragraph, collapse(loose));
+        loose.clear();
+    };
+    for child in el.children() {
+        match child.value() {
+            Node::Text(text) => loose.push_str(text),
+            Node::Element(_) => {
+                let Some(child) = ElementRef::wrap(child) else { continue };
+                let name = child.value().name();
+                if SKIPPED_TAGS.contains(&name) {
+                    continue;
+                }
+                if INLINE_TAGS.contains(&name) {
+                    loose.push_str(&child.text().collect::<String>());
+                    continue;
+                }
+                if name == "br" {
+                    loose.push(' ');
+                    continue;
+                }
+                flush(&mut loose, blocks);
+                match name {
+                    "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => push_block(blocks, BlockKind::Heading, collapse(&child.text().collect::<String>())),
+                    "p" => push_block(blocks, BlockKind::Paragraph, collapse(&child.text().collect::<String>())),
+                    "blockquote" => push_block(blocks, BlockKind::Quote, collapse(&child.text().collect::<String>())),
+                    "pre" => push_block(blocks, BlockKind::Code, child.text().collect::<String>().trim_matches('\n').to_string()),
+                    "li" => {
+                        let mut own = String::new();
+                        let mut nested = Vec::new();
+                        for part in child.children() {
+                            match (part.value(), ElementRef::wrap(part)) {
+                                (Node::Text(text), _) => own.push_str(text),
+                                (_, Some(sub)) if matches!(sub.value().name(), "ul" | "ol") => nested.push(sub),
+                                (_, Some(sub)) => {
+                                    own.push(' ');
+                                    own.push_str(&sub.text().collect::<String>());
+                                }
+                                _ => {}
+                            }
+                        }
+                        push_block(blocks, BlockKind::ListItem, collapse(&own));
+                        for list in nested {
+                            collect_blocks(list, blocks);
+                        }
+                    }
+                    _ => collect_blocks(child, blocks),
+                }
+            }
+            _ => {}
+        }
+    }
+    flush(&mut loose, blocks);
+}
+
+fn push_block(blocks: &mut Vec<Block>, kind: BlockKind, text: String) {
+    if !text.trim().is_empty() {
+        blocks.push(Block { kind, text });
+    }
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scraper::{Html, Selector};
+
+    #[test]
+    fn test_html_to_markdown_structure() {
+        let html = Html::parse_document(
+            r#"<body><nav>Home | About</nav>
+            <h2>Install</h2><p>Run the <code>setup</code> script.</p>
+            <ul><li><p>Linux</p></li><li>macOS <ul><li>Intel</li></ul></li></ul>
+            <blockquote><p>Back up first.</p></blockquote>
+            <pre>fn main() {
+    println!("hi");
+}</pre></body>"#,
+        );
+        let body = html.select(&Selector::parse("body").unwrap()).next().unwrap();
+        let md = html_to_markdown(body, &|_| false);
+        assert_eq!(
+            md,
+            "## Install\n\nRun the `setup` script.\n\n- Linux\n- macOS\n  - Intel\n\n> Back up first.\n\n```\nfn main() {\n    println!(\"hi\");\n}\n```"
+        );
+    }
+
+    #[test]
+    fn test_links_and_images_resolved_against_base() {
+        let html = Html::parse_document(
+            r##"<body><h2>Guides</h2><p>Read the <a href="/docs/start">getting started [beta] guide</a>, or
+            <a href="#top">jump up</a>. <img src="img/chart (1).png" alt="Chart"> <a href="javascript:void(0)">Menu</a></p></body>"##,
+        );
+        let body = html.select(&Selector::parse("body").unwrap()).next().unwrap();
+        let base = Url::parse("https://example.com/blog/post").unwrap();
+        assert_eq!(
+            html_to_markdown_with_links(body, &|_| false, &base),
+            "## Guides\n\nRead the [getting started \\[beta\\] guide](https://example.com/docs/start), or jump up. \
+             ![Chart](https://example.com/blog/img/chart%20%281%29.png) Menu"
+        );
+        // Without a base, links and images are only text as before
+        assert_eq!(html_to_markdown(body, &|_| false), "## Guides\n\nRead the getting started [beta] guide, or jump up. Menu");
+    }
+
+    #[test]
+    fn test_code_block_language_fence() {
+        let html = Html::parse_document(
+            r#"<body><pre><code class="language-python">print("hi")</code></pre>
+            <div class="highlight highlight-source-rust"><pre>fn main() {}</pre></div>
+            <pre><code class="hljs">no hint</code></pre></body>"#,
+        );
+        let body = html.select(&Selector::parse("body").unwrap()).next().unwrap();
+        let md = html_to_markdown(body, &|_| false);
+        assert_eq!(md, "```python\nprint(\"hi\")\n```\n\n```rust\nfn main() {}\n```\n\n```\nno hint\n```");
+    }
+}
\ No newline at end of file
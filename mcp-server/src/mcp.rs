@@ -1,5 +1,6 @@
 use crate::types::*;
 use crate::{search, scrape, AppState};
+use crate::rust_scraper::ScrapeOptions;
 use axum::{
     extract::State,
     http::StatusCode,
@@ -78,6 +79,42 @@ pub async fn list_tools() -> Json<McpToolsResponse> {
                         "type": "integer",
                         "minimum": 1,
                         "description": "Page number for pagination"
+                    },
+                    "max_pages": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": 5,
+                        "description": "Fetch this many result pages (from pageno) concurrently and merge them in ranking order"
+                    },
+                    "follow_up_below": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "With fewer results than this, also search SearXNG's top suggestion once and append its results"
+                    },
+                    "normalize_urls": {
+                        "type": "boolean",
+                        "description": "Normalize result URLs (lowercase host, no fragment) for stable keys; originals kept as raw_url"
+                    },
+                    "timeout_limit": {
+                        "type": "number",
+                        "exclusiveMinimum": 0,
+                        "description": "Seconds SearXNG waits for slow engines before returning partial results"
+                    },
+                    "published_after": {
+                        "type": "string",
+                        "description": "Only results published on/after this date (YYYY-MM-DD); undated results pass"
+                    },
+                    "published_before": {
+                        "type": "string",
+                        "description": "Only results published on/before this date (YYYY-MM-DD); undated results pass"
+                    },
+                    "drop_undated": {
+                        "type": "boolean",
+                        "description": "With a date filter, also drop results without a publication date"
+                    },
+                    "template_filter": {
+                        "type": "string",
+                        "description": "Only return results rendered with this SearXNG template (e.g., 'images.html', 'videos.html')"
                     }
                 },
                 "required": ["query"]
@@ -92,16 +129,267 @@ pub async fn list_tools() -> Json<McpToolsResponse> {
                     "url": {
                         "type": "string",
                         "description": "The URL to scrape content from"
+                    },
+                    "preserve_whitespace": {
+                        "type": "boolean",
+                        "description": "Keep indentation and line breaks (code, ASCII tables, poetry) instead of collapsing whitespace"
+                    },
+                    "as_document": {
+                        "type": "boolean",
+                        "description": "Return the main content as a markdown document that keeps the heading hierarchy"
+                    },
+                    "format": {
+                        "type": "string",
+                        "enum": ["text", "markdown"],
+                        "description": "Content format: plain text preview (default) or markdown with headings, links, lists and code blocks"
+                    },
+                    "max_paragraphs": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "Only return the first N paragraphs of the content (lead/preview)"
+                    },
+                    "follow_next": {
+                        "type": "boolean",
+                        "description": "Follow rel=next links and merge a multi-chapter docs book into one document"
+                    },
+                    "max_pages": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "Chapters to merge with follow_next, including the first (default 10, max 50)"
+                    },
+                    "preview_chars": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": MAX_PREVIEW_CHARS,
+                        "description": "Characters of content to include in the preview, cut at a word boundary (default 2000, max 50000)"
+                    },
+                    "timeout_secs": {
+                        "type": "number",
+                        "exclusiveMinimum": 0,
+                        "maximum": MAX_SCRAPE_TIMEOUT_SECS,
+                        "description": "Give up on the page after this many seconds (default 30, max 120)"
+                    },
+                    "max_body_bytes": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "Abort the download once the page body passes this many bytes (at most the server's limit, 10 MiB by default)"
                     }
                 },
                 "required": ["url"]
             }),
         },
+        McpTool {
+            name: "scrape_urls".to_string(),
+            description: "Scrape several URLs concurrently. Returns one JSON block per URL, in order, with the page summary or the error for that URL.".to_string(),
+            input_schema: scrape_urls_schema(),
+        },
     ];
     
     Json(McpToolsResponse { tools })
 }
 
+/// Text for a search with no results, listing SearXNG suggestions/corrections when available
+pub fn no_results_text(query: &str, outcome: &SearchOutcome) -> String {
+    let mut text = format!("No search results found for query: {}", query);
+    let hints: Vec<&String> = outcome.corrections.iter().chain(outcome.suggestions.iter()).collect();
+    if !hints.is_empty() {
+        let mut unique: Vec<&String> = Vec::new();
+        for h in hints {
+            if !unique.contains(&h) {
+                unique.push(h);
+            }
+        }
+        text.push_str(&format!(
+            "\nDid you mean: {}",
+            unique.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ")
+        ));
+    }
+    text
+}
+
+/// Default and maximum characters of `clean_content` in the `scrape_url` tool's preview
+pub const DEFAULT_PREVIEW_CHARS: usize = 2000;
+pub const MAX_PREVIEW_CHARS: usize = 50_000;
+
+/// The `preview_chars` tool argument, clamped to `1..=MAX_PREVIEW_CHARS`
+pub fn preview_chars(arg: Option<&serde_json::Value>) -> usize {
+    arg.and_then(|v| v.as_u64())
+        .map_or(DEFAULT_PREVIEW_CHARS, |n| (n as usize).clamp(1, MAX_PREVIEW_CHARS))
+}
+
+/// At most `max_chars` characters of `text` without splitting a word, with an ellipsis when cut.
+/// A single word longer than half the preview is cut mid-word rather than dropped.
+pub fn preview(text: &str, max_chars: usize) -> String {
+    let Some((end, _)) = text.char_indices().nth(max_chars) else { return text.to_string() };
+    let mut cut = &text[..end];
+    if !text[end..].starts_with(char::is_whitespace) {
+        if let Some(space) = cut.rfind(char::is_whitespace).filter(|i| *i >= end / 2) {
+            cut = &cut[..space];
+        }
+    }
+    format!("{}…", cut.trim_end())
+}
+
+/// Longest `timeout_secs` a `scrape_url` call may ask for
+pub const MAX_SCRAPE_TIMEOUT_SECS: u64 = 120;
+
+/// The `timeout_secs` and `max_body_bytes` arguments of the `scrape_url` tool as per-call fetch limits
+pub fn scrape_options(timeout_secs: Option<&serde_json::Value>, max_body_bytes: Option<&serde_json::Value>) -> Result<ScrapeOptions, String> {
+    let timeout = match timeout_secs {
+        None => None,
+        Some(v) => match v.as_f64() {
+            Some(secs) if secs > 0.0 => Some(std::time::Duration::from_secs_f64(secs.min(MAX_SCRAPE_TIMEOUT_SECS as f64))),
+            _ => return Err("timeout_secs must be a positive number of seconds".to_string()),
+        },
+    };
+    let max_body_bytes = match max_body_bytes {
+        None => None,
+        Some(v) => match v.as_u64() {
+            Some(bytes) if bytes > 0 => Some(bytes as usize),
+            _ => return Err("max_body_bytes must be a positive integer".to_string()),
+        },
+    };
+    Ok(ScrapeOptions { timeout, max_body_bytes, ..Default::default() })
+}
+
+/// The `format` argument of the `scrape_url` tool: whether markdown was requested
+pub fn markdown_format(arg: Option<&serde_json::Value>) -> Result<bool, String> {
+    match arg.map(|v| v.as_str()) {
+        None | Some(Some("text")) => Ok(false),
+        Some(Some("markdown")) => Ok(true),
+        _ => Err("format must be \"text\" or \"markdown\"".to_string()),
+    }
+}
+
+/// Text output of the `scrape_url` tool (HTTP and stdio): the markdown content or document when
+/// requested, otherwise a metadata summary with a `preview_chars` preview of the content
+pub fn scrape_text(content: &ScrapeResponse, preview_chars: usize) -> String {
+    if let Some(document) = content.markdown_content.as_ref().or(content.document.as_ref()) {
+        return format!("# {}\n\nURL: {}\n\n{}", content.title, content.url, document);
+    }
+    let headings = content.headings.iter()
+        .take(10)
+        .map(|h| format!("- {} {}", h.level.to_uppercase(), h.text))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let content_preview = if content.clean_content.is_empty() {
+        "[No content extracted - this may indicate a parsing issue]".to_string()
+    } else {
+        preview(&content.clean_content, preview_chars)
+    };
+    format!(
+        "{}\nURL: {}\nCanonical: {}\nWord Count: {} ({}m)\nLanguage: {}\nSite: {}\nAuthor: {}\nPublished: {}\n\nDescription: {}\nOG Image: {}\n\nHeadings:\n{}\n\nLinks: {}  Images: {}\n\nPreview:\n{}",
+        content.title,
+        content.url,
+        content.canonical_url.as_deref().unwrap_or("-"),
+        content.word_count,
+        content.reading_time_minutes.unwrap_or(((content.word_count as f64 / 200.0).ceil() as u32).max(1)),
+        content.language,
+        content.site_name.as_deref().unwrap_or("-"),
+        content.author.as_deref().unwrap_or("-"),
+        content.published_at.as_deref().unwrap_or("-"),
+        content.meta_description,
+        content.og_image.as_deref().unwrap_or("-"),
+        headings,
+        content.links.len(),
+        content.images.len(),
+        content_preview
+    )
+}
+
+/// Default and maximum number of pages the `scrape_urls` tool fetches at once
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 5;
+pub const MAX_BATCH_CONCURRENCY: usize = 10;
+/// Most URLs accepted by one `scrape_urls` call
+pub const MAX_BATCH_URLS: usize = 20;
+
+/// Input schema of the `scrape_urls` tool (HTTP and stdio)
+pub fn scrape_urls_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "urls": {
+                "type": "array",
+                "items": { "type": "string" },
+                "minItems": 1,
+                "maxItems": MAX_BATCH_URLS,
+                "description": "The URLs to scrape"
+            },
+            "max_concurrency": {
+                "type": "integer",
+                "minimum": 1,
+                "maximum": MAX_BATCH_CONCURRENCY,
+                "description": "Pages to fetch at once (default 5, max 10)"
+            },
+            "preview_chars": {
+                "type": "integer",
+                "minimum": 1,
+                "maximum": MAX_PREVIEW_CHARS,
+                "description": "Characters of content to include in each preview, cut at a word boundary (default 2000, max 50000)"
+            }
+        },
+        "required": ["urls"]
+    })
+}
+
+/// The `urls` and clamped `max_concurrency` arguments of the `scrape_urls` tool
+pub fn scrape_urls_args(arguments: &serde_json::Value) -> Result<(Vec<String>, usize), String> {
+    let urls: Vec<String> = arguments
+        .get("urls")
+        .and_then(|v| v.as_array())
+        .ok_or("Missing required parameter: urls")?
+        .iter()
+        .map(|v| v.as_str().map(str::to_string).ok_or("urls must be an array of strings"))
+        .collect::<Result<_, _>>()?;
+    if urls.is_empty() || urls.len() > MAX_BATCH_URLS {
+        return Err(format!("urls must contain between 1 and {} URLs", MAX_BATCH_URLS));
+    }
+    let max_concurrency = arguments
+        .get("max_concurrency")
+        .and_then(|v| v.as_u64())
+        .map_or(DEFAULT_BATCH_CONCURRENCY, |n| (n as usize).clamp(1, MAX_BATCH_CONCURRENCY));
+    Ok((urls, max_concurrency))
+}
+
+/// Output of the `scrape_urls` tool: one JSON text per URL, in order, with `is_error` set per URL.
+/// A page carries its `scrape_text` summary; a failure carries the same code and message as `scrape_url`.
+pub async fn scrape_urls_texts(state: &Arc<AppState>, urls: &[String], max_concurrency: usize, preview_chars: usize) -> Vec<String> {
+    let results = scrape::scrape_urls(state, urls, max_concurrency).await;
+    urls.iter()
+        .zip(results)
+        .map(|(url, result)| match result {
+            Ok(content) => serde_json::json!({ "url": url, "is_error": false, "text": scrape_text(&content, preview_chars) }),
+            Err(e) => {
+                error!("Scrape tool error for {}: {}", url, e);
+                serde_json::json!({
+                    "url": url,
+                    "is_error": true,
+                    "code": crate::error::error_code(&e),
+                    "message": format!("Scraping failed: {}", e),
+                })
+            }
+        })
+        .map(|item| item.to_string())
+        .collect()
+}
+
+/// Human-readable error text plus a JSON item with a stable `code` clients can branch on
+pub fn tool_error_texts(prefix: &str, e: &anyhow::Error) -> [String; 2] {
+    let message = format!("{}: {}", prefix, e);
+    let detail = serde_json::json!({ "code": crate::error::error_code(e), "message": message }).to_string();
+    [message, detail]
+}
+
+fn tool_error(prefix: &str, e: &anyhow::Error) -> McpCallResponse {
+    McpCallResponse {
+        content: tool_error_texts(prefix, e)
+            .into_iter()
+            .map(|text| McpContent { content_type: "text".to_string(), text })
+            .collect(),
+        is_error: true,
+    }
+}
+
 pub async fn call_tool(
     State(state): State<Arc<AppState>>,
     Json(request): Json<McpCallRequest>,
@@ -142,20 +430,32 @@ pub async fn call_tool(
             if let Some(v) = request.arguments.get("pageno").and_then(|v| v.as_u64()) {
                 overrides.pageno = Some(v as u32);
             }
+            if let Some(v) = request.arguments.get("max_pages").and_then(|v| v.as_u64()) {
+                overrides.max_pages = Some(v as u32);
+            }
+            overrides.follow_up_below = request.arguments.get("follow_up_below").and_then(|v| v.as_u64()).map(|n| n as usize);
+            overrides.normalize_urls = request.arguments.get("normalize_urls").and_then(|v| v.as_bool());
+            overrides.timeout_limit = request.arguments.get("timeout_limit").and_then(|v| v.as_f64());
+            overrides.published_after = request.arguments.get("published_after").and_then(|v| v.as_str()).map(str::to_string);
+            overrides.published_before = request.arguments.get("published_before").and_then(|v| v.as_str()).map(str::to_string);
+            overrides.drop_undated = request.arguments.get("drop_undated").and_then(|v| v.as_bool());
+            overrides.template_filter = request.arguments.get("template_filter").and_then(|v| v.as_str()).map(str::to_string);
             
             // Perform search
             let ov_opt = Some(overrides);
-            match search::search_web_with_params(&state, query, ov_opt).await {
-                Ok(results) => {
+            match search::search_web_outcome(&state, query, ov_opt).await {
+                Ok(outcome) => {
+                    let results = &outcome.results;
                     let content_text = if results.is_empty() {
-                        format!("No search results found for query: {}", query)
+                        no_results_text(query, &outcome)
                     } else {
                         let mut text = format!("Found {} search results for '{}':\n\n", results.len(), query);
                         for (i, result) in results.iter().take(10).enumerate() {
                             text.push_str(&format!(
-                                "{}. **{}**\n   URL: {}\n   Snippet: {}\n\n",
+                                "{}. **{}**{}\n   URL: {}\n   Snippet: {}\n\n",
                                 i + 1,
                                 result.title,
+                                result.follow_up_query.as_ref().map(|q| format!(" (via suggestion: {})", q)).unwrap_or_default(),
                                 result.url,
                                 result.content.chars().take(200).collect::<String>()
                             ));
@@ -173,13 +473,7 @@ pub async fn call_tool(
                 }
                 Err(e) => {
                     error!("Search tool error: {}", e);
-                    Ok(Json(McpCallResponse {
-                        content: vec![McpContent {
-                            content_type: "text".to_string(),
-                            text: format!("Search failed: {}", e),
-                        }],
-                        is_error: true,
-                    }))
+                    Ok(Json(tool_error("Search failed", &e)))
                 }
             }
         }
@@ -196,35 +490,27 @@ pub async fn call_tool(
                         }),
                     )
                 })?;
+            let markdown = markdown_format(request.arguments.get("format"))
+                .map_err(|error| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error })))?;
+            let options = scrape_options(request.arguments.get("timeout_secs"), request.arguments.get("max_body_bytes"))
+                .map_err(|error| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error })))?;
+            
+            let overrides = scrape::ScrapeParamOverrides {
+                preserve_whitespace: request.arguments.get("preserve_whitespace").and_then(|v| v.as_bool()),
+                markdown: Some(markdown),
+                as_document: request.arguments.get("as_document").and_then(|v| v.as_bool()),
+                max_paragraphs: request.arguments.get("max_paragraphs").and_then(|v| v.as_u64()).map(|n| n as usize),
+                follow_next: request.arguments.get("follow_next").and_then(|v| v.as_bool()).unwrap_or(false).then(|| {
+                    request.arguments.get("max_pages").and_then(|v| v.as_u64()).map_or(scrape::DEFAULT_BOOK_PAGES, |n| n as usize)
+                }),
+                options,
+                ..Default::default()
+            };
             
             // Perform scraping - only Rust-native path
-            match scrape::scrape_url(&state, url).await {
+            match scrape::scrape_url_with_params(&state, url, Some(overrides)).await.and_then(scrape::reject_error_status) {
                 Ok(content) => {
-                    let content_text = {
-                        let headings = content.headings.iter()
-                            .take(10)
-                            .map(|h| format!("- {} {}", h.level.to_uppercase(), h.text))
-                            .collect::<Vec<_>>()
-                            .join("\n");
-                        format!(
-                            "{}\nURL: {}\nCanonical: {}\nWord Count: {} ({}m)\nLanguage: {}\nSite: {}\nAuthor: {}\nPublished: {}\n\nDescription: {}\nOG Image: {}\n\nHeadings:\n{}\n\nLinks: {}  Images: {}\n\nPreview:\n{}",
-                            content.title,
-                            content.url,
-                            content.canonical_url.as_deref().unwrap_or("-"),
-                            content.word_count,
-                            content.reading_time_minutes.unwrap_or(((content.word_count as f64 / 200.0).ceil() as u32).max(1)),
-                            content.language,
-                            content.site_name.as_deref().unwrap_or("-"),
-                            content.author.as_deref().unwrap_or("-"),
-                            content.published_at.as_deref().unwrap_or("-"),
-                            content.meta_description,
-                            content.og_image.as_deref().unwrap_or("-"),
-                            headings,
-                            content.links.len(),
-                            content.images.len(),
-                            content.clean_content.chars().take(1200).collect::<String>()
-                        )
-                    };
+                    let content_text = scrape_text(&content, preview_chars(request.arguments.get("preview_chars")));
                     
                     Ok(Json(McpCallResponse {
                         content: vec![McpContent {
@@ -236,16 +522,19 @@ pub async fn call_tool(
                 }
                 Err(e) => {
                     error!("Scrape tool error: {}", e);
-                    Ok(Json(McpCallResponse {
-                        content: vec![McpContent {
-                            content_type: "text".to_string(),
-                            text: format!("Scraping failed: {}", e),
-                        }],
-                        is_error: true,
-                    }))
+                    Ok(Json(tool_error("Scraping failed", &e)))
                 }
             }
         }
+        "scrape_urls" => {
+            let (urls, max_concurrency) = scrape_urls_args(&request.arguments)
+                .map_err(|error| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error })))?;
+            let texts = scrape_urls_texts(&state, &urls, max_concurrency, preview_chars(request.arguments.get("preview_chars"))).await;
+            Ok(Json(McpCallResponse {
+                content: texts.into_iter().map(|text| McpContent { content_type: "text".to_string(), text }).collect(),
+                is_error: false,
+            }))
+        }
         _ => Err((
             StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
@@ -253,4 +542,82 @@ pub async fn call_tool(
             }),
         )),
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::empty_scrape_response;
+
+    #[test]
+    fn test_preview_respects_requested_length() {
+        let mut page = empty_scrape_response("https://example.com/long");
+        page.clean_content = "Extraction keeps whole words in previews for language model clients. ".repeat(100);
+
+        for requested in [40, 300, 5000] {
+            let text = scrape_text(&page, preview_chars(Some(&serde_json::json!(requested))));
+            let shown = text.split("Preview:\n").nth(1).unwrap();
+            let body = shown.strip_suffix('…').unwrap();
+            assert!(body.chars().count() <= requested, "{} > {}", body.chars().count(), requested);
+            assert!(body.chars().count() > requested / 2);
+            assert!(page.clean_content.starts_with(body) && page.clean_content[body.len()..].starts_with(' '), "cut mid-word: {:?}", body);
+        }
+
+        // Short content is returned whole; the length is clamped and defaults without the argument
+        assert_eq!(preview("short text", 100), "short text");
+        assert_eq!(preview_chars(Some(&serde_json::json!(10_000_000))), MAX_PREVIEW_CHARS);
+        assert_eq!(preview_chars(None), DEFAULT_PREVIEW_CHARS);
+    }
+
+    #[tokio::test]
+    async fn test_scrape_urls_reports_failures_per_url() {
+        use axum::{response::Html, routing::get, Router};
+        let app = Router::new().route("/ok", get(|| async {
+            Html("<html><head><title>Fine</title></head><body><article><p>This page scrapes without any trouble at all.</p></article></body></html>")
+        }));
+        let base = crate::test_support::serve(app).await;
+        let state = Arc::new(crate::test_support::local_state("http://127.0.0.1:9"));
+        let request = McpCallRequest {
+            name: "scrape_urls".to_string(),
+            arguments: serde_json::json!({ "urls": [format!("{}/ok", base), "not a url"], "max_concurrency": 2 }),
+        };
+
+        let Json(response) = call_tool(State(state), Json(request)).await.unwrap();
+        assert!(!response.is_error);
+        let items: Vec<serde_json::Value> = response.content.iter().map(|c| serde_json::from_str(&c.text).unwrap()).collect();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0]["is_error"], false);
+        assert!(items[0]["text"].as_str().unwrap().starts_with("Fine"));
+        assert_eq!(items[1]["url"], "not a url");
+        assert_eq!(items[1]["is_error"], true);
+        assert!(items[1]["message"].as_str().unwrap().starts_with("Scraping failed"));
+
+        // A missing or oversized list is rejected up front
+        assert!(scrape_urls_args(&serde_json::json!({})).is_err());
+        assert!(scrape_urls_args(&serde_json::json!({ "urls": vec!["https://example.com"; MAX_BATCH_URLS + 1] })).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scrape_url_max_body_bytes_argument() {
+        use axum::{response::Html, routing::get, Router};
+        let app = Router::new().route("/big", get(|| async {
+            Html(format!("<html><head><title>Big</title></head><body><p>{}</p></body></html>", "word ".repeat(2000)))
+        }));
+        let base = crate::test_support::serve(app).await;
+        let state = Arc::new(crate::test_support::local_state("http://127.0.0.1:9"));
+        let request = McpCallRequest {
+            name: "scrape_url".to_string(),
+            arguments: serde_json::json!({ "url": format!("{}/big", base), "max_body_bytes": 512, "timeout_secs": 5 }),
+        };
+
+        let Json(response) = call_tool(State(state), Json(request)).await.unwrap();
+        assert!(response.is_error);
+        let detail: serde_json::Value = serde_json::from_str(&response.content[1].text).unwrap();
+        assert_eq!(detail["code"], "body_too_large");
+
+        let options = scrape_options(Some(&serde_json::json!(600)), None).unwrap();
+        assert_eq!(options.timeout, Some(std::time::Duration::from_secs(MAX_SCRAPE_TIMEOUT_SECS)));
+        assert!(scrape_options(Some(&serde_json::json!(0)), None).is_err());
+        assert!(scrape_options(None, Some(&serde_json::json!("1MB"))).is_err());
+    }
 }
\ No newline at end of file
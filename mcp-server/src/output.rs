@@ -0,0 +1,242 @@
+use crate::types::*;
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts},
+    response::{IntoResponse, Json, Response},
+};
+use serde::Serialize;
+use std::convert::Infallible;
+
+/// MIME type of MessagePack response bodies
+pub const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+
+/// Response format negotiated from the request `Accept` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResponseFormat {
+    #[default]
+    Json,
+    Csv,
+    MessagePack,
+}
+
+impl ResponseFormat {
+    /// Pick a format from an `Accept` header value: the first of CSV or MessagePack that is explicitly
+    /// listed, otherwise JSON
+    pub fn from_accept(accept: &str) -> Self {
+        accept
+            .split(',')
+            .map(|part| part.split(';').next().unwrap_or("").trim())
+            .find_map(|mime| {
+                if mime.eq_ignore_ascii_case("text/csv") {
+                    Some(ResponseFormat::Csv)
+                } else if mime.eq_ignore_ascii_case(MSGPACK_CONTENT_TYPE) || mime.eq_ignore_ascii_case("application/x-msgpack") {
+                    Some(ResponseFormat::MessagePack)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Encode `value` in the negotiated format. MessagePack keeps field names so optional fields stay
+/// optional for the client; CSV only exists for search results, so other bodies fall back to JSON.
+pub fn encode<T: Serialize>(format: ResponseFormat, value: &T) -> anyhow::Result<Response> {
+    match format {
+        ResponseFormat::MessagePack => {
+            Ok(([(header::CONTENT_TYPE, MSGPACK_CONTENT_TYPE)], rmp_serde::to_vec_named(value)?).into_response())
+        }
+        ResponseFormat::Json | ResponseFormat::Csv => Ok(Json(value).into_response()),
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ResponseFormat
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .headers
+            .get(header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .map(ResponseFormat::from_accept)
+            .unwrap_or_default())
+    }
+}
+
+/// Check a `fields` projection against the serialized field names of `T`. `optional` lists fields
+/// that are omitted from a default value (`skip_serializing_if`) but are still valid to request.
+pub fn validate_fields<T: Serialize + Default>(fields: &[String], optional: &[&str]) -> Result<(), String> {
+    let known = match serde_json::to_value(T::default()) {
+        Ok(serde_json::Value::Object(map)) => map.into_iter().map(|(k, _)| k).collect::<Vec<_>>(),
+        _ => Vec::new(),
+    };
+    match fields.iter().find(|f| !known.contains(f) && !optional.contains(&f.as_str())) {
+        Some(unknown) => Err(format!("Unknown field '{}'", unknown)),
+        None => Ok(()),
+    }
+}
+
+/// Keep only `fields` of a serialized object (non-objects are returned unchanged)
+pub fn project_fields(value: serde_json::Value, fields: &[String]) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(mut map) => {
+            map.retain(|k, _| fields.contains(k));
+            serde_json::Value::Object(map)
+        }
+        other => other,
+    }
+}
+
+/// Serialize search results as CSV with a header row (url,title,content,engine,score)
+pub fn search_results_to_csv(results: &[SearchResult]) -> String {
+    let mut out = String::from("url,title,content,engine,score\n");
+    for r in results {
+        let fields = [
+            csv_field(&r.url),
+            csv_field(&r.title),
+            csv_field(&r.content),
+            csv_field(r.engine.as_deref().unwrap_or("")),
+            r.score.map(|s| s.to_string()).unwrap_or_default(),
+        ];
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// A scrape as a schema.org `Article` JSON-LD object. Properties without a value are left out
+/// rather than sent as null, which JSON-LD validators flag.
+pub fn scrape_to_article(page: &ScrapeResponse) -> serde_json::Value {
+    let text = |s: &str| Some(s.trim()).filter(|s| !s.is_empty()).map(str::to_string);
+    let headline = page.readable_title.clone().or_else(|| page.og_title.clone()).or_else(|| text(&page.title));
+    let mut article = serde_json::Map::new();
+    article.insert("@context".into(), "https://schema.org".into());
+    article.insert("@type".into(), "Article".into());
+    let mut set = |key: &str, value: Option<serde_json::Value>| {
+        if let Some(value) = value {
+            article.insert(key.to_string(), value);
+        }
+    };
+    set("headline", headline.map(Into::into));
+    set("url", Some(page.canonical_url.clone().unwrap_or_else(|| page.url.clone()).into()));
+    set("description", text(&page.meta_description).or_else(|| page.og_description.clone()).map(Into::into));
+    set("author", page.author.clone().or_else(|| page.author_profile.as_ref().and_then(|p| p.name.clone())).map(|name| {
+        let mut person = serde_json::json!({ "@type": "Person", "name": name });
+        if let Some(url) = page.author_profile.as_ref().and_then(|p| p.url.clone()) {
+            person["url"] = url.into();
+        }
+        person
+    }));
+    set("datePublished", page.published_at.clone().map(Into::into));
+    set("publisher", page.site_name.clone().map(|name| serde_json::json!({ "@type": "Organization", "name": name })));
+    set("image", page.og_image.clone().map(Into::into));
+    set("inLanguage", text(&page.language).filter(|l| l != "unknown").map(Into::into));
+    set("keywords", text(&page.meta_keywords).map(Into::into));
+    set("wordCount", Some(page.word_count.into()));
+    set("articleBody", Some(page.clean_content.clone().into()));
+    serde_json::Value::Object(article)
+}
+
+/// Quote a CSV field when it contains separators, quotes or line breaks (RFC 4180)
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_negotiation() {
+        assert_eq!(ResponseFormat::from_accept("text/csv"), ResponseFormat::Csv);
+        assert_eq!(ResponseFormat::from_accept("application/json, text/csv;q=0.5"), ResponseFormat::Csv);
+        assert_eq!(ResponseFormat::from_accept("application/json"), ResponseFormat::Json);
+        assert_eq!(ResponseFormat::from_accept("*/*"), ResponseFormat::Json);
+        assert_eq!(ResponseFormat::from_accept("application/msgpack, application/json;q=0.5"), ResponseFormat::MessagePack);
+        assert_eq!(ResponseFormat::from_accept("application/x-msgpack"), ResponseFormat::MessagePack);
+    }
+
+    #[test]
+    fn test_scrape_response_round_trips_through_msgpack() {
+        let mut page = crate::test_support::empty_scrape_response("https://example.com/post");
+        page.title = "Packed".to_string();
+        page.clean_content = "Binary clients skip JSON parsing.".to_string();
+        page.word_count = 5;
+        page.author = Some("Ada".to_string());
+        page.wall_type = Some(WallType::Paywall);
+        page.markdown_content = Some("# Packed".to_string());
+
+        let response = encode(ResponseFormat::MessagePack, &page).unwrap();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], MSGPACK_CONTENT_TYPE);
+        let bytes = rmp_serde::to_vec_named(&page).unwrap();
+        let decoded: ScrapeResponse = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), serde_json::to_value(&page).unwrap());
+        assert!(bytes.len() < serde_json::to_vec(&page).unwrap().len());
+    }
+
+    #[test]
+    fn test_search_results_to_csv() {
+        let results = vec![
+            SearchResult {
+                url: "https://example.com/a".to_string(),
+                title: "Hello, \"world\"".to_string(),
+                content: "line one\nline two".to_string(),
+                engine: Some("google".to_string()),
+                score: Some(1.5),
+                ..Default::default()
+            },
+            SearchResult {
+                url: "https://example.com/b".to_string(),
+                title: "Plain".to_string(),
+                content: "simple".to_string(),
+                engine: None,
+                score: None,
+                ..Default::default()
+            },
+        ];
+        let csv = search_results_to_csv(&results);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("url,title,content,engine,score"));
+        assert!(csv.contains("https://example.com/a,\"Hello, \"\"world\"\"\",\"line one\nline two\",google,1.5\n"));
+        assert!(csv.ends_with("https://example.com/b,Plain,simple,,\n"));
+    }
+
+    #[test]
+    fn test_scrape_to_article() {
+        let page = ScrapeResponse {
+            title: "Trail extended | Harbour News".to_string(),
+            readable_title: Some("Trail extended".to_string()),
+            clean_content: "The council voted on Tuesday to extend the harbour trail.".to_string(),
+            word_count: 10,
+            language: "en".to_string(),
+            author: Some("Ada Example".to_string()),
+            author_profile: Some(AuthorProfile { url: Some("https://news.example/authors/ada".to_string()), ..Default::default() }),
+            published_at: Some("2024-05-02".to_string()),
+            site_name: Some("Harbour News".to_string()),
+            og_image: Some("https://news.example/trail.jpg".to_string()),
+            ..crate::test_support::empty_scrape_response("https://news.example/trail")
+        };
+        let article = scrape_to_article(&page);
+        let object = article.as_object().expect("a JSON-LD node is an object");
+        assert_eq!(object["@context"], "https://schema.org");
+        assert_eq!(object["@type"], "Article");
+        assert_eq!(object["headline"], "Trail extended");
+        assert_eq!(object["url"], "https://news.example/trail");
+        assert_eq!(object["author"], serde_json::json!({ "@type": "Person", "name": "Ada Example", "url": "https://news.example/authors/ada" }));
+        assert_eq!(object["publisher"], serde_json::json!({ "@type": "Organization", "name": "Harbour News" }));
+        assert_eq!(object["datePublished"], "2024-05-02");
+        assert_eq!(object["image"], "https://news.example/trail.jpg");
+        assert_eq!(object["articleBody"], "The council voted on Tuesday to extend the harbour trail.");
+        assert!(object.values().all(|v| !v.is_null()));
+        assert!(!object.contains_key("description") && !object.contains_key("keywords"));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,199 @@
+//! Per-domain "load more" pagination: for feed-style pages whose HTML only holds the first batch,
+//! fetch the site's JSON pagination API page by page and merge the item text into the scrape.
+
+use crate::rust_scraper::RustScraper;
+use crate::types::ScrapeResponse;
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::{debug, warn};
+use url::Url;
+
+/// One domain's pagination API, as configured in `SCRAPE_PAGINATION_RULES` (a JSON array)
+#[derive(Debug, Clone, Deserialize)]
+pub struct PaginationRule {
+    /// Regex matched against the page host
+    pub host: String,
+    /// API URL template; `{page}` is the page number, `{path}` the page path, `{url}` the encoded page URL
+    pub api: String,
+    /// Dot-separated path to the item array in each JSON page (empty when the page is the array)
+    #[serde(default)]
+    pub items: String,
+    /// Item fields holding text, joined per item; items that are plain strings are used as-is
+    #[serde(default)]
+    pub text_fields: Vec<String>,
+    /// First API page to fetch; the HTML usually already holds page 1
+    #[serde(default = "default_start_page")]
+    pub start_page: u32,
+    #[serde(default = "default_max_pages")]
+    pub max_pages: u32,
+}
+
+fn default_start_page() -> u32 {
+    2
+}
+
+fn default_max_pages() -> u32 {
+    5
+}
+
+/// Pagination rules keyed by host regex; the first matching rule wins
+#[derive(Debug, Clone, Default)]
+pub struct PaginationRules {
+    rules: Vec<(Regex, PaginationRule)>,
+}
+
+impl PaginationRules {
+    pub fn parse(json: &str) -> Result<Self> {
+        let rules: Vec<PaginationRule> = serde_json::from_str(json).map_err(|e| anyhow!("Invalid pagination rules: {}", e))?;
+        let rules = rules
+            .into_iter()
+            .map(|rule| {
+                let pattern = Regex::new(&rule.host).map_err(|e| anyhow!("Invalid pagination host pattern '{}': {}", rule.host, e))?;
+                Ok((pattern, rule))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Rules from `SCRAPE_PAGINATION_RULES`; invalid config is logged and ignored
+    pub fn from_env() -> Self {
+        let Ok(json) = std::env::var("SCRAPE_PAGINATION_RULES") else { return Self::default() };
+        if json.trim().is_empty() {
+            return Self::default();
+        }
+        Self::parse(&json).unwrap_or_else(|e| {
+            warn!("{}", e);
+            Self::default()
+        })
+    }
+
+    pub fn rule_for(&self, url: &Url) -> Option<&PaginationRule> {
+        let host = url.host_str()?;
+        self.rules.iter().find(|(pattern, _)| pattern.is_match(host)).map(|(_, rule)| rule)
+    }
+}
+
+impl PaginationRule {
+    fn page_url(&self, page_url: &Url, page: u32) -> String {
+        let encoded: String = url::form_urlencoded::byte_serialize(page_url.as_str().as_bytes()).collect();
+        self.api
+            .replace("{page}", &page.to_string())
+            .replace("{path}", page_url.path())
+            .replace("{url}", &encoded)
+    }
+
+    /// Text of each item in one JSON page; `None` when the item array is missing
+    fn item_texts(&self, page: &Value) -> Option<Vec<String>> {
+        let items = self
+            .items
+            .split('.')
+            .filter(|key| !key.is_empty())
+            .try_fold(page, |value, key| value.get(key))?
+            .as_array()?;
+        let texts = items
+            .iter()
+            .filter_map(|item| {
+                let text = match item {
+                    Value::String(s) => s.trim().to_string(),
+                    _ => self
+                        .text_fields
+                        .iter()
+                        .filter_map(|field| item.get(field).and_then(Value::as_str))
+                        .map(str::trim)
+                        .filter(|s| !s.is_empty())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                };
+                (!text.is_empty()).then_some(text)
+            })
+            .collect();
+        Some(texts)
+    }
+}
+
+/// Fetch follow-up API pages until one comes back empty or fails, or `max_pages` is reached,
+/// and append their item text to the result. Returns the number of pages merged.
+/// API URLs are fetched through `scraper`'s client and address check, so they can't reach private hosts either.
+pub async fn merge_pages(scraper: &RustScraper, rule: &PaginationRule, result: &mut ScrapeResponse) -> u32 {
+    let Ok(page_url) = Url::parse(&result.url) else { return 0 };
+    // Keep the layout of the extracted text: paragraph modes put one item per paragraph
+    let separator = if result.clean_content.contains('\n') { "\n\n" } else { " " };
+    let mut merged = 0;
+    for page in rule.start_page..rule.start_page.saturating_add(rule.max_pages) {
+        let api_url = rule.page_url(&page_url, page);
+        let body = async {
+            let parsed = Url::parse(&api_url)?;
+            scraper.check_address(&parsed).await?;
+            Ok::<_, anyhow::Error>(scraper.client().get(&api_url).send().await?.error_for_status()?.json::<Value>().await?)
+        };
+        let texts = match body.await {
+            Ok(json) => rule.item_texts(&json).unwrap_or_default(),
+            Err(e) => {
+                debug!("Pagination API {} failed: {}", api_url, e);
+                break;
+            }
+        };
+        if texts.is_empty() {
+            break;
+        }
+        for text in texts {
+            if !result.clean_content.is_empty() {
+                result.clean_content.push_str(separator);
+            }
+            result.word_count += crate::rust_scraper::count_words(&text);
+            result.char_count += crate::rust_scraper::count_chars(&text);
+            result.clean_content.push_str(&text);
+        }
+        merged += 1;
+    }
+    if merged > 0 {
+        result.reading_time_minutes = Some(((result.word_count as f64 / 200.0).ceil() as u32).max(1));
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Query, response::Html, routing::get, Json, Router};
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_merges_json_pages_until_empty() {
+        let app = Router::new()
+            .route("/feed", get(|| async {
+                Html("<html><head><title>Feed</title></head><body><article><p>First post in the feed.</p></article></body></html>")
+            }))
+            .route("/api/feed", get(|Query(q): Query<HashMap<String, String>>| async move {
+                let page: u32 = q["page"].parse().unwrap();
+                let posts = match page {
+                    2 => vec![serde_json::json!({"title": "Second", "body": "post text"})],
+                    3 => vec![serde_json::json!({"title": "Third", "body": "post text"})],
+                    _ => vec![],
+                };
+                Json(serde_json::json!({ "data": { "posts": posts } }))
+            }));
+        let base = crate::test_support::serve(app).await;
+        let rules = PaginationRules::parse(
+            r#"[{"host": "^127\\.0\\.0\\.1$", "api": "{origin}/api{path}?page={page}", "items": "data.posts", "text_fields": ["title", "body"]}]"#
+                .replace("{origin}", &base)
+                .as_str(),
+        )
+        .unwrap();
+
+        let mut result = crate::test_support::empty_scrape_response(&format!("{}/feed", base));
+        result.clean_content = "First post in the feed.".to_string();
+        result.word_count = 5;
+        let rule = rules.rule_for(&Url::parse(&result.url).unwrap()).unwrap();
+        let guarded = merge_pages(&RustScraper::new().with_allow_private_addresses(false), rule, &mut result.clone()).await;
+        assert_eq!(guarded, 0);
+        let merged = merge_pages(&crate::test_support::local_scraper(), rule, &mut result).await;
+
+        assert_eq!(merged, 2);
+        assert_eq!(result.clean_content, "First post in the feed. Second post text Third post text");
+        assert_eq!(result.word_count, 11);
+        assert!(rules.rule_for(&Url::parse("https://example.com/feed").unwrap()).is_none());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,142 @@
+//! Lexical relevance: Okapi BM25 of texts against a query. Re-ranks search results from any source
+//! by title and snippet, ignoring engine scores, and picks a page's best passage for excerpts.
+
+use crate::types::{RankedResult, SearchResult};
+use std::collections::{HashMap, HashSet};
+
+/// Term-frequency saturation
+const K1: f64 = 1.2;
+/// Length normalization (0 ignores document length, 1 fully normalizes)
+const B: f64 = 0.75;
+
+/// Most results one `/rerank` request may carry
+pub const MAX_RERANK_RESULTS: usize = 500;
+
+/// Words per passage considered for an excerpt; sentences are grouped up to about this length
+const PASSAGE_WORDS: usize = 40;
+
+/// BM25 score of each text against `query`, with the texts themselves as the collection
+fn bm25<S: AsRef<str>>(query: &str, texts: &[S]) -> Vec<f64> {
+    let terms: HashSet<String> = crate::local_index::tokenize(query).collect();
+    let docs: Vec<HashMap<String, u32>> = texts
+        .iter()
+        .map(|text| {
+            let mut freqs = HashMap::new();
+            for term in crate::local_index::tokenize(text.as_ref()) {
+                *freqs.entry(term).or_insert(0) += 1;
+            }
+            freqs
+        })
+        .collect();
+    let lengths: Vec<f64> = docs.iter().map(|d| d.values().sum::<u32>() as f64).collect();
+    let avg_len = (lengths.iter().sum::<f64>() / lengths.len().max(1) as f64).max(1.0);
+    let n = docs.len() as f64;
+    let idf: HashMap<&String, f64> = terms
+        .iter()
+        .map(|t| {
+            let df = docs.iter().filter(|d| d.contains_key(t)).count() as f64;
+            (t, (1.0 + (n - df + 0.5) / (df + 0.5)).ln())
+        })
+        .collect();
+
+    docs.iter()
+        .zip(&lengths)
+        .map(|(doc, &len)| {
+            terms
+                .iter()
+                .filter_map(|t| {
+                    let tf = *doc.get(t)? as f64;
+                    Some(idf[t] * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * len / avg_len)))
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// Score `results` against `query` and return them best first. Results the query doesn't touch
+/// score 0 and keep their input order after the matches.
+pub fn rerank(query: &str, results: Vec<SearchResult>) -> Vec<RankedResult> {
+    let texts: Vec<String> = results.iter().map(|r| format!("{} {}", r.title, r.content)).collect();
+    let mut ranked: Vec<RankedResult> = results
+        .into_iter()
+        .zip(bm25(query, &texts))
+        .map(|(result, relevance)| RankedResult { result, relevance })
+        .collect();
+    // Stable, so ties keep the caller's order
+    ranked.sort_by(|a, b| b.relevance.total_cmp(&a.relevance));
+    ranked
+}
+
+/// The passage of `text` most relevant to `query`, cut to `max_chars`; `None` when no passage
+/// contains a query term. Passages are runs of whole sentences of about `PASSAGE_WORDS` words.
+pub fn best_passage(query: &str, text: &str, max_chars: usize) -> Option<String> {
+    let mut passages: Vec<String> = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    for word in text.split_whitespace() {
+        current.push(word);
+        let sentence_end = word.ends_with(['.', '!', '?']);
+        if (sentence_end && current.len() >= PASSAGE_WORDS / 2) || current.len() >= PASSAGE_WORDS * 2 {
+            passages.push(current.join(" "));
+            current.clear();
+        }
+    }
+    if !current.is_empty() {
+        passages.push(current.join(" "));
+    }
+    let scores = bm25(query, &passages);
+    let (best, _) = passages
+        .iter()
+        .zip(scores)
+        .filter(|(_, score)| *score > 0.0)
+        // First passage wins ties
+        .fold(None, |best: Option<(&String, f64)>, (p, s)| match best {
+            Some((_, top)) if top >= s => best,
+            _ => Some((p, s)),
+        })?;
+    Some(crate::mcp::preview(best, max_chars))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_most_relevant_result_ranks_first() {
+        let result = |url: &str, title: &str, content: &str, score: f64| SearchResult {
+            url: url.to_string(),
+            title: title.to_string(),
+            content: content.to_string(),
+            score: Some(score),
+            ..Default::default()
+        };
+        let results = vec![
+            result("https://a.example/", "Cooking pasta", "Boil water, add salt and pasta.", 9.0),
+            result("https://b.example/", "Rust ownership", "The borrow checker enforces ownership rules in Rust.", 1.0),
+            result("https://c.example/", "Rust belt history", "Factories across the region closed.", 5.0),
+            result("https://d.example/", "Gardening", "Tomatoes need sun.", 2.0),
+        ];
+        let ranked = rerank("rust borrow checker", results);
+        let urls: Vec<&str> = ranked.iter().map(|r| r.result.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://b.example/", "https://c.example/", "https://a.example/", "https://d.example/"]);
+        assert!(ranked[0].relevance > ranked[1].relevance && ranked[1].relevance > 0.0);
+        assert_eq!(ranked[2].relevance, 0.0);
+        // Engine scores are carried through untouched
+        assert_eq!(ranked[0].result.score, Some(1.0));
+
+        let json = serde_json::to_value(&ranked[0]).unwrap();
+        assert_eq!(json["url"], "https://b.example/");
+        assert!(json["relevance"].as_f64().unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_best_passage() {
+        let text = "Our company was founded in 1998 and has offices in four countries. We value teamwork and \
+            open communication across all of our teams. The borrow checker rejects programs where a reference \
+            outlives the value it points to, which is how Rust prevents dangling pointers. Contact us for \
+            more information about careers, partnerships and press enquiries.";
+        let passage = best_passage("rust borrow checker", text, 300).unwrap();
+        assert!(passage.starts_with("The borrow checker rejects"), "{}", passage);
+        assert!(best_passage("kubernetes", text, 300).is_none());
+        assert!(best_passage("borrow", text, 20).unwrap().chars().count() <= 21);
+    }
+}
\ No newline at end of file
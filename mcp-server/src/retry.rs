@@ -0,0 +1,83 @@
+use anyhow::Result;
+use backoff::future::retry;
+use backoff::ExponentialBackoff;
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Maximum attempts per retried outbound call, from `RETRY_MAX_ATTEMPTS` (default 4)
+pub fn max_attempts() -> u32 {
+    std::env::var("RETRY_MAX_ATTEMPTS")
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(4)
+}
+
+/// Like `backoff::future::retry`, but also stops after `max_attempts` attempts,
+/// so a fast-failing endpoint can't be hammered for the whole elapsed-time budget.
+pub async fn retry_capped<T, F, Fut>(backoff: ExponentialBackoff, max_attempts: u32, mut operation: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, backoff::Error<anyhow::Error>>>,
+{
+    let attempts = AtomicU32::new(0);
+    retry(backoff, || {
+        let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+        let fut = operation();
+        async move {
+            match fut.await {
+                Err(backoff::Error::Transient { err, .. }) if attempt >= max_attempts => {
+                    Err(backoff::Error::permanent(err.context(format!("giving up after {} attempts", attempt))))
+                }
+                other => other,
+            }
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use backoff::ExponentialBackoffBuilder;
+    use std::sync::atomic::AtomicU32;
+    use std::time::Duration;
+
+    fn fast_backoff() -> ExponentialBackoff {
+        ExponentialBackoffBuilder::new()
+            .with_initial_interval(Duration::from_millis(1))
+            .with_max_interval(Duration::from_millis(2))
+            .with_max_elapsed_time(Some(Duration::from_secs(5)))
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_retry_capped_stops_after_max_attempts() {
+        let calls = AtomicU32::new(0);
+        let result: Result<()> = retry_capped(fast_backoff(), 3, || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Err(backoff::Error::transient(anyhow!("boom"))) }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_capped_returns_success() {
+        let calls = AtomicU32::new(0);
+        let result = retry_capped(fast_backoff(), 5, || {
+            let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                if n < 2 {
+                    Err(backoff::Error::transient(anyhow!("flaky")))
+                } else {
+                    Ok(n)
+                }
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 2);
+    }
+}
@@ -0,0 +1,268 @@
+//! robots.txt parsing and matching (RFC 9309), with a process-wide cache of parsed files per origin
+//! and per-origin `Crawl-delay` pacing.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Longest `Crawl-delay` honored; larger values would outlast any request timeout
+pub const MAX_CRAWL_DELAY: Duration = Duration::from_secs(10);
+
+/// Longest wait for a fetch slot; scrapes hold outbound permits while they wait, so a longer queue
+/// fails with the remaining wait instead
+pub const MAX_CRAWL_WAIT: Duration = Duration::from_secs(2);
+
+/// Origins whose robots.txt is kept; the cache starts over when it fills up
+const MAX_CACHED_ORIGINS: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Group {
+    agents: Vec<String>,
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+/// A parsed robots.txt. A missing or unreadable file is `RobotsTxt::default()`, which allows everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RobotsTxt {
+    groups: Vec<Group>,
+}
+
+impl RobotsTxt {
+    /// Parse a robots.txt body. Consecutive `User-agent` lines share the rules that follow them;
+    /// unknown fields (`Sitemap`, `Host`) and rules before any `User-agent` are ignored.
+    pub fn parse(text: &str) -> Self {
+        let mut groups: Vec<Group> = Vec::new();
+        let mut in_agents = false;
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once(':') else { continue };
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    if !in_agents {
+                        groups.push(Group::default());
+                        in_agents = true;
+                    }
+                    if let Some(group) = groups.last_mut() {
+                        group.agents.push(value.to_ascii_lowercase());
+                    }
+                }
+                key @ ("allow" | "disallow") => {
+                    in_agents = false;
+                    // An empty Disallow allows everything, the same as no rule
+                    if let Some(group) = groups.last_mut().filter(|_| !value.is_empty()) {
+                        group.rules.push(Rule { allow: key == "allow", pattern: value.to_string() });
+                    }
+                }
+                "crawl-delay" => {
+                    in_agents = false;
+                    if let Some(group) = groups.last_mut() {
+                        // Values too large for a Duration (or infinite) are taken as the cap rather than panicking
+                        group.crawl_delay = value
+                            .parse::<f64>()
+                            .ok()
+                            .filter(|d| *d >= 0.0)
+                            .map(|d| Duration::try_from_secs_f64(d).map_or(MAX_CRAWL_DELAY, |d| d.min(MAX_CRAWL_DELAY)));
+                    }
+                }
+                _ => {}
+            }
+        }
+        Self { groups }
+    }
+
+    /// Groups for `user_agent`: those naming the longest prefix of its product token, else the `*` groups
+    fn groups_for(&self, user_agent: &str) -> Vec<&Group> {
+        let token = user_agent.split('/').next().unwrap_or("").trim().to_ascii_lowercase();
+        let specific = self
+            .groups
+            .iter()
+            .flat_map(|g| g.agents.iter().filter(|a| *a != "*" && token.starts_with(a.as_str())).map(move |a| (a.len(), g)))
+            .max_by_key(|(len, _)| *len)
+            .map(|(len, _)| len);
+        self.groups
+            .iter()
+            .filter(|g| match specific {
+                Some(len) => g.agents.iter().any(|a| a.len() == len && *a != "*" && token.starts_with(a.as_str())),
+                None => g.agents.iter().any(|a| a == "*"),
+            })
+            .collect()
+    }
+
+    /// Whether `user_agent` may fetch `path` (path plus query). The longest matching rule wins and
+    /// `Allow` wins a tie; no matching rule means allowed.
+    pub fn is_allowed(&self, user_agent: &str, path: &str) -> bool {
+        if path == "/robots.txt" {
+            return true;
+        }
+        self.groups_for(user_agent)
+            .into_iter()
+            .flat_map(|g| &g.rules)
+            .filter(|r| pattern_matches(&r.pattern, path))
+            .max_by_key(|r| (r.pattern.len(), r.allow))
+            .is_none_or(|r| r.allow)
+    }
+
+    /// The `Crawl-delay` for `user_agent`, capped at `MAX_CRAWL_DELAY`
+    pub fn crawl_delay(&self, user_agent: &str) -> Option<Duration> {
+        self.groups_for(user_agent).into_iter().find_map(|g| g.crawl_delay).map(|d| d.min(MAX_CRAWL_DELAY))
+    }
+}
+
+/// robots.txt path pattern: a prefix match where `*` matches any run of characters and a trailing `$`
+/// anchors the end
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(p) => (p, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = path.strip_prefix(parts.next().unwrap_or("")) else { return false };
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        if anchored && i == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+fn cache() -> &'static Mutex<HashMap<String, Arc<RobotsTxt>>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, Arc<RobotsTxt>>>> = OnceLock::new();
+    CACHE.get_or_init(Mutex::default)
+}
+
+/// The cached robots.txt of `origin` (e.g. `https://example.com`), if it was fetched before
+pub fn cached(origin: &str) -> Option<Arc<RobotsTxt>> {
+    cache().lock().unwrap().get(origin).cloned()
+}
+
+/// Keep `robots` for `origin` for the rest of the process
+pub fn store(origin: &str, robots: RobotsTxt) -> Arc<RobotsTxt> {
+    let robots = Arc::new(robots);
+    let mut cache = cache().lock().unwrap();
+    if cache.len() >= MAX_CACHED_ORIGINS {
+        cache.clear();
+    }
+    cache.insert(origin.to_string(), Arc::clone(&robots));
+    robots
+}
+
+/// Wait for `origin`'s next fetch slot so fetches there start at least `delay` apart, across
+/// concurrent scrapes. A slot more than `MAX_CRAWL_WAIT` away isn't taken; the wait is returned instead.
+pub async fn wait_crawl_delay(origin: &str, delay: Duration) -> Result<(), Duration> {
+    static NEXT_SLOT: OnceLock<Mutex<HashMap<String, Instant>>> = OnceLock::new();
+    let slot = {
+        let mut slots = NEXT_SLOT.get_or_init(Mutex::default).lock().unwrap();
+        let now = Instant::now();
+        slots.retain(|_, next| *next > now);
+        let slot = slots.get(origin).copied().unwrap_or(now).max(now);
+        if slot - now > MAX_CRAWL_WAIT {
+            return Err(slot - now);
+        }
+        // Overflowing `Instant` arithmetic would panic with the lock held and poison it for every host
+        slots.insert(origin.to_string(), slot.checked_add(delay.min(MAX_CRAWL_DELAY)).unwrap_or(slot));
+        slot
+    };
+    tokio::time::sleep_until(slot.into()).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allow_and_disallow_rules() {
+        let robots = RobotsTxt::parse(
+            "# comments are ignored\n\
+             User-agent: *\n\
+             Disallow: /private/\n\
+             Allow: /private/press/\n\
+             Disallow: /*.pdf$\n\
+             Disallow:\n\
+             Sitemap: https://example.com/sitemap.xml\n",
+        );
+        assert!(robots.is_allowed("mcp-server", "/blog/post"));
+        assert!(!robots.is_allowed("mcp-server", "/private/notes"));
+        assert!(robots.is_allowed("mcp-server", "/private/press/launch"));
+        assert!(!robots.is_allowed("mcp-server", "/files/report.pdf"));
+        assert!(robots.is_allowed("mcp-server", "/files/report.pdf?download=1"));
+        assert!(robots.is_allowed("mcp-server", "/robots.txt"));
+
+        // Equal-length rules: Allow wins
+        let tie = RobotsTxt::parse("User-agent: *\nDisallow: /page\nAllow: /page\n");
+        assert!(tie.is_allowed("mcp-server", "/page"));
+
+        assert!(RobotsTxt::default().is_allowed("mcp-server", "/anything"));
+        assert!(RobotsTxt::parse("").is_allowed("mcp-server", "/anything"));
+    }
+
+    #[test]
+    fn test_wildcard_and_specific_agents() {
+        let robots = RobotsTxt::parse(
+            "User-agent: *\n\
+             Disallow: /\n\
+             Crawl-delay: 60\n\
+             \n\
+             User-agent: Googlebot\n\
+             User-agent: MCP-Server\n\
+             Disallow: /search\n\
+             Crawl-delay: 1.5\n",
+        );
+        // The named group replaces the wildcard group entirely
+        assert!(robots.is_allowed("MCP-Server/1.0", "/docs"));
+        assert!(!robots.is_allowed("mcp-server", "/search?q=x"));
+        assert_eq!(robots.crawl_delay("mcp-server"), Some(Duration::from_millis(1500)));
+        // Everyone else falls back to `*`, whose long delay is capped
+        assert!(!robots.is_allowed("OtherBot", "/docs"));
+        assert_eq!(robots.crawl_delay("OtherBot"), Some(MAX_CRAWL_DELAY));
+
+        assert!(pattern_matches("/a*/c", "/abb/c/d"));
+        assert!(pattern_matches("/*/c$", "/x/c"));
+        assert!(!pattern_matches("/*/c$", "/x/c/d"));
+    }
+
+    #[tokio::test]
+    async fn test_crawl_delay_spaces_fetches() {
+        let origin = "http://crawl-delay.test";
+        let started = Instant::now();
+        for _ in 0..3 {
+            wait_crawl_delay(origin, Duration::from_millis(100)).await.unwrap();
+        }
+        assert!(started.elapsed() >= Duration::from_millis(200));
+
+        // A queue longer than MAX_CRAWL_WAIT is refused with the remaining wait, and takes no slot
+        let slow = "http://slow-crawl-delay.test";
+        wait_crawl_delay(slow, MAX_CRAWL_DELAY).await.unwrap();
+        let wait = wait_crawl_delay(slow, MAX_CRAWL_DELAY).await.unwrap_err();
+        assert!(wait > MAX_CRAWL_WAIT && wait <= MAX_CRAWL_DELAY, "{:?}", wait);
+        assert!(wait_crawl_delay(slow, MAX_CRAWL_DELAY).await.unwrap_err() <= wait);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_crawl_delay_is_capped() {
+        for value in ["99999999999999999999", "1e300", "inf"] {
+            let robots = RobotsTxt::parse(&format!("User-agent: *\nCrawl-delay: {}\n", value));
+            assert_eq!(robots.crawl_delay("mcp-server"), Some(MAX_CRAWL_DELAY), "{}", value);
+        }
+        assert_eq!(RobotsTxt::parse("User-agent: *\nCrawl-delay: -1\n").crawl_delay("mcp-server"), None);
+
+        // An absurd delay passed straight in neither panics nor poisons the pacing state for other hosts
+        wait_crawl_delay("http://huge-delay.test", Duration::MAX).await.unwrap();
+        let started = Instant::now();
+        wait_crawl_delay("http://other-host.test", Duration::from_millis(10)).await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
\ No newline at end of file
@@ -1,6 +1,9 @@
 use crate::types::*;
+use crate::error::ScrapeError;
+use crate::extractors::ExtractorRegistry;
+use crate::robots::RobotsTxt;
 use anyhow::{anyhow, Result};
-use chrono::Utc;
+use chrono::{Datelike, Utc};
 use rand::Rng;
 use readability::extractor;
 use regex::Regex;
@@ -8,7 +11,8 @@ use reqwest::Client;
 use scraper::{Html, Selector};
 use select::{document::Document as SelectDoc, predicate::{Name as SelName, Attr as SelAttr, Predicate}};
 use std::collections::HashSet;
-use tracing::{info, warn};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
 use url::Url;
 use whatlang::{detect, Lang};
 
@@ -22,20 +26,576 @@ const USER_AGENTS: &[&str] = &[
     "Mozilla/5.0 (X11; Ubuntu; Linux x86_64; rv:89.0) Gecko/20100101 Firefox/89.0",
 ];
 
+/// Built-in boilerplate lines, matched against the whole normalized line (see `normalize_garbage_candidate`)
+const DEFAULT_GARBAGE_LINES: &[&str] = &[
+    r"share( this( article| post| page| story)?| on \w+)?",
+    r"subscribe( now| today| for free| to (our|the) newsletter)?",
+    r"sign up( now| for free| for (our|the) newsletter)?",
+    r"(accept|allow|reject)( all)?( cookies)?",
+    r"cookie (settings|preferences|policy)",
+    r"advertisement|sponsored( content)?|ad",
+    r"(our )?newsletter",
+    r"related (articles|posts|stories)",
+    r"comments?|\d+ comments?|leave a (comment|reply)",
+    r"read more|continue reading|show more",
+    r"terms of (service|use)|privacy policy",
+];
+
+/// User-supplied extra garbage lines from `SCRAPE_GARBAGE_PATTERNS` (regexes separated by `;`)
+fn extra_garbage_patterns() -> Vec<String> {
+    std::env::var("SCRAPE_GARBAGE_PATTERNS")
+        .map(|v| v.split(';').map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// Whether `file://` URLs may be scraped (`SCRAPE_EXTRA_SCHEMES=file`). Off by default: it exposes the
+/// local filesystem, so only enable it for fixture testing and offline ingestion in controlled setups.
+pub fn file_scheme_enabled() -> bool {
+    std::env::var("SCRAPE_EXTRA_SCHEMES").is_ok_and(|v| {
+        v.split(',').map(str::trim).filter(|s| !s.is_empty()).any(|scheme| {
+            if scheme.eq_ignore_ascii_case("file") {
+                return true;
+            }
+            warn!("Ignoring unsupported scheme '{}' in SCRAPE_EXTRA_SCHEMES", scheme);
+            false
+        })
+    })
+}
+
+/// Whether the scraper client accepts invalid TLS certificates (`SCRAPER_ACCEPT_INVALID_CERTS=1`), for
+/// internal services with self-signed certificates. Off by default; warns once when enabled.
+pub fn accept_invalid_certs() -> bool {
+    static WARNED: std::sync::Once = std::sync::Once::new();
+    let enabled = matches!(std::env::var("SCRAPER_ACCEPT_INVALID_CERTS").as_deref(), Ok("1") | Ok("true"));
+    if enabled {
+        WARNED.call_once(|| {
+            warn!("SCRAPER_ACCEPT_INVALID_CERTS is set: TLS certificates are NOT verified when scraping; use only for trusted internal hosts");
+        });
+    }
+    enabled
+}
+
+/// Whether scrapes honor robots.txt (`SCRAPER_RESPECT_ROBOTS=1`); off by default
+pub fn respect_robots_enabled() -> bool {
+    matches!(std::env::var("SCRAPER_RESPECT_ROBOTS").as_deref(), Ok("1") | Ok("true"))
+}
+
+/// Product token matched against robots.txt `User-agent` lines (`SCRAPER_ROBOTS_AGENT`, default `mcp-server`)
+pub fn robots_agent() -> String {
+    std::env::var("SCRAPER_ROBOTS_AGENT")
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "mcp-server".to_string())
+}
+
+/// Largest robots.txt read; RFC 9309 asks crawlers to read at least 500 KiB
+const MAX_ROBOTS_BYTES: usize = 512 * 1024;
+
+/// Scraper HTTP client; only this client (never the search client) may skip certificate checks.
+/// Unless private addresses are allowed, names resolve and redirects are followed only to public addresses.
+fn scraper_client(timeout: std::time::Duration, accept_invalid_certs: bool, allow_private: bool, max_redirects: usize, cookies: Option<Arc<reqwest::cookie::Jar>>) -> Client {
+    let builder = crate::http_client_builder_with_timeout(timeout)
+        .redirect(crate::ssrf::redirect_policy(allow_private, max_redirects))
+        .danger_accept_invalid_certs(accept_invalid_certs);
+    let builder = if allow_private { builder } else { builder.dns_resolver(Arc::new(crate::ssrf::PublicResolver)) };
+    match cookies {
+        Some(jar) => builder.cookie_provider(jar),
+        None => builder,
+    }
+    .build()
+    .expect("Failed to create HTTP client")
+}
+
+/// A form login and the cookie jar of its session, private to one scraper
+struct LoginSession {
+    login: FormLogin,
+    jar: Arc<reqwest::cookie::Jar>,
+    logged_in: tokio::sync::OnceCell<()>,
+}
+
+/// Map a whatlang language to a short language code
+fn lang_code(lang: Lang) -> String {
+    match lang {
+        Lang::Eng => "en".to_string(),
+        Lang::Spa => "es".to_string(),
+        Lang::Fra => "fr".to_string(),
+        Lang::Deu => "de".to_string(),
+        Lang::Ita => "it".to_string(),
+        Lang::Por => "pt".to_string(),
+        Lang::Rus => "ru".to_string(),
+        Lang::Jpn => "ja".to_string(),
+        Lang::Kor => "ko".to_string(),
+        Lang::Cmn => "zh".to_string(),
+        _ => format!("{:?}", lang).to_lowercase(),
+    }
+}
+
+/// Split paragraph-preserving text into `(language code, text)` runs, detecting each paragraph
+/// separately. Paragraphs too short to detect reliably join the run before them; adjacent
+/// paragraphs in the same language are merged.
+pub fn language_segments(text: &str) -> Vec<(String, String)> {
+    let mut segments: Vec<(String, String)> = Vec::new();
+    for paragraph in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        let detected = detect(paragraph).filter(|info| info.is_reliable()).map(|info| lang_code(info.lang()));
+        match (segments.last_mut(), detected) {
+            (Some((code, run)), lang) if lang.as_ref().is_none_or(|l| l == code) || code == "unknown" => {
+                // A leading undetected run takes the language of the first detected paragraph
+                if let Some(lang) = lang {
+                    *code = lang;
+                }
+                run.push_str("\n\n");
+                run.push_str(paragraph);
+            }
+            (_, lang) => segments.push((lang.unwrap_or_else(|| "unknown".to_string()), paragraph.to_string())),
+        }
+    }
+    segments
+}
+
+/// Positive integer from the environment, or `default`
+fn env_limit(name: &str, default: usize) -> usize {
+    std::env::var(name).ok().and_then(|v| v.parse::<usize>().ok()).filter(|n| *n > 0).unwrap_or(default)
+}
+
+/// One case-insensitive regex matching any full garbage line; invalid user patterns are skipped
+fn garbage_line_regex(extra: &[String]) -> Regex {
+    let mut alternatives: Vec<String> = DEFAULT_GARBAGE_LINES.iter().map(|p| p.to_string()).collect();
+    for pattern in extra {
+        match Regex::new(pattern) {
+            Ok(_) => alternatives.push(pattern.clone()),
+            Err(e) => warn!("Ignoring invalid garbage pattern '{}': {}", pattern, e),
+        }
+    }
+    Regex::new(&format!("(?i)^(?:{})$", alternatives.join("|"))).unwrap()
+}
+
+/// Lowercase, collapse whitespace and strip decorative punctuation ("» Read more →") around a line
+fn normalize_garbage_candidate(line: &str) -> String {
+    line.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+/// How much of a page the metadata fast path reads while looking for `</head>`
+const METADATA_MAX_HEAD_BYTES: usize = 512 * 1024;
+
+/// Default size of the ranged GET used for metadata (`METADATA_RANGE_BYTES`; 0 disables ranged requests)
+const DEFAULT_METADATA_RANGE_BYTES: usize = 64 * 1024;
+
+/// Default page size from which extraction passes run concurrently (`SCRAPE_PARALLEL_EXTRACTION_BYTES`; 0 disables)
+const DEFAULT_PARALLEL_EXTRACTION_BYTES: usize = 256 * 1024;
+
+/// Words a `#content`/`main`/`article` container needs before the mdBook extractor considers it (`SCRAPE_MDBOOK_MIN_WORDS`)
+const DEFAULT_MDBOOK_MIN_WORDS: usize = 50;
+
+/// Lead over readability's word count the mdBook score needs to be chosen (`SCRAPE_MDBOOK_MARGIN_WORDS`)
+const DEFAULT_MDBOOK_MARGIN_WORDS: usize = 0;
+
+/// Share of an extraction's text that may be link text before it is taken for navigation and the
+/// next strategy is tried (`SCRAPE_MAX_LINK_DENSITY`, 0-1)
+const DEFAULT_MAX_LINK_DENSITY: f64 = 0.5;
+
+/// Default cap on downloaded and decompressed body size (10 MiB)
+const DEFAULT_MAX_BODY_BYTES: usize = 10 * 1024 * 1024;
+
+/// Default cap on headings returned per page (`SCRAPE_MAX_HEADINGS`)
+const DEFAULT_MAX_HEADINGS: usize = 200;
+
+/// Default cap on characters per heading text (`SCRAPE_MAX_HEADING_CHARS`)
+const DEFAULT_MAX_HEADING_CHARS: usize = 300;
+
+/// Default caps on structured-data objects returned per page and on their total serialized size
+/// (`SCRAPE_MAX_STRUCTURED_DATA`, `SCRAPE_MAX_STRUCTURED_DATA_BYTES`)
+const DEFAULT_MAX_STRUCTURED_DATA: usize = 20;
+const DEFAULT_MAX_STRUCTURED_DATA_BYTES: usize = 64 * 1024;
+
+/// Per-call limits for [`RustScraper::scrape_url_with_options`]; `None` keeps the scraper's own setting
+#[derive(Debug, Clone, Default)]
+pub struct ScrapeOptions {
+    /// Whole-request timeout, from connecting until the body is read (defaults to `HTTP_TIMEOUT_MS`)
+    pub timeout: Option<std::time::Duration>,
+    /// Redirects followed before giving up (default 10)
+    pub max_redirects: Option<usize>,
+    /// Largest body downloaded; the transfer is aborted as soon as it is exceeded (defaults to `SCRAPE_MAX_BODY_BYTES`)
+    pub max_body_bytes: Option<usize>,
+    /// Sent instead of a random browser User-Agent
+    pub user_agent: Option<String>,
+}
+
 /// Enhanced Rust-native web scraper
+#[derive(Clone)]
 pub struct RustScraper {
     client: Client,
+    accept_invalid_certs: bool,
+    session: Option<Arc<LoginSession>>,
+    max_body_bytes: usize,
+    preserve_whitespace: bool,
+    as_document: bool,
+    as_markdown: bool,
+    keep_paragraphs: bool,
+    keep_raw_urls: bool,
+    keep_inline_state: bool,
+    as_blocks: bool,
+    build_vcard: bool,
+    structured_data: bool,
+    max_structured_data: usize,
+    max_structured_data_bytes: usize,
+    respect_robots: bool,
+    allow_private_addresses: bool,
+    allow_file_scheme: bool,
+    max_headings: usize,
+    max_heading_chars: usize,
+    metadata_range_bytes: usize,
+    parallel_extraction_bytes: usize,
+    mdbook_min_words: usize,
+    mdbook_margin_words: usize,
+    max_link_density: f64,
+    timeout: std::time::Duration,
+    extractors: Arc<ExtractorRegistry>,
+    garbage_line: Regex,
 }
 
 impl RustScraper {
     pub fn new() -> Self {
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .redirect(reqwest::redirect::Policy::limited(10))
-            .build()
-            .expect("Failed to create HTTP client");
+        let timeout = crate::http_timeout();
+        let accept_invalid_certs = accept_invalid_certs();
+        let allow_private_addresses = crate::ssrf::allow_private_addresses();
+        let client = scraper_client(timeout, accept_invalid_certs, allow_private_addresses, crate::ssrf::DEFAULT_MAX_REDIRECTS, None);
+
+        let max_body_bytes = std::env::var("SCRAPE_MAX_BODY_BYTES")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_MAX_BODY_BYTES);
+
+        Self {
+            client,
+            accept_invalid_certs,
+            session: None,
+            max_body_bytes,
+            preserve_whitespace: false,
+            as_document: false,
+            as_markdown: false,
+            keep_paragraphs: false,
+            keep_raw_urls: false,
+            keep_inline_state: false,
+            as_blocks: false,
+            build_vcard: false,
+            structured_data: false,
+            max_structured_data: env_limit("SCRAPE_MAX_STRUCTURED_DATA", DEFAULT_MAX_STRUCTURED_DATA),
+            max_structured_data_bytes: env_limit("SCRAPE_MAX_STRUCTURED_DATA_BYTES", DEFAULT_MAX_STRUCTURED_DATA_BYTES),
+            respect_robots: respect_robots_enabled(),
+            allow_private_addresses,
+            allow_file_scheme: file_scheme_enabled(),
+            max_headings: env_limit("SCRAPE_MAX_HEADINGS", DEFAULT_MAX_HEADINGS),
+            max_heading_chars: env_limit("SCRAPE_MAX_HEADING_CHARS", DEFAULT_MAX_HEADING_CHARS),
+            metadata_range_bytes: std::env::var("METADATA_RANGE_BYTES")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(DEFAULT_METADATA_RANGE_BYTES),
+            parallel_extraction_bytes: std::env::var("SCRAPE_PARALLEL_EXTRACTION_BYTES")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(DEFAULT_PARALLEL_EXTRACTION_BYTES),
+            mdbook_min_words: env_limit("SCRAPE_MDBOOK_MIN_WORDS", DEFAULT_MDBOOK_MIN_WORDS),
+            mdbook_margin_words: std::env::var("SCRAPE_MDBOOK_MARGIN_WORDS")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(DEFAULT_MDBOOK_MARGIN_WORDS),
+            max_link_density: std::env::var("SCRAPE_MAX_LINK_DENSITY")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|d| (0.0..=1.0).contains(d))
+                .unwrap_or(DEFAULT_MAX_LINK_DENSITY),
+            timeout,
+            extractors: Arc::new(ExtractorRegistry::new()),
+            garbage_line: garbage_line_regex(&extra_garbage_patterns()),
+        }
+    }
+
+    /// Run the extraction passes concurrently for pages of at least `bytes` of HTML (0 never does)
+    pub fn with_parallel_extraction(mut self, bytes: usize) -> Self {
+        self.parallel_extraction_bytes = bytes;
+        self
+    }
+
+    /// Accept self-signed and otherwise invalid TLS certificates (defaults to `SCRAPER_ACCEPT_INVALID_CERTS`)
+    pub fn with_invalid_certs(mut self, accept: bool) -> Self {
+        if accept {
+            warn!("Scraper client built without TLS certificate verification");
+        }
+        self.accept_invalid_certs = accept;
+        self.client = scraper_client(self.timeout, accept, self.allow_private_addresses, crate::ssrf::DEFAULT_MAX_REDIRECTS, self.session_jar());
+        self
+    }
+
+    /// Let scrapes reach loopback, private and link-local addresses (defaults to `SCRAPER_ALLOW_PRIVATE_ADDRESSES`)
+    pub fn with_allow_private_addresses(mut self, allow: bool) -> Self {
+        if allow != self.allow_private_addresses {
+            self.allow_private_addresses = allow;
+            self.client = scraper_client(self.timeout, self.accept_invalid_certs, allow, crate::ssrf::DEFAULT_MAX_REDIRECTS, self.session_jar());
+        }
+        self
+    }
+
+    /// The scraper's HTTP client, guarded against private addresses like every scrape fetch;
+    /// pair it with [`Self::check_address`] for the first URL, whose IP literal it doesn't vet
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Unless private addresses are allowed, fail a URL whose host is or resolves to one with `PrivateAddress`.
+    /// Redirects and later connections are guarded by the client itself.
+    pub async fn check_address(&self, url: &Url) -> std::result::Result<(), ScrapeError> {
+        if self.allow_private_addresses {
+            return Ok(());
+        }
+        crate::ssrf::check_url(url).await
+    }
+
+    /// Submit `login` before the first fetch and send its session cookies with every request.
+    /// The cookie jar belongs to this scraper only, so sessions never leak between scrapes.
+    pub fn with_login(mut self, login: FormLogin) -> Self {
+        let jar = Arc::new(reqwest::cookie::Jar::default());
+        self.client = scraper_client(self.timeout, self.accept_invalid_certs, self.allow_private_addresses, crate::ssrf::DEFAULT_MAX_REDIRECTS, Some(Arc::clone(&jar)));
+        self.session = Some(Arc::new(LoginSession { login, jar, logged_in: tokio::sync::OnceCell::new() }));
+        self
+    }
+
+    /// Cookie jar of the login session, if any
+    fn session_jar(&self) -> Option<Arc<reqwest::cookie::Jar>> {
+        self.session.as_ref().map(|s| Arc::clone(&s.jar))
+    }
+
+    /// Post the login form once per session; retried fetches reuse the session cookie
+    async fn ensure_logged_in(&self, target: &Url) -> Result<()> {
+        let Some(session) = &self.session else { return Ok(()) };
+        session
+            .logged_in
+            .get_or_try_init(|| async {
+                let login = &session.login;
+                let failed = |reason: String| ScrapeError::LoginFailed { login_url: login.login_url.clone(), reason };
+                let mut form: Vec<(&str, &str)> = login.extra_fields.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+                form.push((&login.username_field, &login.username));
+                form.push((&login.password_field, &login.password));
+                if let Ok(login_url) = Url::parse(&login.login_url) {
+                    self.check_address(&login_url).await?;
+                }
+                info!("Logging in at {} before scraping {}", login.login_url, target);
+                let response = self
+                    .client
+                    .post(&login.login_url)
+                    .header("User-Agent", self.get_random_user_agent())
+                    .form(&form)
+                    .send()
+                    .await
+                    .map_err(|e| failed(format!("request failed: {}", e.without_url())))?;
+                if !response.status().is_success() {
+                    return Err(failed(format!("HTTP {}", response.status().as_u16())));
+                }
+                if reqwest::cookie::CookieStore::cookies(session.jar.as_ref(), target).is_none() {
+                    return Err(failed(format!("no session cookie was set for {}", target)));
+                }
+                Ok(())
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Container size the mdBook extractor needs (`min_words`) and its required lead over readability (`margin_words`)
+    pub fn with_mdbook_threshold(mut self, min_words: usize, margin_words: usize) -> Self {
+        self.mdbook_min_words = min_words;
+        self.mdbook_margin_words = margin_words;
+        self
+    }
+
+    /// Reject readability output and heuristic containers whose text is more than `density` link text (1 disables)
+    pub fn with_max_link_density(mut self, density: f64) -> Self {
+        self.max_link_density = density;
+        self
+    }
+
+    /// Per-request timeout of the underlying client
+    pub fn timeout(&self) -> std::time::Duration {
+        self.timeout
+    }
+
+    /// Use `timeout` per request instead of the shared `http_timeout`
+    pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = timeout;
+        self.client = scraper_client(timeout, self.accept_invalid_certs, self.allow_private_addresses, crate::ssrf::DEFAULT_MAX_REDIRECTS, self.session_jar());
+        self
+    }
+
+    /// Largest response body the scraper reads (`SCRAPE_MAX_BODY_BYTES`)
+    pub fn max_body_bytes(&self) -> usize {
+        self.max_body_bytes
+    }
+
+    /// Keep indentation and line structure (ASCII tables, poetry, code) instead of collapsing whitespace
+    pub fn with_preserve_whitespace(mut self, preserve: bool) -> Self {
+        self.preserve_whitespace = preserve;
+        self
+    }
+
+    /// Accept `file://` URLs and read them from the local filesystem (defaults to `SCRAPE_EXTRA_SCHEMES`)
+    pub fn with_file_scheme(mut self, allow: bool) -> Self {
+        self.allow_file_scheme = allow;
+        self
+    }
+
+    /// Also return the page's JSON-LD objects as `structured_data`
+    pub fn with_structured_data(mut self, structured_data: bool) -> Self {
+        self.structured_data = structured_data;
+        self
+    }
+
+    /// Return at most `max` structured-data objects totalling at most `max_bytes` of JSON
+    pub fn with_structured_data_limits(mut self, max: usize, max_bytes: usize) -> Self {
+        self.max_structured_data = max;
+        self.max_structured_data_bytes = max_bytes;
+        self
+    }
+
+    /// Return at most `max` headings of at most `max_chars` characters each
+    pub fn with_heading_limits(mut self, max: usize, max_chars: usize) -> Self {
+        self.max_headings = max;
+        self.max_heading_chars = max_chars;
+        self
+    }
+
+    /// Ask for only the first `bytes` of a page when fetching metadata (0 always downloads up to `</head>`)
+    pub fn with_metadata_range(mut self, bytes: usize) -> Self {
+        self.metadata_range_bytes = bytes;
+        self
+    }
+
+    /// Consult these site-specific extractors before the generic pipeline
+    pub fn with_extractors(mut self, extractors: Arc<ExtractorRegistry>) -> Self {
+        self.extractors = extractors;
+        self
+    }
+
+    /// Also drop lines consisting entirely of one of these regexes (in addition to the built-in list)
+    pub fn with_garbage_patterns(mut self, patterns: &[String]) -> Self {
+        self.garbage_line = garbage_line_regex(patterns);
+        self
+    }
+
+    /// Keep blank lines between paragraphs in `clean_content` (whitespace inside paragraphs is still collapsed)
+    pub fn with_paragraphs(mut self, keep_paragraphs: bool) -> Self {
+        self.keep_paragraphs = keep_paragraphs;
+        self
+    }
+
+    /// Also render the main content as a markdown document with its heading hierarchy
+    pub fn with_document(mut self, as_document: bool) -> Self {
+        self.as_document = as_document;
+        self
+    }
+
+    /// Keep the original `href`/`src` of links and images next to the resolved absolute URL
+    pub fn with_raw_urls(mut self, keep: bool) -> Self {
+        self.keep_raw_urls = keep;
+        self
+    }
+
+    /// Also parse JSON state embedded in scripts (`__NEXT_DATA__`, `window.__INITIAL_STATE__ = {...}`)
+    pub fn with_inline_state(mut self, keep: bool) -> Self {
+        self.keep_inline_state = keep;
+        self
+    }
+
+    /// Also return the readability output as typed blocks (heading, paragraph, list item, code, quote)
+    pub fn with_blocks(mut self, as_blocks: bool) -> Self {
+        self.as_blocks = as_blocks;
+        self
+    }
+
+    /// Refuse URLs the site's robots.txt disallows and pace fetches by its `Crawl-delay`
+    pub fn with_respect_robots(mut self, respect: bool) -> Self {
+        self.respect_robots = respect;
+        self
+    }
+
+    /// The robots.txt governing `url`, fetched once per origin and kept for the process lifetime.
+    /// A missing file (any 4xx) allows everything; a 5xx or failed fetch also allows, but isn't cached.
+    async fn robots_for(&self, url: &Url, user_agent: &str) -> Arc<RobotsTxt> {
+        let origin = url.origin().ascii_serialization();
+        if let Some(robots) = crate::robots::cached(&origin) {
+            return robots;
+        }
+        let robots_url = format!("{}/robots.txt", origin);
+        let response = self.client.get(&robots_url).header("User-Agent", user_agent).send().await;
+        let robots = match response {
+            Ok(response) if response.status().is_success() => match read_body_limited(response, MAX_ROBOTS_BYTES).await {
+                Ok(body) => RobotsTxt::parse(&String::from_utf8_lossy(&body)),
+                Err(e) => {
+                    warn!("Could not read {}: {}; allowing", robots_url, e);
+                    return Arc::default();
+                }
+            },
+            Ok(response) if response.status().is_client_error() => RobotsTxt::default(),
+            Ok(response) => {
+                warn!("{} answered HTTP {}; allowing for now", robots_url, response.status().as_u16());
+                return Arc::default();
+            }
+            Err(e) => {
+                warn!("Could not fetch {}: {}; allowing for now", robots_url, e.without_url());
+                return Arc::default();
+            }
+        };
+        crate::robots::store(&origin, robots)
+    }
+
+    /// Whether `user_agent` may fetch `url` under its host's robots.txt. Only http(s) URLs have one.
+    pub async fn is_allowed(&self, url: &str, user_agent: &str) -> Result<bool> {
+        let parsed = Url::parse(url).map_err(|e| ScrapeError::InvalidUrl(format!("Invalid URL '{}': {}", url, e)))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Ok(true);
+        }
+        let path = match parsed.query() {
+            Some(query) => format!("{}?{}", parsed.path(), query),
+            None => parsed.path().to_string(),
+        };
+        Ok(self.robots_for(&parsed, user_agent).await.is_allowed(user_agent, &path))
+    }
+
+    /// With `respect_robots`, fail a disallowed URL with `RobotsDisallowed`
+    pub async fn check_robots(&self, url: &str) -> Result<()> {
+        if self.respect_robots && !self.is_allowed(url, &robots_agent()).await? {
+            return Err(ScrapeError::RobotsDisallowed { url: url.to_string() }.into());
+        }
+        Ok(())
+    }
 
-        Self { client }
+    /// With robots.txt respected, wait for the host's `Crawl-delay` slot. The caller holds outbound permits
+    /// meanwhile, so a slot further off than `robots::MAX_CRAWL_WAIT` fails with `RateLimited` instead.
+    pub async fn wait_crawl_delay(&self, url: &Url) -> std::result::Result<(), ScrapeError> {
+        if !self.respect_robots {
+            return Ok(());
+        }
+        let agent = robots_agent();
+        let Some(delay) = self.robots_for(url, &agent).await.crawl_delay(&agent) else { return Ok(()) };
+        let origin = url.origin().ascii_serialization();
+        crate::robots::wait_crawl_delay(&origin, delay).await.map_err(|wait| ScrapeError::RateLimited {
+            origin,
+            retry_after: std::time::Duration::from_secs(wait.as_secs_f64().ceil() as u64),
+        })
+    }
+
+    /// Also return the main content as markdown with links and images (`markdown_content`)
+    pub fn with_markdown(mut self, as_markdown: bool) -> Self {
+        self.as_markdown = as_markdown;
+        self
+    }
+
+    /// Also assemble the page's emails, phones and postal address into a vCard
+    pub fn with_vcard(mut self, build: bool) -> Self {
+        self.build_vcard = build;
+        self
     }
 
     /// Get a random User-Agent string
@@ -45,33 +605,218 @@ impl RustScraper {
         USER_AGENTS[index]
     }
 
+    /// GET request with a random User-Agent and browser-like headers
+    pub(crate) fn browser_request(&self, url: &str) -> reqwest::RequestBuilder {
+        self.browser_request_with(&self.client, url, self.get_random_user_agent())
+    }
+
+    /// GET request through `client` with browser-like headers and the given User-Agent
+    fn browser_request_with(&self, client: &Client, url: &str, user_agent: &str) -> reqwest::RequestBuilder {
+        client
+            .get(url)
+            .header("User-Agent", user_agent)
+            .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8")
+            .header("Accept-Language", "en-US,en;q=0.5")
+            // Rely on reqwest automatic decompression; remove manual Accept-Encoding to avoid serving compressed body as text
+            .header("DNT", "1")
+            .header("Connection", "keep-alive")
+            .header("Upgrade-Insecure-Requests", "1")
+    }
+
+    /// Metadata fast path: read the body only until `</head>` (bounded) and extract preview fields,
+    /// skipping content extraction entirely
+    pub async fn fetch_metadata(&self, url: &str) -> Result<PageMetadata> {
+        let parsed_url = Url::parse(url).map_err(|e| ScrapeError::InvalidUrl(format!("Invalid URL '{}': {}", url, e)))?;
+        if parsed_url.scheme() != "http" && parsed_url.scheme() != "https" {
+            return Err(ScrapeError::InvalidUrl("URL must use HTTP or HTTPS protocol".into()).into());
+        }
+        self.check_address(&parsed_url).await?;
+        // Servers honoring Range answer 206 with just the prefix; those ignoring it stream the page,
+        // which is read only up to `</head>` anyway
+        let range = self.metadata_range_bytes.min(METADATA_MAX_HEAD_BYTES);
+        if range > 0 {
+            let response = self
+                .browser_request(url)
+                .header(reqwest::header::RANGE, format!("bytes=0-{}", range - 1))
+                .send()
+                .await
+                .map_err(ScrapeError::from_fetch)?;
+            // 416: the page is shorter than the range or the server refuses ranges
+            if response.status() != reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+                let (head, complete) = self.read_head(response, url).await?;
+                // A head longer than the range needs the full document
+                if complete {
+                    return Ok(self.extract_metadata(&parsed_url, &String::from_utf8_lossy(&head)));
+                }
+            }
+        }
+        let response = self.browser_request(url).send().await.map_err(ScrapeError::from_fetch)?;
+        let (head, _) = self.read_head(response, url).await?;
+        Ok(self.extract_metadata(&parsed_url, &String::from_utf8_lossy(&head)))
+    }
+
+    /// Read a response until `</head>` or the metadata byte limit. The flag is false only for a
+    /// partial (206) body that ended before `</head>`.
+    async fn read_head(&self, mut response: reqwest::Response, url: &str) -> Result<(Vec<u8>, bool)> {
+        if !response.status().is_success() {
+            return Err(anyhow!("HTTP {} for {}", response.status().as_u16(), url));
+        }
+        let partial = response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+        let limit = self.max_body_bytes.min(METADATA_MAX_HEAD_BYTES);
+        let mut head = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| anyhow!("Failed to read response body: {}", e))? {
+            head.extend_from_slice(&chunk);
+            let window = head.len().saturating_sub(chunk.len() + 7);
+            if head[window..].windows(7).any(|w| w.eq_ignore_ascii_case(b"</head>")) {
+                return Ok((head, true));
+            }
+            if head.len() >= limit {
+                break;
+            }
+        }
+        Ok((head, !partial))
+    }
+
+    /// Preview metadata from (possibly partial) HTML
+    pub fn extract_metadata(&self, parsed_url: &Url, html: &str) -> PageMetadata {
+        let document = Html::parse_document(html);
+        let (og_title, og_description, og_image) = self.extract_open_graph(&document, parsed_url);
+        let description = Some(self.extract_meta_description(&document))
+            .filter(|d| !d.is_empty())
+            .or(og_description);
+        let title = og_title.filter(|t| !t.is_empty()).unwrap_or_else(|| self.extract_title(&document));
+        PageMetadata {
+            url: parsed_url.to_string(),
+            title,
+            description,
+            og_image,
+            favicon: self.extract_favicon(&document, parsed_url),
+            publisher: self.extract_publisher(&document).or_else(|| parsed_url.host_str().map(|h| h.trim_start_matches("www.").to_string())),
+        }
+    }
+
+    /// Declared icon link, falling back to `/favicon.ico` on the page's origin
+    fn extract_favicon(&self, document: &Html, base: &Url) -> Option<String> {
+        let selector = Selector::parse("link[rel][href]").ok()?;
+        let declared = document.select(&selector).find_map(|el| {
+            let rel = el.value().attr("rel")?.to_ascii_lowercase();
+            rel.split_whitespace()
+                .any(|r| r == "icon" || r == "apple-touch-icon")
+                .then(|| el.value().attr("href"))
+                .flatten()
+        });
+        match declared {
+            Some(href) => base.join(href.trim()).ok().map(|u| u.to_string()),
+            None => base.join("/favicon.ico").ok().map(|u| u.to_string()),
+        }
+    }
+
+    /// Publisher from og:site_name, then JSON-LD `publisher.name`
+    fn extract_publisher(&self, document: &Html) -> Option<String> {
+        self.extract_site_name(document).or_else(|| {
+            let mut publisher = None;
+            for value in self.extract_json_ld(document) {
+                visit_json_ld_nodes(&value, &mut |node| {
+                    if publisher.is_none() {
+                        publisher = node
+                            .get("publisher")
+                            .and_then(|p| p.get("name"))
+                            .and_then(|n| n.as_str())
+                            .map(|n| n.trim().to_string())
+                            .filter(|n| !n.is_empty());
+                    }
+                });
+            }
+            publisher
+        })
+    }
+
+    /// Contact card from `mailto:`/`tel:` links and JSON-LD `email`/`telephone`/`PostalAddress`,
+    /// named after the author, else the publisher, else the host. `None` when nothing contactable is found.
+    fn extract_vcard(&self, document: &Html, author: Option<&str>, base: &Url) -> Option<String> {
+        let mut emails: Vec<String> = Vec::new();
+        let mut phones: Vec<String> = Vec::new();
+        let mut address = None;
+        if let Ok(sel) = Selector::parse("a[href]") {
+            for href in document.select(&sel).filter_map(|el| el.value().attr("href")) {
+                let href = href.trim();
+                if let Some(email) = scheme_value(href, "mailto:").and_then(normalize_email) {
+                    push_unique(&mut emails, email);
+                } else if let Some(phone) = scheme_value(href, "tel:").and_then(normalize_phone) {
+                    push_unique(&mut phones, phone);
+                }
+            }
+        }
+        for value in self.extract_json_ld(document) {
+            visit_json_ld_nodes(&value, &mut |node| {
+                if let Some(email) = node.get("email").and_then(|v| v.as_str()) {
+                    let email = scheme_value(email, "mailto:").unwrap_or(email);
+                    if let Some(email) = normalize_email(email) {
+                        push_unique(&mut emails, email);
+                    }
+                }
+                if let Some(phone) = node.get("telephone").and_then(|v| v.as_str()).and_then(normalize_phone) {
+                    push_unique(&mut phones, phone);
+                }
+                if address.is_none() && json_ld_type_is(node, &["PostalAddress"]) {
+                    address = postal_address(node);
+                }
+            });
+        }
+        if emails.is_empty() && phones.is_empty() && address.is_none() {
+            return None;
+        }
+        let publisher = self.extract_publisher(document);
+        let name = author
+            .or(publisher.as_deref())
+            .map(str::to_string)
+            .or_else(|| base.host_str().map(str::to_string))
+            .unwrap_or_default();
+        emails.truncate(MAX_VCARD_VALUES);
+        phones.truncate(MAX_VCARD_VALUES);
+        Some(render_vcard(&name, author.is_some(), publisher.as_deref(), &emails, &phones, address.as_ref()))
+    }
+
     /// Scrape a URL with enhanced content extraction
     pub async fn scrape_url(&self, url: &str) -> Result<ScrapeResponse> {
+        self.scrape_url_with_options(url, &ScrapeOptions::default()).await
+    }
+
+    /// Scrape a URL with per-call timeout, redirect, body size and User-Agent settings. A body over
+    /// the size limit fails with `BodyTooLarge` without being read into memory past the limit.
+    pub async fn scrape_url_with_options(&self, url: &str, options: &ScrapeOptions) -> Result<ScrapeResponse> {
         info!("Scraping URL with Rust-native scraper: {}", url);
+        let max_body_bytes = options.max_body_bytes.unwrap_or(self.max_body_bytes);
 
         // Validate URL
         let parsed_url = Url::parse(url)
-            .map_err(|e| anyhow!("Invalid URL '{}': {}", url, e))?;
+            .map_err(|e| ScrapeError::InvalidUrl(format!("Invalid URL '{}': {}", url, e)))?;
 
+        if parsed_url.scheme() == "file" && self.allow_file_scheme {
+            return self.scrape_file(url, parsed_url, max_body_bytes).await;
+        }
         if parsed_url.scheme() != "http" && parsed_url.scheme() != "https" {
-            return Err(anyhow!("URL must use HTTP or HTTPS protocol"));
+            return Err(ScrapeError::InvalidUrl("URL must use HTTP or HTTPS protocol".into()).into());
         }
+        self.check_address(&parsed_url).await?;
+        self.check_robots(url).await?;
+        self.wait_crawl_delay(&parsed_url).await?;
+        self.ensure_logged_in(&parsed_url).await?;
 
-        // Make HTTP request with random User-Agent
-        let user_agent = self.get_random_user_agent();
-        let response = self
-            .client
-            .get(url)
-            .header("User-Agent", user_agent)
-            .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8")
-            .header("Accept-Language", "en-US,en;q=0.5")
-            // Rely on reqwest automatic decompression; remove manual Accept-Encoding to avoid serving compressed body as text
-            .header("DNT", "1")
-            .header("Connection", "keep-alive")
-            .header("Upgrade-Insecure-Requests", "1")
-            .send()
-            .await
-            .map_err(|e| anyhow!("Failed to fetch URL: {}", e))?;
+        // A different redirect limit needs its own client; the session's cookie jar carries over
+        let client = match options.max_redirects {
+            Some(n) if n != crate::ssrf::DEFAULT_MAX_REDIRECTS => {
+                scraper_client(self.timeout, self.accept_invalid_certs, self.allow_private_addresses, n, self.session_jar())
+            }
+            _ => self.client.clone(),
+        };
+        // Make HTTP request with the requested or a random User-Agent
+        let user_agent = options.user_agent.as_deref().unwrap_or_else(|| self.get_random_user_agent());
+        let mut request = self.browser_request_with(&client, url, user_agent);
+        if let Some(timeout) = options.timeout {
+            request = request.timeout(timeout);
+        }
+        let response = request.send().await.map_err(ScrapeError::from_fetch)?;
 
         let status_code = response.status().as_u16();
         let content_type = response
@@ -81,37 +826,120 @@ impl RustScraper {
             .unwrap_or("text/html")
             .to_string();
 
+        // Gzipped data files (.gz, application/gzip) are decompressed and returned as text
+        if self.is_gzip_resource(&parsed_url, &content_type) {
+            let bytes = read_body_limited(response, max_body_bytes).await?;
+            let text = decompress_gzip_text(&bytes, max_body_bytes)?;
+            let result = self.plain_text_response(url, &parsed_url, text, status_code, content_type);
+            info!("Successfully scraped gzip resource: {} ({} words)", result.title, result.word_count);
+            return Ok(result);
+        }
+
         // Get response body
-        let html = response
-            .text()
-            .await
-            .map_err(|e| anyhow!("Failed to read response body: {}", e))?;
+        let body = read_body_limited(response, max_body_bytes).await?;
+        let html = decode_body(&body, &content_type);
+
+        // Plain-text documents carry no markup; use the body as-is instead of parsing it as HTML
+        if content_type.to_ascii_lowercase().starts_with("text/plain") {
+            let result = self.plain_text_response(url, &parsed_url, html, status_code, content_type);
+            info!("Successfully scraped plain text: {} ({} words)", result.title, result.word_count);
+            return Ok(result);
+        }
+
+        let result = self.extract_html(url, parsed_url, html, status_code, content_type).await?;
+        info!("Successfully scraped: {} ({} words)", result.title, result.word_count);
+        Ok(result)
+    }
+
+    /// Scrape a local file fixture; HTML is detected by extension, anything else is treated as plain text
+    async fn scrape_file(&self, url: &str, parsed_url: Url, max_body_bytes: usize) -> Result<ScrapeResponse> {
+        let path = parsed_url
+            .to_file_path()
+            .map_err(|_| ScrapeError::InvalidUrl(format!("Invalid file URL '{}'", url)))?;
+        let metadata = tokio::fs::metadata(&path).await.map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        if metadata.len() as usize > max_body_bytes {
+            return Err(ScrapeError::BodyTooLarge { limit: max_body_bytes }.into());
+        }
+        let bytes = tokio::fs::read(&path).await.map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        let text = String::from_utf8_lossy(&bytes).into_owned();
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase();
+        if matches!(extension.as_str(), "html" | "htm" | "xhtml") {
+            self.extract_html(url, parsed_url, text, 200, "text/html".to_string()).await
+        } else {
+            Ok(self.plain_text_response(url, &parsed_url, text, 200, "text/plain".to_string()))
+        }
+    }
+
+    /// Run site-specific extractors or the generic pipeline on already-fetched HTML.
+    /// Parsing and text extraction are CPU-bound, so they run off the async worker threads.
+    pub async fn extract_html(&self, url: &str, parsed_url: Url, html: String, status_code: u16, content_type: String) -> Result<ScrapeResponse> {
+        let this = self.clone();
+        let url_owned = url.to_string();
+        tokio::task::spawn_blocking(move || {
+            match this.extractors.extract(&parsed_url, &html, status_code, &content_type) {
+                Some(custom) => {
+                    info!("Custom extractor handled {}", url_owned);
+                    custom
+                }
+                None => this.extract_page(&url_owned, &parsed_url, html, status_code, content_type),
+            }
+        })
+        .await
+        .map_err(|e| anyhow!("Extraction task failed: {}", e))
+    }
 
+    /// Parse an HTML document and build the full scrape response (synchronous, CPU-bound)
+    pub fn extract_page(&self, url: &str, parsed_url: &Url, html: String, status_code: u16, content_type: String) -> ScrapeResponse {
         // Parse HTML
-    let document = Html::parse_document(&html);
+        let document = Html::parse_document(&html);
         
         // Extract basic metadata
-    let title = self.extract_title(&document);
-    let meta_description = self.extract_meta_description(&document);
-    let meta_keywords = self.extract_meta_keywords(&document);
+        let title = self.extract_title(&document);
+        let meta_description = self.extract_meta_description(&document);
+        let meta_keywords = self.extract_meta_keywords(&document);
         let language = self.detect_language(&document, &html);
-    let canonical_url = self.extract_canonical(&document, &parsed_url);
-    let site_name = self.extract_site_name(&document);
-    let (og_title, og_description, og_image) = self.extract_open_graph(&document, &parsed_url);
-    let author = self.extract_author(&document);
-    let published_at = self.extract_published_time(&document);
+        let canonical_url = self.extract_canonical(&document, parsed_url);
+        let site_name = self.extract_site_name(&document);
+        let readable_title = readable_title(&title, site_name.as_deref());
+        let theme_color = self.extract_theme_color(&document);
+        let viewport = self.extract_viewport(&document);
+        let mobile_friendly = Some(viewport.as_deref().is_some_and(is_responsive_viewport));
+        let (og_title, og_description, og_image) = self.extract_open_graph(&document, parsed_url);
+        let og_video = self.extract_og_video(&document, parsed_url);
+        let author = self.extract_author(&document);
+        let author_profile = self.extract_author_profile(&document, parsed_url);
+        let date_from_url = date_from_url_path(parsed_url);
+        let published_at = self.extract_published_time(&document).or_else(|| date_from_url.clone());
+        let modified_at = self.extract_modified_time(&document);
+        let age_days = age_days(
+            &[published_at.as_deref(), date_from_url.as_deref(), modified_at.as_deref()],
+            Utc::now().date_naive(),
+        );
 
         // Extract readable content using readability
-        let clean_content = self.extract_clean_content(&html, &parsed_url);
-    let word_count = self.count_words(&clean_content);
-    let reading_time_minutes = Some(((word_count as f64 / 200.0).ceil() as u32).max(1));
+        let (clean_content, extraction) = self.extract_clean_content(&html, parsed_url);
+        let word_count = self.count_words(&clean_content);
+        let reading_time_minutes = Some(((word_count as f64 / 200.0).ceil() as u32).max(1));
+        let char_count = count_chars(&clean_content);
 
         // Extract structured data
-        let headings = self.extract_headings(&document);
-        let links = self.extract_links(&document, &parsed_url);
-        let images = self.extract_images(&document, &parsed_url);
+        let (headings, headings_total) = self.extract_headings(&document);
+        let toc = self.extract_toc(&document);
+        let offers = self.extract_offers(&document);
+        let audio = self.extract_audio(&document, parsed_url);
+        let (paywalled, wall_type) = self.classify_page(&document, word_count);
+        let amp_url = self.extract_amp_url(&document, parsed_url);
+        let markdown_document = if self.as_document { Some(self.build_document(&document)) } else { None };
+        let markdown_content = if self.as_markdown { Some(self.to_markdown(&html, parsed_url)) } else { None };
+        let links = self.extract_links(&document, parsed_url);
+        let images = self.extract_images(&document, parsed_url);
+        let inline_state = if self.keep_inline_state { extract_inline_state(&document) } else { Vec::new() };
+        let blocks = if self.as_blocks { self.extract_blocks(&html, parsed_url) } else { Vec::new() };
+        let vcard = if self.build_vcard { self.extract_vcard(&document, author.as_deref(), parsed_url) } else { None };
+        let (structured_data, structured_data_truncated) =
+            if self.structured_data { self.extract_structured_data(&document) } else { (Vec::new(), false) };
 
-        let result = ScrapeResponse {
+        ScrapeResponse {
             url: url.to_string(),
             title,
             content: html,
@@ -119,25 +947,102 @@ impl RustScraper {
             meta_description,
             meta_keywords,
             headings,
+            headings_total,
             links,
             images,
             timestamp: Utc::now().to_rfc3339(),
             status_code,
             content_type,
             word_count,
+            char_count,
             language,
             canonical_url,
             site_name,
             author,
+            author_profile,
             published_at,
             og_title,
+            readable_title,
             og_description,
             og_image,
+            og_video,
             reading_time_minutes,
+            toc,
+            offers,
+            audio,
+            paywalled,
+            wall_type,
+            amp_url,
+            theme_color,
+            date_from_url,
+            age_days,
+            viewport,
+            mobile_friendly,
+            content_truncated: false,
+            from_cache_fallback: false,
+            rendered: false,
+            extraction_failed: false,
+            pages_merged: 0,
+            chapter_urls: Vec::new(),
+            language_segments: Vec::new(),
+            inline_state,
+            blocks,
+            vcard,
+            structured_data,
+            structured_data_truncated,
+            document: markdown_document,
+            markdown_content,
+            extraction: Some(extraction),
+        }
+    }
+
+    /// Build a response for a `text/plain` body without HTML parsing
+    fn plain_text_response(&self, url: &str, parsed_url: &Url, text: String, status_code: u16, content_type: String) -> ScrapeResponse {
+        let clean_content = text.trim().to_string();
+        let word_count = self.count_words(&clean_content);
+        let reading_time_minutes = Some(((word_count as f64 / 200.0).ceil() as u32).max(1));
+        let char_count = count_chars(&clean_content);
+        let language = match detect(&clean_content) {
+            Some(info) => lang_code(info.lang()),
+            None => "unknown".to_string(),
         };
 
-        info!("Successfully scraped: {} ({} words)", result.title, result.word_count);
-        Ok(result)
+        ScrapeResponse {
+            url: url.to_string(),
+            title: self.title_from_url(parsed_url),
+            content: text,
+            clean_content,
+            timestamp: Utc::now().to_rfc3339(),
+            status_code,
+            content_type,
+            word_count,
+            char_count,
+            language,
+            reading_time_minutes,
+            extraction: Some(ExtractionReport { strategy: "plain_text", candidate_words: Vec::new() }),
+            ..Default::default()
+        }
+    }
+
+    /// Whether the resource is a gzip file, judged by URL extension or declared content type
+    fn is_gzip_resource(&self, url: &Url, content_type: &str) -> bool {
+        let ct = content_type.to_ascii_lowercase();
+        url.path().to_ascii_lowercase().ends_with(".gz")
+            || ct.starts_with("application/gzip")
+            || ct.starts_with("application/x-gzip")
+    }
+
+    /// Derive a title from the URL's last non-empty path segment, falling back to the host
+    fn title_from_url(&self, url: &Url) -> String {
+        url.path_segments()
+            .and_then(|mut segments| segments.rfind(|s| !s.is_empty()))
+            .map(|s| {
+                percent_encoding::percent_decode_str(s)
+                    .decode_utf8_lossy()
+                    .to_string()
+            })
+            .or_else(|| url.host_str().map(|h| h.to_string()))
+            .unwrap_or_else(|| "No Title".to_string())
     }
 
     /// Extract page title with fallback to h1
@@ -214,6 +1119,23 @@ impl RustScraper {
         None
     }
 
+    fn extract_viewport(&self, document: &Html) -> Option<String> {
+        let selector = Selector::parse("meta[name=\"viewport\"]").ok()?;
+        let content = document.select(&selector).next()?.value().attr("content")?.trim();
+        (!content.is_empty()).then(|| content.to_string())
+    }
+
+    /// Extract `<meta name="theme-color">`, preferring the variant without a media query; invalid colors are ignored
+    fn extract_theme_color(&self, document: &Html) -> Option<String> {
+        let selector = Selector::parse("meta[name=\"theme-color\"]").ok()?;
+        let mut metas: Vec<_> = document.select(&selector).collect();
+        metas.sort_by_key(|el| el.value().attr("media").is_some());
+        metas
+            .into_iter()
+            .filter_map(|el| el.value().attr("content"))
+            .find_map(normalize_css_color)
+    }
+
     /// Extract OpenGraph basic fields
     fn extract_open_graph(&self, document: &Html, base: &Url) -> (Option<String>, Option<String>, Option<String>) {
         let og_title = if let Ok(sel) = Selector::parse("meta[property=\"og:title\"]") {
@@ -228,6 +1150,24 @@ impl RustScraper {
         (og_title, og_description, og_image)
     }
 
+    /// `og:video` (or `og:video:url`/`og:video:secure_url`) with its declared dimensions and type
+    fn extract_og_video(&self, document: &Html, base: &Url) -> Option<OgVideo> {
+        let meta = |property: &str| {
+            let sel = Selector::parse(&format!("meta[property=\"{}\"]", property)).ok()?;
+            document
+                .select(&sel)
+                .find_map(|e| e.value().attr("content").map(str::trim).filter(|s| !s.is_empty()).map(str::to_string))
+        };
+        let src = meta("og:video").or_else(|| meta("og:video:url")).or_else(|| meta("og:video:secure_url"))?;
+        let dimension = |property: &str| meta(property).and_then(|v| v.parse::<u32>().ok()).filter(|n| *n > 0);
+        Some(OgVideo {
+            url: base.join(&src).map(|u| u.to_string()).unwrap_or(src),
+            width: dimension("og:video:width"),
+            height: dimension("og:video:height"),
+            mime: meta("og:video:type"),
+        })
+    }
+
     /// Extract author
     fn extract_author(&self, document: &Html) -> Option<String> {
         // Meta author
@@ -245,19 +1185,399 @@ impl RustScraper {
         None
     }
 
-    /// Extract published time
-    fn extract_published_time(&self, document: &Html) -> Option<String> {
-        if let Ok(sel) = Selector::parse("meta[property=\"article:published_time\"]") {
-            if let Some(el) = document.select(&sel).next() {
-                if let Some(content) = el.value().attr("content") { return Some(content.trim().to_string()); }
+    /// Author profile from the JSON-LD `author` (or a lone `Person`), then `rel=author` links and
+    /// author-card markup for whatever is still missing. URLs are resolved against `base`.
+    fn extract_author_profile(&self, document: &Html, base: &Url) -> Option<AuthorProfile> {
+        let resolve = |raw: &str| {
+            let raw = raw.trim();
+            (!raw.is_empty() && !raw.starts_with("data:")).then(|| base.join(raw).ok().map(|u| u.to_string())).flatten()
+        };
+        let mut profile = AuthorProfile::default();
+
+        // 1) JSON-LD: the first `author` that is an object, else a standalone Person node
+        let mut person: Option<serde_json::Map<String, serde_json::Value>> = None;
+        let mut lone_person = None;
+        for value in self.extract_json_ld(document) {
+            visit_json_ld_nodes(&value, &mut |node| {
+                let author = match node.get("author") {
+                    Some(serde_json::Value::Array(items)) => items.first(),
+                    other => other,
+                };
+                if person.is_none() {
+                    person = author.and_then(|a| a.as_object()).cloned();
+                }
+                if lone_person.is_none() && json_ld_type_is(node, &["Person"]) {
+                    lone_person = Some(node.clone());
+                }
+            });
+        }
+        if let Some(node) = person.or(lone_person) {
+            let text = |key: &str| node.get(key).and_then(|v| v.as_str()).map(str::trim).filter(|s| !s.is_empty());
+            profile.name = text("name").map(str::to_string);
+            profile.url = text("url").and_then(resolve);
+            profile.image = match node.get("image") {
+                Some(serde_json::Value::String(src)) => resolve(src),
+                Some(serde_json::Value::Object(img)) => img.get("url").or_else(|| img.get("contentUrl")).and_then(|v| v.as_str()).and_then(resolve),
+                Some(serde_json::Value::Array(items)) => items.first().and_then(|v| v.as_str()).and_then(resolve),
+                _ => None,
+            };
+            match node.get("sameAs") {
+                Some(serde_json::Value::String(link)) => profile.social.extend(resolve(link)),
+                Some(serde_json::Value::Array(links)) => profile.social.extend(links.iter().filter_map(|v| v.as_str()).filter_map(resolve)),
+                _ => {}
             }
         }
-        None
-    }
 
-    /// Detect language from HTML attributes and content
-    fn detect_language(&self, document: &Html, html: &str) -> String {
-        // Try HTML lang attribute
+        // 2) rel=author link: profile URL, and a name from the anchor text
+        if let Ok(sel) = Selector::parse("a[rel~=\"author\"][href], link[rel~=\"author\"][href]") {
+            if let Some(el) = document.select(&sel).next() {
+                if profile.url.is_none() {
+                    profile.url = el.value().attr("href").and_then(resolve);
+                }
+                if profile.name.is_none() {
+                    let name = el.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" ");
+                    profile.name = (!name.is_empty()).then_some(name);
+                }
+            }
+        }
+
+        // 3) Author card: avatar and social links
+        if let (Ok(card_sel), Ok(img_sel), Ok(link_sel)) = (
+            Selector::parse(".author-card, .author-box, .author-bio, .author-info, [itemprop=\"author\"], [rel~=\"author\"]"),
+            Selector::parse("img[src]"),
+            Selector::parse("a[href]"),
+        ) {
+            if let Some(card) = document.select(&card_sel).find(|c| c.value().name() != "link") {
+                if profile.image.is_none() {
+                    profile.image = card.select(&img_sel).next().and_then(|img| img.value().attr("src")).and_then(resolve);
+                }
+                for href in card.select(&link_sel).filter_map(|a| a.value().attr("href")).filter_map(resolve) {
+                    if is_social_profile(&href) {
+                        profile.social.push(href);
+                    }
+                }
+            }
+        }
+
+        let mut seen = HashSet::new();
+        profile.social.retain(|link| seen.insert(link.clone()));
+        let empty = profile.name.is_none() && profile.url.is_none() && profile.image.is_none() && profile.social.is_empty();
+        (!empty).then_some(profile)
+    }
+
+    /// Extract published time
+    fn extract_published_time(&self, document: &Html) -> Option<String> {
+        if let Ok(sel) = Selector::parse("meta[property=\"article:published_time\"]") {
+            if let Some(el) = document.select(&sel).next() {
+                if let Some(content) = el.value().attr("content") { return Some(content.trim().to_string()); }
+            }
+        }
+        None
+    }
+
+    /// Last-modified time from `article:modified_time` or `og:updated_time`
+    fn extract_modified_time(&self, document: &Html) -> Option<String> {
+        ["article:modified_time", "og:updated_time"].iter().find_map(|property| {
+            let sel = Selector::parse(&format!("meta[property=\"{}\"]", property)).ok()?;
+            let content = document.select(&sel).next()?.value().attr("content")?.trim();
+            (!content.is_empty()).then(|| content.to_string())
+        })
+    }
+
+    /// Parse every JSON-LD script block; malformed blocks are skipped
+    fn extract_json_ld(&self, document: &Html) -> Vec<serde_json::Value> {
+        let Ok(sel) = Selector::parse("script[type=\"application/ld+json\"]") else { return Vec::new() };
+        document
+            .select(&sel)
+            .filter_map(|el| serde_json::from_str::<serde_json::Value>(el.text().collect::<String>().trim()).ok())
+            .collect()
+    }
+
+    /// JSON-LD objects in page order, top-level arrays and `@graph` split into nodes, kept while within
+    /// `max_structured_data` objects and `max_structured_data_bytes` of JSON. An object that doesn't fit
+    /// is skipped (a smaller later one may still fit); the flag reports whether anything was left out.
+    fn extract_structured_data(&self, document: &Html) -> (Vec<serde_json::Value>, bool) {
+        let nodes = self.extract_json_ld(document).into_iter().flat_map(|value| match value {
+            serde_json::Value::Array(items) => items,
+            serde_json::Value::Object(mut map) => match map.remove("@graph") {
+                Some(serde_json::Value::Array(graph)) => graph,
+                Some(graph) => {
+                    map.insert("@graph".to_string(), graph);
+                    vec![serde_json::Value::Object(map)]
+                }
+                None => vec![serde_json::Value::Object(map)],
+            },
+            other => vec![other],
+        });
+        let (mut kept, mut bytes, mut truncated) = (Vec::new(), 0usize, false);
+        for node in nodes {
+            let size = serde_json::to_string(&node).map_or(usize::MAX, |json| json.len());
+            if kept.len() >= self.max_structured_data || bytes.saturating_add(size) > self.max_structured_data_bytes {
+                truncated = true;
+                continue;
+            }
+            bytes += size;
+            kept.push(node);
+        }
+        (kept, truncated)
+    }
+
+    /// Extract product offers (price, currency, availability) from JSON-LD, microdata and `.price` markup
+    fn extract_offers(&self, document: &Html) -> Vec<Offer> {
+        let mut offers = Vec::new();
+
+        // 1) JSON-LD Offer / AggregateOffer nodes anywhere in the graph
+        for value in self.extract_json_ld(document) {
+            visit_json_ld_nodes(&value, &mut |node| {
+                if !json_ld_type_is(node, &["Offer", "AggregateOffer"]) {
+                    return;
+                }
+                let price = node.get("price").or_else(|| node.get("lowPrice")).and_then(json_price);
+                let currency = node.get("priceCurrency").and_then(|v| v.as_str()).map(normalize_currency);
+                if let (Some(price), Some(currency)) = (price, currency) {
+                    let availability = node.get("availability").and_then(|v| v.as_str()).map(short_availability);
+                    offers.push(Offer { price, currency, availability });
+                }
+            });
+        }
+
+        // 2) Microdata: [itemprop=price] with currency/availability from the enclosing itemscope
+        if offers.is_empty() {
+            if let (Ok(price_sel), Ok(currency_sel), Ok(avail_sel)) = (
+                Selector::parse("[itemprop=\"price\"]"),
+                Selector::parse("[itemprop=\"priceCurrency\"]"),
+                Selector::parse("[itemprop=\"availability\"]"),
+            ) {
+                for el in document.select(&price_sel) {
+                    let raw = el.value().attr("content").map(|s| s.to_string()).unwrap_or_else(|| el.text().collect());
+                    let Some(price) = parse_price_number(&raw) else { continue };
+                    let scope = el
+                        .ancestors()
+                        .filter_map(scraper::ElementRef::wrap)
+                        .find(|a| a.value().attr("itemscope").is_some());
+                    let prop = |sel: &Selector| {
+                        scope.and_then(|s| s.select(sel).next()).and_then(|e| {
+                            e.value()
+                                .attr("content")
+                                .or_else(|| e.value().attr("href"))
+                                .map(|v| v.to_string())
+                                .or_else(|| Some(e.text().collect::<String>()))
+                        })
+                    };
+                    let currency = prop(&currency_sel)
+                        .map(|c| normalize_currency(c.trim()))
+                        .or_else(|| currency_from_text(&raw));
+                    if let Some(currency) = currency {
+                        let availability = prop(&avail_sel).map(|a| short_availability(a.trim()));
+                        offers.push(Offer { price, currency, availability });
+                    }
+                }
+            }
+        }
+
+        // 3) Heuristic: visible ".price" elements such as "$19.99" or "12,50 €"
+        if offers.is_empty() {
+            if let Ok(sel) = Selector::parse(".price, .product-price, [class*=\"price\"]") {
+                for el in document.select(&sel) {
+                    let text = el.text().collect::<String>();
+                    let text = text.trim();
+                    if text.is_empty() || text.len() > 40 {
+                        continue;
+                    }
+                    if let (Some(price), Some(currency)) = (parse_price_number(text), currency_from_text(text)) {
+                        offers.push(Offer { price, currency, availability: None });
+                    }
+                }
+            }
+        }
+
+        let mut unique = Vec::new();
+        for offer in offers {
+            if !unique.contains(&offer) {
+                unique.push(offer);
+            }
+        }
+        unique
+    }
+
+    /// Audio enclosures from JSON-LD `AudioObject`/`PodcastEpisode`, `og:audio` and `<audio>` elements.
+    /// Entries are deduplicated by URL, later sources filling in a missing duration or MIME type.
+    fn extract_audio(&self, document: &Html, base: &Url) -> Vec<AudioMeta> {
+        let mut found: Vec<AudioMeta> = Vec::new();
+        let mut add = |src: &str, duration: Option<String>, mime: Option<String>| {
+            let src = src.trim();
+            if src.is_empty() || src.starts_with("data:") {
+                return;
+            }
+            let Ok(url) = base.join(src) else { return };
+            let url = url.to_string();
+            let clean = |v: Option<String>| v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+            let (duration, mime) = (clean(duration), clean(mime));
+            match found.iter_mut().find(|a| a.url == url) {
+                Some(existing) => {
+                    existing.duration = existing.duration.take().or(duration);
+                    existing.mime = existing.mime.take().or(mime);
+                }
+                None => found.push(AudioMeta { url, duration, mime }),
+            }
+        };
+        let text = |node: &serde_json::Map<String, serde_json::Value>, key: &str| node.get(key).and_then(|v| v.as_str()).map(str::to_string);
+
+        // 1) JSON-LD: audio objects, and episodes whose media object carries no duration of its own
+        for value in self.extract_json_ld(document) {
+            visit_json_ld_nodes(&value, &mut |node| {
+                if json_ld_type_is(node, &["AudioObject"]) {
+                    if let Some(src) = text(node, "contentUrl").or_else(|| text(node, "url")) {
+                        add(&src, text(node, "duration"), text(node, "encodingFormat"));
+                    }
+                } else if json_ld_type_is(node, &["PodcastEpisode"]) {
+                    let media = node.get("associatedMedia").or_else(|| node.get("audio")).and_then(|m| m.as_object());
+                    let src = media.and_then(|m| text(m, "contentUrl").or_else(|| text(m, "url")));
+                    if let Some(src) = src {
+                        let duration = media.and_then(|m| text(m, "duration")).or_else(|| text(node, "duration")).or_else(|| text(node, "timeRequired"));
+                        add(&src, duration, media.and_then(|m| text(m, "encodingFormat")));
+                    }
+                }
+            });
+        }
+
+        // 2) Open Graph
+        let meta = |property: &str| {
+            Selector::parse(&format!("meta[property=\"{}\"]", property))
+                .ok()
+                .and_then(|sel| document.select(&sel).next().and_then(|e| e.value().attr("content")).map(str::to_string))
+        };
+        if let Some(src) = meta("og:audio:secure_url").or_else(|| meta("og:audio")).or_else(|| meta("og:audio:url")) {
+            add(&src, None, meta("og:audio:type"));
+        }
+
+        // 3) <audio src> and <audio><source src type>
+        if let (Ok(audio_sel), Ok(source_sel)) = (Selector::parse("audio"), Selector::parse("source[src]")) {
+            for audio in document.select(&audio_sel) {
+                if let Some(src) = audio.value().attr("src") {
+                    add(src, None, audio.value().attr("type").map(str::to_string));
+                }
+                for source in audio.select(&source_sel) {
+                    if let Some(src) = source.value().attr("src") {
+                        add(src, None, source.value().attr("type").map(str::to_string));
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    /// Page classification pass: the paywall flag, and which wall (if any) the page mostly is.
+    /// Paywalls are reported at any length; login, age and consent walls only on pages with little
+    /// real content, since full articles routinely carry a cookie banner or a header login form.
+    fn classify_page(&self, document: &Html, word_count: usize) -> (bool, Option<WallType>) {
+        let paywalled = self.detect_paywall(document, word_count);
+        if paywalled {
+            return (true, Some(WallType::Paywall));
+        }
+        if word_count >= WALL_MAX_WORDS {
+            return (false, None);
+        }
+        let body_text = document.root_element().text().collect::<String>().to_lowercase();
+        let body_text = body_text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let wall = if AGE_GATE_PHRASES.iter().any(|p| body_text.contains(p)) {
+            Some(WallType::Age)
+        } else if self.has_login_form(document) || LOGIN_WALL_PHRASES.iter().any(|p| body_text.contains(p)) {
+            Some(WallType::Login)
+        } else if self.has_consent_markup(document) || CONSENT_PHRASES.iter().any(|p| body_text.contains(p)) {
+            Some(WallType::Consent)
+        } else {
+            None
+        };
+        (false, wall)
+    }
+
+    /// A form asking for a password
+    fn has_login_form(&self, document: &Html) -> bool {
+        Selector::parse("form input[type=\"password\"]").is_ok_and(|sel| document.select(&sel).next().is_some())
+    }
+
+    /// Markup of the common consent-management platforms, or a dialog with an accept button
+    fn has_consent_markup(&self, document: &Html) -> bool {
+        const MARKERS: &[&str] = &["cookie-consent", "cookieconsent", "cookie-banner", "consent-banner", "onetrust", "didomi", "cookiebot", "qc-cmp", "sp_message", "gdpr-consent"];
+        let marked = Selector::parse("[class], [id]").is_ok_and(|sel| {
+            document.select(&sel).any(|el| {
+                let v = el.value();
+                v.id().into_iter().chain(v.classes()).any(|ident| {
+                    let ident = ident.to_ascii_lowercase();
+                    MARKERS.iter().any(|m| ident.contains(m))
+                })
+            })
+        });
+        marked
+            || Selector::parse("button, a[role=button], input[type=submit]").is_ok_and(|sel| {
+                document.select(&sel).any(|el| {
+                    let label = el.text().collect::<String>() + el.value().attr("value").unwrap_or("");
+                    let label = label.trim().to_lowercase();
+                    ["accept all", "accept cookies", "allow all cookies", "agree and continue", "i agree"].iter().any(|b| label.starts_with(b))
+                })
+            })
+    }
+
+    /// Heuristic paywall detection: JSON-LD `isAccessibleForFree: false`, paywall markup markers,
+    /// or a "subscribe to continue" teaser on a short extraction
+    fn detect_paywall(&self, document: &Html, word_count: usize) -> bool {
+        let declared_paywalled = self.extract_json_ld(document).iter().any(|value| {
+            let mut found = false;
+            visit_json_ld_nodes(value, &mut |node| {
+                match node.get("isAccessibleForFree") {
+                    Some(serde_json::Value::Bool(false)) => found = true,
+                    Some(serde_json::Value::String(s)) if s.eq_ignore_ascii_case("false") => found = true,
+                    _ => {}
+                }
+            });
+            found
+        });
+        if declared_paywalled {
+            return true;
+        }
+
+        const MARKERS: &[&str] = &[
+            "paywall", "regwall", "metered-content", "subscriber-only", "subscribers-only",
+            "premium-content", "article-locked", "piano-offer", "tp-modal",
+        ];
+        if let Ok(sel) = Selector::parse("[class], [id]") {
+            let has_marker = document.select(&sel).any(|el| {
+                let v = el.value();
+                v.id().into_iter().chain(v.classes()).any(|ident| {
+                    let ident = ident.to_ascii_lowercase();
+                    MARKERS.iter().any(|m| ident.contains(m))
+                })
+            });
+            if has_marker {
+                return true;
+            }
+        }
+
+        // Teaser pages end with a subscription prompt and carry little real text
+        const TEASER_PHRASES: &[&str] = &[
+            "subscribe to continue reading", "subscribe to read the full", "this article is for subscribers",
+            "this content is for subscribers", "already a subscriber", "to continue reading, please",
+        ];
+        if word_count < 400 {
+            let body_text = document.root_element().text().collect::<String>().to_lowercase();
+            if TEASER_PHRASES.iter().any(|p| body_text.contains(p)) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Extract the AMP version link (`<link rel="amphtml">`), often served without a paywall
+    fn extract_amp_url(&self, document: &Html, base: &Url) -> Option<String> {
+        let sel = Selector::parse("link[rel=\"amphtml\"]").ok()?;
+        let href = document.select(&sel).next()?.value().attr("href")?;
+        base.join(href.trim()).ok().map(|u| u.to_string())
+    }
+
+    /// Detect language from HTML attributes and content
+    fn detect_language(&self, document: &Html, html: &str) -> String {
+        // Try HTML lang attribute
         if let Ok(selector) = Selector::parse("html") {
             if let Some(html_element) = document.select(&selector).next() {
                 if let Some(lang) = html_element.value().attr("lang") {
@@ -277,111 +1597,176 @@ impl RustScraper {
 
         // Use whatlang for content-based detection
         if let Some(info) = detect(html) {
-            match info.lang() {
-                Lang::Eng => "en".to_string(),
-                Lang::Spa => "es".to_string(),
-                Lang::Fra => "fr".to_string(),
-                Lang::Deu => "de".to_string(),
-                Lang::Ita => "it".to_string(),
-                Lang::Por => "pt".to_string(),
-                Lang::Rus => "ru".to_string(),
-                Lang::Jpn => "ja".to_string(),
-                Lang::Kor => "ko".to_string(),
-                Lang::Cmn => "zh".to_string(),
-                _ => format!("{:?}", info.lang()).to_lowercase(),
-            }
+            lang_code(info.lang())
         } else {
             "unknown".to_string()
         }
     }
 
     /// Extract clean, readable content using readability, preceded by HTML preprocessing
-    fn extract_clean_content(&self, html: &str, base_url: &Url) -> String {
+    /// Returns the text together with which pass won and each candidate's word count
+    fn extract_clean_content(&self, html: &str, base_url: &Url) -> (String, ExtractionReport) {
         // 1) Pre-clean HTML to strip obvious boilerplate and ads before readability
         let pre = self.preprocess_html(html);
 
-        // 1a) mdBook-style extractor (e.g., Rust Book) — try focused body first
-        if let Some(md_text) = self.extract_mdbook_like(&pre) {
-            if md_text.len() > 120 { // substantial content
-                return self.post_clean_text(&md_text);
-            }
-        }
+        // mdBook output only wins when substantial and its link-penalized score reaches readability's word
+        // count plus `mdbook_margin_words`, so a nav-heavy `#content` loses to readability's article text
+        let mdbook_wins = |md: Option<(String, usize)>, readability_text: &str| {
+            let rt_words = self.count_words(readability_text);
+            md.filter(|(text, score)| text.len() > 120 && *score >= rt_words.saturating_add(self.mdbook_margin_words))
+                .map(|(text, _)| {
+                    let candidate_words = vec![("mdbook", self.count_words(&text)), ("readability", rt_words)];
+                    (self.post_clean_text(&text), ExtractionReport { strategy: "mdbook", candidate_words })
+                })
+        };
+        let mdbook_words = |md: &Option<(String, usize)>| md.as_ref().map(|(text, _)| self.count_words(text));
 
-        // 2) Readability pass
-        let readability_text = match extractor::extract(&mut pre.as_bytes(), base_url) {
-            Ok(product) => {
-                let text = html2text::from_read(product.content.as_bytes(), 80);
-                self.post_clean_text(&text)
+        let (md_words, readability_text, heuristic_text) = if self.parallel_extraction_bytes > 0 && pre.len() >= self.parallel_extraction_bytes {
+            // Large pages: run the independent passes on separate threads; the choice below is the same
+            let (md_text, readability_text, heuristic_text) = std::thread::scope(|s| {
+                let md = s.spawn(|| self.extract_mdbook_like(&pre));
+                let readability = s.spawn(|| self.readability_pass(&pre, base_url));
+                let heuristic = self.heuristic_main_extraction(&pre);
+                fn joined<T>(result: std::thread::Result<T>) -> T {
+                    result.unwrap_or_else(|e| std::panic::resume_unwind(e))
+                }
+                (joined(md.join()), joined(readability.join()), heuristic)
+            });
+            let md_words = mdbook_words(&md_text);
+            if let Some(won) = mdbook_wins(md_text, &readability_text) {
+                return won;
             }
-            Err(e) => {
-                warn!("Readability extraction failed: {}, will try heuristics", e);
-                String::new()
+            (md_words, readability_text, heuristic_text)
+        } else {
+            // 1a) mdBook-style extractor (e.g., Rust Book) against 2) readability
+            let md_text = self.extract_mdbook_like(&pre);
+            let md_words = mdbook_words(&md_text);
+            let readability_text = self.readability_pass(&pre, base_url);
+            if let Some(won) = mdbook_wins(md_text, &readability_text) {
+                return won;
             }
+            // 3) Heuristic main-content extraction (article/main/role=main/etc.)
+            (md_words, readability_text, self.heuristic_main_extraction(&pre))
         };
 
-        // 3) Heuristic main-content extraction (article/main/role=main/etc.)
-        let heuristic_text = self.heuristic_main_extraction(&pre);
-
-        // 4) Choose the better result by word count; be aggressive if one is near-empty
+        // 4) Choose the better result by word count; be aggressive if one is near-empty;
+        //    readability wins ties
         let rt_words = self.count_words(&readability_text);
         let ht_words = self.count_words(&heuristic_text);
+        let mut candidate_words: Vec<(&'static str, usize)> = md_words.map(|w| ("mdbook", w)).into_iter().collect();
+        candidate_words.extend([("readability", rt_words), ("heuristic", ht_words)]);
 
-        let chosen = if rt_words == 0 && ht_words > 0 {
-            heuristic_text
+        let (strategy, chosen) = if rt_words == 0 && ht_words > 0 {
+            ("heuristic", heuristic_text)
         } else if ht_words == 0 && rt_words > 0 {
-            readability_text
+            ("readability", readability_text)
         } else if ht_words > rt_words.saturating_add(20) {
-            heuristic_text
+            ("heuristic", heuristic_text)
         } else if rt_words > 0 {
-            readability_text
+            ("readability", readability_text)
         } else {
             // 5) Fallback to simple whole-document text extraction
-            self.fallback_text_extraction(&pre)
+            ("fallback", self.fallback_text_extraction(&pre))
         };
 
         // Final sanitize; ensure non-trivial output by adding a last-resort html2text over full doc
         let final_text = self.post_clean_text(&chosen);
         if final_text.len() < 80 {
             let whole = html2text::from_read(pre.as_bytes(), 80);
-            return self.post_clean_text(&whole);
+            return (self.post_clean_text(&whole), ExtractionReport { strategy: "html2text", candidate_words });
         }
-        final_text
+        (final_text, ExtractionReport { strategy, candidate_words })
     }
 
-    /// Extract content from mdBook-like structures (#content, main, article) using select crate
-    fn extract_mdbook_like(&self, html: &str) -> Option<String> {
-        let doc = SelectDoc::from(html);
-        // Try #content first - this is mdBook's main content container
-        if let Some(node) = doc.find(SelName("div").and(SelAttr("id", "content"))).next() {
-            let inner = node.inner_html();
-            let text = html2text::from_read(inner.as_bytes(), 80);
-            let cleaned = self.clean_text(&text);
-            let word_count = self.count_words(&cleaned);
-            info!("mdBook extractor (#content): {} words", word_count);
-            if word_count > 50 { 
-                return Some(cleaned); 
+    /// Readability's article HTML walked into typed blocks; empty when readability fails
+    fn extract_blocks(&self, html: &str, base_url: &Url) -> Vec<Block> {
+        let pre = self.preprocess_html(html);
+        match readability_extract(&pre, base_url) {
+            Ok(product) => {
+                let fragment = Html::parse_fragment(&product.content);
+                crate::markdown::html_to_blocks(fragment.root_element())
+            }
+            Err(e) => {
+                warn!("Readability extraction failed: {}, no blocks", e);
+                Vec::new()
             }
         }
-        // Try main
-        if let Some(node) = doc.find(SelName("main")).next() {
-            let inner = node.inner_html();
-            let text = html2text::from_read(inner.as_bytes(), 80);
-            let cleaned = self.clean_text(&text);
-            let word_count = self.count_words(&cleaned);
-            info!("mdBook extractor (main): {} words", word_count);
-            if word_count > 50 { 
-                return Some(cleaned); 
+    }
+
+    /// Readability pass over pre-cleaned HTML; empty when readability fails
+    fn readability_pass(&self, pre: &str, base_url: &Url) -> String {
+        match readability_extract(pre, base_url) {
+            Ok(product) => {
+                let density = link_density(Html::parse_fragment(&product.content).root_element());
+                if density > self.max_link_density {
+                    debug!("Readability picked a block that is {:.0}% links, trying the next strategy", density * 100.0);
+                    return String::new();
+                }
+                let text = html2text::from_read(product.content.as_bytes(), 80);
+                self.post_clean_text(&text)
+            }
+            Err(e) => {
+                warn!("Readability extraction failed: {}, will try heuristics", e);
+                String::new()
+            }
+        }
+    }
+
+    /// Render the main content container (or the whole body) as markdown, skipping boilerplate blocks
+    fn build_document(&self, document: &Html) -> String {
+        let Some(root) = main_container(document) else { return String::new() };
+        crate::markdown::html_to_markdown(root, &|el| self.is_boilerplate_block(el))
+    }
+
+    /// Readability's main-content fragment as markdown, with links and images resolved against
+    /// `base_url` like `extract_links` does. The main container stands in when readability fails.
+    pub fn to_markdown(&self, html: &str, base_url: &Url) -> String {
+        let skip = |el: &scraper::ElementRef| self.is_boilerplate_block(el);
+        let pre = self.preprocess_html(html);
+        if let Ok(product) = readability_extract(&pre, base_url) {
+            let fragment = Html::parse_fragment(&product.content);
+            let markdown = crate::markdown::html_to_markdown_with_links(fragment.root_element(), &skip, base_url);
+            if !markdown.is_empty() {
+                return markdown;
             }
         }
-        // Try article
-        if let Some(node) = doc.find(SelName("article")).next() {
+        let document = Html::parse_document(html);
+        main_container(&document)
+            .map(|root| crate::markdown::html_to_markdown_with_links(root, &skip, base_url))
+            .unwrap_or_default()
+    }
+
+    /// Whether a block's id or class marks it as boilerplate; headings are always kept, even when
+    /// their class looks like a "header" wrapper
+    fn is_boilerplate_block(&self, el: &scraper::ElementRef) -> bool {
+        let v = el.value();
+        if matches!(v.name(), "h1" | "h2" | "h3" | "h4" | "h5" | "h6") {
+            return false;
+        }
+        v.id().into_iter().chain(v.classes()).any(|ident| self.is_noise_identifier(ident))
+    }
+
+    /// Extract content from mdBook-like structures (#content, main, article) using select crate
+    /// Returns the text and its score (words outside links minus words in links) from the first of
+    /// `#content`, `main` and `article` holding more than `mdbook_min_words` words.
+    fn extract_mdbook_like(&self, html: &str) -> Option<(String, usize)> {
+        let doc = SelectDoc::from(html);
+        // #content first - this is mdBook's main content container
+        let candidates = [
+            ("#content", doc.find(SelName("div").and(SelAttr("id", "content"))).next()),
+            ("main", doc.find(SelName("main")).next()),
+            ("article", doc.find(SelName("article")).next()),
+        ];
+        for (label, node) in candidates {
+            let Some(node) = node else { continue };
             let inner = node.inner_html();
             let text = html2text::from_read(inner.as_bytes(), 80);
             let cleaned = self.clean_text(&text);
             let word_count = self.count_words(&cleaned);
-            info!("mdBook extractor (article): {} words", word_count);
-            if word_count > 50 { 
-                return Some(cleaned); 
+            info!("mdBook extractor ({}): {} words", label, word_count);
+            if word_count > self.mdbook_min_words {
+                let link_words: usize = node.find(SelName("a")).map(|a| self.count_words(&a.text())).sum();
+                return Some((cleaned, word_count.saturating_sub(2 * link_words)));
             }
         }
         info!("mdBook extractor found no suitable content");
@@ -445,6 +1830,26 @@ impl RustScraper {
 
     /// Clean extracted text (whitespace normalization)
     fn clean_text(&self, text: &str) -> String {
+        if self.preserve_whitespace {
+            // Only normalize line endings, trailing spaces and runs of blank lines
+            let lines: Vec<&str> = text.lines().map(|l| l.trim_end()).collect();
+            let re_blank_runs = Regex::new(r"\n{3,}").unwrap();
+            return re_blank_runs
+                .replace_all(&lines.join("\n"), "\n\n")
+                .trim_matches('\n')
+                .to_string();
+        }
+
+        if self.keep_paragraphs {
+            let re_paragraph_break = Regex::new(r"\n\s*\n").unwrap();
+            return re_paragraph_break
+                .split(text)
+                .map(|p| p.split_whitespace().collect::<Vec<_>>().join(" "))
+                .filter(|p| !p.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n\n");
+        }
+
         // Remove excessive whitespace
         let re_whitespace = Regex::new(r"\s+").unwrap();
         let re_newlines = Regex::new(r"\n\s*\n").unwrap();
@@ -457,30 +1862,37 @@ impl RustScraper {
 
     /// Final post-processing to strip boilerplate lines, trackers, CTA, share/cookie prompts
     fn post_clean_text(&self, text: &str) -> String {
-        // Normalize first
-    let out = self.clean_text(text);
-
-        // Drop lines matching common garbage patterns
-        let garbage = [
-            r"(?i)subscribe", r"(?i)sign up", r"(?i)cookie", r"(?i)accept all",
-            r"(?i)advert", r"(?i)sponsor", r"(?i)newsletter", r"(?i)\bshare\b", r"(?i)related articles",
-            r"(?i)^comments?$", r"(?i)read more", r"(?i)continue reading", r"(?i)terms of service", r"(?i)privacy policy",
-        ];
-        let re_garbage = Regex::new(&format!("{}", garbage.join("|"))).unwrap();
+        // Drop lines that consist entirely of boilerplate (buttons, banners); prose mentioning those words stays.
+        // This runs on the original lines, before normalization may join them.
+        let filtered = text
+            .lines()
+            .filter(|line| !self.garbage_line.is_match(&normalize_garbage_candidate(line)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let out = self.clean_text(&filtered);
 
         let mut kept = Vec::new();
         for line in out.split('\n') {
             let line_trim = line.trim();
-            if line_trim.is_empty() { continue; }
-            // Remove very short noisy lines and those matching garbage
+            if self.preserve_whitespace {
+                // Keep indentation, blank separators and short lines such as closing braces
+                kept.push(line.trim_end().to_string());
+                continue;
+            }
+            if line_trim.is_empty() {
+                // Paragraph separator; adjacent ones collapse in the dedup below
+                if self.keep_paragraphs && !kept.is_empty() { kept.push(String::new()); }
+                continue;
+            }
+            // Remove very short noisy lines
             if line_trim.len() < 3 { continue; }
-            if re_garbage.is_match(line_trim) { continue; }
             kept.push(line_trim.to_string());
         }
 
         // Deduplicate adjacent lines
         kept.dedup();
         let result = kept.join("\n");
+        let result = if self.preserve_whitespace || self.keep_paragraphs { result.trim_matches('\n').to_string() } else { result };
         // Collapse too many newlines
         let re_multi_nl = Regex::new(r"\n{3,}").unwrap();
         re_multi_nl.replace_all(&result, "\n\n").to_string()
@@ -549,6 +1961,10 @@ impl RustScraper {
         for sel_str in selectors.iter() {
             if let Ok(sel) = Selector::parse(sel_str) {
                 for el in document.select(&sel) {
+                    // Navigation blocks (`<section class="content">` of menus) are mostly link text
+                    if link_density(el) > self.max_link_density {
+                        continue;
+                    }
                     let mut parts = Vec::new();
                     self.extract_text_recursive(&el, &mut parts);
                     let text = self.post_clean_text(&parts.join(" "));
@@ -566,13 +1982,15 @@ impl RustScraper {
 
     /// Count words in text
     fn count_words(&self, text: &str) -> usize {
-        text.split_whitespace().count()
+        count_words(text)
     }
 
     /// Extract headings (h1-h6)
-    fn extract_headings(&self, document: &Html) -> Vec<Heading> {
+    /// Headings grouped by level (h1 first), capped to `max_headings`; also returns the uncapped count
+    fn extract_headings(&self, document: &Html) -> (Vec<Heading>, usize) {
         let mut headings = Vec::new();
-        
+        let mut total = 0;
+
         for level in 1..=6 {
             let sel: &str = match level {
                 1 => "h1",
@@ -585,17 +2003,51 @@ impl RustScraper {
             if let Ok(selector) = Selector::parse(sel) {
                 for element in document.select(&selector) {
                     let text = element.text().collect::<String>().trim().to_string();
-                    if !text.is_empty() {
+                    if text.is_empty() {
+                        continue;
+                    }
+                    total += 1;
+                    if headings.len() < self.max_headings {
                         headings.push(Heading {
                             level: sel.to_string(),
-                            text,
+                            text: truncate_chars(&text, self.max_heading_chars),
                         });
                     }
                 }
             }
         }
-        
-        headings
+
+        (headings, total)
+    }
+
+    /// Build a table of contents from h1-h6 in document order, using heading ids as anchors
+    fn extract_toc(&self, document: &Html) -> Vec<TocEntry> {
+        let mut toc = Vec::new();
+        let Ok(selector) = Selector::parse("h1, h2, h3, h4, h5, h6") else { return toc };
+        let anchor_selector = Selector::parse("a[id], a[name]").ok();
+
+        for element in document.select(&selector) {
+            let text = element.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" ");
+            if text.is_empty() {
+                continue;
+            }
+            let level = element.value().name()[1..].parse::<u8>().unwrap_or(1);
+            // Prefer the heading's own id, then an inner named anchor (<h2><a id="x">..</a></h2>)
+            let anchor = element
+                .value()
+                .id()
+                .map(|id| id.to_string())
+                .or_else(|| {
+                    anchor_selector.as_ref().and_then(|sel| {
+                        element.select(sel).next().and_then(|a| {
+                            a.value().id().or_else(|| a.value().attr("name")).map(|v| v.to_string())
+                        })
+                    })
+                })
+                .filter(|a| !a.trim().is_empty());
+            toc.push(TocEntry { level, text, anchor });
+        }
+        toc
     }
 
     /// Extract links with absolute URLs
@@ -606,7 +2058,7 @@ impl RustScraper {
         if let Ok(selector) = Selector::parse("a[href]") {
             for element in document.select(&selector) {
                 if let Some(href) = element.value().attr("href") {
-                    let text = element.text().collect::<String>().trim().to_string();
+                    let text = link_label(&element);
                     
                     // Convert relative URLs to absolute
                     let absolute_url = match base_url.join(href) {
@@ -614,12 +2066,13 @@ impl RustScraper {
                         Err(_) => href.to_string(),
                     };
                     
-                    // Avoid duplicates
-                    if !seen_urls.contains(&absolute_url) {
-                        seen_urls.insert(absolute_url.clone());
+                    // Avoid duplicates, treating canonically equal URLs (tracking params, fragments) as one
+                    let key = crate::url_utils::normalize_url_with_base(base_url, href).unwrap_or_else(|_| absolute_url.clone());
+                    if seen_urls.insert(key) {
                         links.push(Link {
                             url: absolute_url,
                             text,
+                            raw_href: self.keep_raw_urls.then(|| href.to_string()),
                         });
                     }
                 }
@@ -654,6 +2107,7 @@ impl RustScraper {
                             src: absolute_src,
                             alt,
                             title,
+                            raw_src: self.keep_raw_urls.then(|| src.to_string()),
                         });
                     }
                 }
@@ -670,40 +2124,1528 @@ impl Default for RustScraper {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    
-    #[tokio::test]
-    async fn test_rust_scraper() {
-        let scraper = RustScraper::new();
-        
-        // Test with a simple HTML page
-        match scraper.scrape_url("https://httpbin.org/html").await {
-            Ok(content) => {
-                assert!(!content.title.is_empty(), "Title should not be empty");
-                assert!(!content.clean_content.is_empty(), "Content should not be empty");
-                assert_eq!(content.status_code, 200, "Status code should be 200");
-                assert!(content.word_count > 0, "Word count should be greater than 0");
+/// Stream a response body into memory, failing with `BodyTooLarge` as soon as it passes `limit` bytes
+/// (straight away when the declared Content-Length already does)
+pub(crate) async fn read_body_limited(mut response: reqwest::Response, limit: usize) -> Result<Vec<u8>> {
+    if response.content_length().is_some_and(|len| len > limit as u64) {
+        return Err(ScrapeError::BodyTooLarge { limit }.into());
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| anyhow!("Failed to read response body: {}", e))? {
+        if body.len() + chunk.len() > limit {
+            return Err(ScrapeError::BodyTooLarge { limit }.into());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// Decompress a gzip body into text, enforcing `max_bytes` on both the compressed and decompressed size.
+/// Bodies without the gzip magic number were already decoded in transit (Content-Encoding) and pass through.
+fn decompress_gzip_text(bytes: &[u8], max_bytes: usize) -> Result<String> {
+    use std::io::Read;
+
+    if bytes.len() > max_bytes {
+        return Err(anyhow!("Compressed body exceeds limit of {} bytes", max_bytes));
+    }
+    let decompressed = if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut out = Vec::new();
+        flate2::read::MultiGzDecoder::new(bytes)
+            .take(max_bytes as u64 + 1)
+            .read_to_end(&mut out)
+            .map_err(|e| anyhow!("Failed to decompress gzip body: {}", e))?;
+        if out.len() > max_bytes {
+            return Err(anyhow!("Decompressed body exceeds limit of {} bytes", max_bytes));
+        }
+        out
+    } else {
+        bytes.to_vec()
+    };
+    String::from_utf8(decompressed).map_err(|_| anyhow!("Gzip payload is not UTF-8 text"))
+}
+
+/// Decode a response body with the header charset (UTF-8 by default, BOM wins). When that yields
+/// many U+FFFD replacement characters and the page's `<meta>` declares a different charset, the
+/// body is re-decoded with the meta charset, keeping whichever decode has fewer replacements.
+pub fn decode_body(bytes: &[u8], content_type: &str) -> String {
+    let header_encoding = charset_param(content_type)
+        .and_then(|label| encoding_rs::Encoding::for_label(label.as_bytes()))
+        .unwrap_or(encoding_rs::UTF_8);
+    let (decoded, _, _) = header_encoding.decode(bytes);
+    let replacements = decoded.matches('\u{FFFD}').count();
+    // More than 0.1% replacement characters points at the wrong charset
+    if replacements == 0 || replacements * 1000 < decoded.chars().count() {
+        return decoded.into_owned();
+    }
+    let Some(meta_encoding) = meta_charset(bytes).and_then(|label| encoding_rs::Encoding::for_label(label.as_bytes())) else {
+        return decoded.into_owned();
+    };
+    if meta_encoding == header_encoding {
+        return decoded.into_owned();
+    }
+    let (redecoded, _, _) = meta_encoding.decode(bytes);
+    if redecoded.matches('\u{FFFD}').count() < replacements {
+        info!("Re-decoded body as {} (header declared {})", meta_encoding.name(), header_encoding.name());
+        redecoded.into_owned()
+    } else {
+        decoded.into_owned()
+    }
+}
+
+/// `charset=` parameter of a Content-Type value
+fn charset_param(content_type: &str) -> Option<String> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim().eq_ignore_ascii_case("charset").then(|| value.trim().trim_matches(['"', '\'']).to_string())
+    })
+}
+
+/// Charset declared by `<meta charset>` or `<meta http-equiv="Content-Type">` in the first 4 KiB
+fn meta_charset(bytes: &[u8]) -> Option<String> {
+    static META_CHARSET: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    let re = META_CHARSET.get_or_init(|| Regex::new(r#"(?i)<meta[^>]+charset\s*=\s*["']?([a-z0-9_\-:.]+)"#).unwrap());
+    // Charset labels are ASCII, so a lossy view of the prefix is enough to find them
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(4096)]);
+    re.captures(&head).map(|c| c[1].to_string())
+}
+
+/// JSON state that SPAs embed in scripts, often holding the article text when the DOM is empty:
+/// `<script type="application/json" id="__NEXT_DATA__">` blocks and `window.__NAME__ = {...}`
+/// assignments. Only `__DUNDER__` names are considered, and a literal is kept only if it parses as JSON.
+fn extract_inline_state(document: &Html) -> Vec<serde_json::Value> {
+    static ASSIGNMENT: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    let re = ASSIGNMENT.get_or_init(|| {
+        Regex::new(r#"\b(?:window|self|globalThis)(?:\.__[A-Za-z0-9_]+__|\[["']__[A-Za-z0-9_]+__["']\])\s*=\s*[\[{]"#).unwrap()
+    });
+    let Ok(sel) = Selector::parse("script") else { return Vec::new() };
+    let mut blobs = Vec::new();
+    for script in document.select(&sel) {
+        let text = script.text().collect::<String>();
+        let el = script.value();
+        let json_block = el.attr("type").is_some_and(|t| t.eq_ignore_ascii_case("application/json"))
+            && el.id().is_some_and(|id| id.len() > 4 && id.starts_with("__") && id.ends_with("__"));
+        if json_block {
+            blobs.extend(serde_json::from_str::<serde_json::Value>(text.trim()).ok());
+            continue;
+        }
+        for assignment in re.find_iter(&text) {
+            // The match ends just past the opening bracket; parse one value from there and ignore what follows
+            let literal = &text[assignment.end() - 1..];
+            if let Some(Ok(value)) = serde_json::Deserializer::from_str(literal).into_iter::<serde_json::Value>().next() {
+                blobs.push(value);
             }
-            Err(e) => {
-                println!("Rust scraper test failed: {}", e);
+        }
+    }
+    blobs
+}
+
+/// The element most likely to hold the page's main content: the wordiest match of the first
+/// content selector that matches, down to `body`
+fn main_container(document: &Html) -> Option<scraper::ElementRef<'_>> {
+    let candidates = [
+        "article", "main", "[role=main]", "[itemprop=articleBody]", ".entry-content", ".post-content",
+        ".article-content", "#content", "body",
+    ];
+    candidates
+        .iter()
+        .filter_map(|s| Selector::parse(s).ok())
+        .find_map(|sel| {
+            document
+                .select(&sel)
+                .max_by_key(|el| el.text().map(|t| t.split_whitespace().count()).sum::<usize>())
+        })
+}
+
+/// Readability's extractor with panics turned into errors, so markup that trips a bug inside the
+/// crate only costs the readability pass and the caller falls through to the other strategies.
+/// Its recursion can still overflow the stack on absurdly deep nesting, which is not a panic.
+fn readability_extract(pre: &str, base_url: &Url) -> std::result::Result<extractor::Product, String> {
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| extractor::extract(&mut pre.as_bytes(), base_url))) {
+        Ok(result) => result.map_err(|e| e.to_string()),
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            Err(format!("panicked: {}", message))
+        }
+    }
+}
+
+/// Share of the element's non-whitespace text that sits inside links (0 for an empty element)
+fn link_density(el: scraper::ElementRef) -> f64 {
+    let chars = |text: scraper::element_ref::Text| text.flat_map(str::chars).filter(|c| !c.is_whitespace()).count();
+    let total = chars(el.text());
+    if total == 0 {
+        return 0.0;
+    }
+    let Ok(anchors) = Selector::parse("a") else { return 0.0 };
+    let linked: usize = el.select(&anchors).map(|a| chars(a.text())).sum();
+    linked as f64 / total as f64
+}
+
+/// Visible anchor text, collapsed; icon-only links fall back to `aria-label`, `title`, then a child `img[alt]`
+fn link_label(anchor: &scraper::ElementRef) -> String {
+    let visible = anchor.text().collect::<Vec<_>>().join(" ");
+    let visible = visible.split_whitespace().collect::<Vec<_>>().join(" ");
+    if !visible.is_empty() {
+        return visible;
+    }
+    let attr = |name: &str| anchor.value().attr(name).map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+    attr("aria-label")
+        .or_else(|| attr("title"))
+        .or_else(|| {
+            let img = Selector::parse("img[alt]").ok()?;
+            anchor.select(&img).find_map(|el| el.value().attr("alt").map(str::trim).filter(|v| !v.is_empty()).map(str::to_string))
+        })
+        .unwrap_or_default()
+}
+
+/// Scripts written without spaces between words, where each character is counted as a word
+fn is_unspaced_script(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'     // Hiragana, Katakana
+        | '\u{3400}'..='\u{4DBF}'   // CJK Extension A
+        | '\u{4E00}'..='\u{9FFF}'   // CJK Unified Ideographs
+        | '\u{F900}'..='\u{FAFF}'   // CJK Compatibility Ideographs
+        | '\u{0E00}'..='\u{0E7F}'   // Thai
+        | '\u{20000}'..='\u{2FA1F}' // CJK Extensions B-F
+    )
+}
+
+/// Language-aware word count: whitespace-delimited words for spaced scripts, one word per
+/// character for CJK/Thai, so mixed text like "Rust 编程语言" counts 1 + 4
+pub fn count_words(text: &str) -> usize {
+    text.split_whitespace()
+        .map(|token| {
+            if !token.chars().any(is_unspaced_script) {
+                return 1;
             }
+            let mut words = 0;
+            let mut in_word = false;
+            for c in token.chars() {
+                if is_unspaced_script(c) {
+                    words += 1;
+                    in_word = false;
+                } else if c.is_alphanumeric() {
+                    if !in_word {
+                        words += 1;
+                    }
+                    in_word = true;
+                } else {
+                    in_word = false;
+                }
+            }
+            words
+        })
+        .sum()
+}
+
+/// Non-whitespace characters in `text`
+pub fn count_chars(text: &str) -> usize {
+    text.chars().filter(|c| !c.is_whitespace()).count()
+}
+
+/// Cut `text` to at most `max` characters, marking the cut with an ellipsis
+fn truncate_chars(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((end, _)) => format!("{}…", text[..end].trim_end()),
+        None => text.to_string(),
+    }
+}
+
+/// Target of the page's `rel=next` link (`<link>` or `<a>`), resolved against `base`
+pub fn next_page_url(html: &str, base: &Url) -> Option<String> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("link[rel~=\"next\"][href], a[rel~=\"next\"][href]").ok()?;
+    let href = document.select(&selector).find_map(|el| el.value().attr("href").map(str::trim).filter(|h| !h.is_empty() && !h.starts_with('#')))?;
+    base.join(href).ok().map(|u| u.to_string())
+}
+
+/// Title separators that usually split the page title from the site name
+const TITLE_SEPARATORS: &[&str] = &[" | ", " - ", " – ", " — ", " :: ", " » ", " · ", " / "];
+
+/// Readability-style title cleanup. readability 0.3 reports the raw `<title>` as the product title,
+/// so the site-name trimming happens here: drop a segment equal to `og:site_name`, otherwise
+/// cut after the last separator, or before the first one when that leaves under three words.
+fn readable_title(title: &str, site_name: Option<&str>) -> Option<String> {
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    if title.is_empty() || title == "No Title" {
+        return None;
+    }
+    let Some(separator) = TITLE_SEPARATORS.iter().find(|s| title.contains(*s)) else { return Some(title) };
+    let segments: Vec<&str> = title.split(separator).map(str::trim).filter(|s| !s.is_empty()).collect();
+    if let Some(site) = site_name.map(str::trim).filter(|s| !s.is_empty()) {
+        let kept: Vec<&str> = segments.iter().copied().filter(|s| !s.eq_ignore_ascii_case(site)).collect();
+        if !kept.is_empty() && kept.len() < segments.len() {
+            return Some(kept.join(separator));
         }
     }
-    
-    #[test]
-    fn test_clean_text() {
-        let scraper = RustScraper::new();
-        let text = "  This   is    \n\n\n   some    text   \n\n  ";
-        let cleaned = scraper.clean_text(text);
-        assert_eq!(cleaned, "This is some text");
+    let word_count = |s: &str| s.split_whitespace().count();
+    let (head, _) = title.rsplit_once(separator)?;
+    if word_count(head) >= 3 {
+        return Some(head.trim().to_string());
     }
-    
-    #[test]
-    fn test_word_count() {
+    let (_, tail) = title.split_once(separator)?;
+    // Neither side is clearly the headline; keep the full title
+    Some(if word_count(tail) >= 3 { tail.trim() } else { title.as_str() }.to_string())
+}
+
+/// Hosts whose links in an author card are social profiles rather than article links
+const SOCIAL_HOSTS: &[&str] = &[
+    "twitter.com", "x.com", "github.com", "linkedin.com", "facebook.com", "instagram.com", "youtube.com",
+    "mastodon.social", "bsky.app", "threads.net", "medium.com",
+];
+
+fn is_social_profile(url: &str) -> bool {
+    Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_string)).is_some_and(|host| {
+        let host = host.strip_prefix("www.").unwrap_or(&host);
+        SOCIAL_HOSTS.iter().any(|s| host == *s || host.ends_with(&format!(".{}", s)))
+    })
+}
+
+/// Depth-first walk over every JSON object in a JSON-LD document (including @graph and nested values)
+fn visit_json_ld_nodes(value: &serde_json::Value, visit: &mut dyn FnMut(&serde_json::Map<String, serde_json::Value>)) {
+    match value {
+        serde_json::Value::Object(map) => {
+            visit(map);
+            for child in map.values() {
+                visit_json_ld_nodes(child, visit);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                visit_json_ld_nodes(item, visit);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Pages with at least this many words of content are articles, whatever walls they also show
+const WALL_MAX_WORDS: usize = 150;
+
+const AGE_GATE_PHRASES: &[&str] = &[
+    "are you 18", "are you over 18", "are you 21", "are you over 21", "you must be 18", "you must be 21",
+    "verify your age", "age verification", "enter your date of birth", "legal drinking age",
+];
+
+const LOGIN_WALL_PHRASES: &[&str] = &[
+    "sign in to continue", "log in to continue", "login to continue", "sign in to view", "log in to view",
+    "you must be logged in", "please log in", "please sign in",
+];
+
+const CONSENT_PHRASES: &[&str] = &[
+    "we use cookies", "this site uses cookies", "this website uses cookies", "we value your privacy",
+    "manage cookie preferences", "your privacy choices",
+];
+
+/// Emails and phones kept per vCard; contact pages rarely list more, directories list hundreds
+const MAX_VCARD_VALUES: usize = 10;
+
+/// The part of `href` after a case-insensitive URI scheme prefix like `mailto:`, without the query
+fn scheme_value<'a>(href: &'a str, scheme: &str) -> Option<&'a str> {
+    let head = href.get(..scheme.len())?;
+    if !head.eq_ignore_ascii_case(scheme) {
+        return None;
+    }
+    let rest = &href[scheme.len()..];
+    Some(rest.split('?').next().unwrap_or(rest))
+}
+
+/// Lowercased, percent-decoded address with exactly one `@` and a dotted domain
+fn normalize_email(raw: &str) -> Option<String> {
+    let decoded = percent_encoding::percent_decode_str(raw.trim()).decode_utf8_lossy();
+    let email = decoded.trim().to_ascii_lowercase();
+    let (local, domain) = email.split_once('@')?;
+    let valid = !local.is_empty()
+        && domain.contains('.')
+        && !domain.contains('@')
+        && !email.chars().any(|c| c.is_whitespace() || matches!(c, ',' | ';' | '<' | '>'));
+    valid.then_some(email)
+}
+
+/// Digits only, keeping a leading `+` for international numbers; under 7 digits is not a phone number
+fn normalize_phone(raw: &str) -> Option<String> {
+    let decoded = percent_encoding::percent_decode_str(raw.trim()).decode_utf8_lossy();
+    let decoded = decoded.trim();
+    let digits: String = decoded.chars().filter(char::is_ascii_digit).collect();
+    if !(7..=15).contains(&digits.len()) {
+        return None;
+    }
+    Some(if decoded.starts_with('+') { format!("+{}", digits) } else { digits })
+}
+
+fn push_unique(values: &mut Vec<String>, value: String) {
+    if !values.contains(&value) {
+        values.push(value);
+    }
+}
+
+/// The vCard `ADR` components (street, locality, region, postal code, country) of a JSON-LD `PostalAddress`
+fn postal_address(node: &serde_json::Map<String, serde_json::Value>) -> Option<[String; 5]> {
+    let field = |key: &str| -> String {
+        let value = match node.get(key) {
+            // addressCountry may be a Country node
+            Some(serde_json::Value::Object(country)) => country.get("name"),
+            other => other,
+        };
+        value.and_then(|v| v.as_str()).map(|s| s.split_whitespace().collect::<Vec<_>>().join(" ")).unwrap_or_default()
+    };
+    let parts = ["streetAddress", "addressLocality", "addressRegion", "postalCode", "addressCountry"].map(field);
+    parts.iter().any(|p| !p.is_empty()).then_some(parts)
+}
+
+/// Escape a vCard text value (RFC 2426 §4)
+fn vcard_escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' | ',' | ';' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// A vCard 3.0 with CRLF line endings and lines folded at 75 octets. A person's `N` is split into
+/// family and given names at the last space; an organisation card leaves `N` empty and sets `ORG`.
+fn render_vcard(name: &str, is_person: bool, org: Option<&str>, emails: &[String], phones: &[String], address: Option<&[String; 5]>) -> String {
+    let mut lines = vec!["BEGIN:VCARD".to_string(), "VERSION:3.0".to_string(), format!("FN:{}", vcard_escape(name))];
+    if is_person {
+        let (given, family) = name.rsplit_once(' ').unwrap_or(("", name));
+        lines.push(format!("N:{};{};;;", vcard_escape(family), vcard_escape(given)));
+    } else {
+        lines.push("N:;;;;".to_string());
+    }
+    if let Some(org) = org.or((!is_person).then_some(name)).filter(|o| !o.is_empty()) {
+        lines.push(format!("ORG:{}", vcard_escape(org)));
+    }
+    lines.extend(emails.iter().map(|e| format!("EMAIL;TYPE=INTERNET:{}", e)));
+    lines.extend(phones.iter().map(|p| format!("TEL;TYPE=VOICE:{}", p)));
+    if let Some(parts) = address {
+        let parts: Vec<String> = parts.iter().map(|p| vcard_escape(p)).collect();
+        lines.push(format!("ADR;TYPE=WORK:;;{}", parts.join(";")));
+    }
+    lines.push("END:VCARD".to_string());
+    let mut card = String::new();
+    for line in lines {
+        let mut width = 0;
+        for c in line.chars() {
+            if width + c.len_utf8() > 75 {
+                card.push_str("\r\n ");
+                width = 1;
+            }
+            card.push(c);
+            width += c.len_utf8();
+        }
+        card.push_str("\r\n");
+    }
+    card
+}
+
+/// Whether a JSON-LD node's `@type` (string or array) is one of `types`
+fn json_ld_type_is(node: &serde_json::Map<String, serde_json::Value>, types: &[&str]) -> bool {
+    match node.get("@type") {
+        Some(serde_json::Value::String(t)) => types.contains(&t.as_str()),
+        Some(serde_json::Value::Array(ts)) => ts.iter().filter_map(|t| t.as_str()).any(|t| types.contains(&t)),
+        _ => false,
+    }
+}
+
+/// JSON-LD prices may be numbers or strings
+fn json_price(value: &serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::String(s) => parse_price_number(s),
+        _ => None,
+    }
+}
+
+/// Parse a price such as "1,299.99", "12,50" or "€ 5" into a number
+fn parse_price_number(text: &str) -> Option<f64> {
+    let start = text.find(|c: char| c.is_ascii_digit())?;
+    let numeric: String = text[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == ',' || *c == '.' || *c == ' ' || *c == '\u{a0}')
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let numeric = numeric.trim_end_matches([',', '.']);
+    let last_dot = numeric.rfind('.');
+    let last_comma = numeric.rfind(',');
+    let normalized = match (last_dot, last_comma) {
+        // Whichever separator comes last is the decimal separator
+        (Some(d), Some(c)) if c > d => numeric.replace('.', "").replace(',', "."),
+        (Some(_), Some(_)) => numeric.replace(',', ""),
+        // A lone comma followed by exactly two digits is a decimal comma ("12,50")
+        (None, Some(c)) if numeric.len() - c - 1 == 2 && numeric.matches(',').count() == 1 => numeric.replace(',', "."),
+        (None, Some(_)) => numeric.replace(',', ""),
+        _ => numeric.to_string(),
+    };
+    normalized.parse::<f64>().ok()
+}
+
+/// Currency symbols and their ISO 4217 codes; multi-character symbols first so "R$" wins over "$"
+const CURRENCY_SYMBOLS: &[(&str, &str)] = &[
+    ("US$", "USD"), ("R$", "BRL"), ("C$", "CAD"), ("A$", "AUD"), ("NZ$", "NZD"), ("HK$", "HKD"),
+    ("$", "USD"), ("€", "EUR"), ("£", "GBP"), ("¥", "JPY"), ("₹", "INR"), ("₩", "KRW"),
+    ("₽", "RUB"), ("₺", "TRY"), ("₪", "ILS"), ("₫", "VND"), ("₴", "UAH"), ("zł", "PLN"),
+];
+
+/// Normalize a currency symbol or code to an ISO code where possible
+fn normalize_currency(raw: &str) -> String {
+    let trimmed = raw.trim();
+    CURRENCY_SYMBOLS
+        .iter()
+        .find(|(symbol, _)| *symbol == trimmed)
+        .map(|(_, iso)| iso.to_string())
+        .unwrap_or_else(|| trimmed.to_ascii_uppercase())
+}
+
+/// Find a currency in free text: an explicit 3-letter code, else a known symbol
+fn currency_from_text(text: &str) -> Option<String> {
+    static ISO_RE: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    let iso_re = ISO_RE.get_or_init(|| Regex::new(r"\b(USD|EUR|GBP|JPY|CHF|CAD|AUD|INR|CNY|SEK|NOK|DKK|PLN|BRL|MXN)\b").unwrap());
+    if let Some(m) = iso_re.find(text) {
+        return Some(m.as_str().to_string());
+    }
+    CURRENCY_SYMBOLS
+        .iter()
+        .find(|(symbol, _)| text.contains(symbol))
+        .map(|(_, iso)| iso.to_string())
+}
+
+/// "https://schema.org/InStock" -> "InStock"
+fn short_availability(raw: &str) -> String {
+    raw.rsplit('/').next().unwrap_or(raw).to_string()
+}
+
+/// Parse a plausible date from permalink patterns like `/2023/05/12/` or `/2023-05-12-slug`
+fn date_from_url_path(url: &Url) -> Option<String> {
+    static DATE_RE: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    let date_re = DATE_RE.get_or_init(|| {
+        Regex::new(r"(?:^|/)((?:19|20)\d{2})[/-](\d{1,2})[/-](\d{1,2})(?:[/\-_.]|$)").unwrap()
+    });
+    let caps = date_re.captures(url.path())?;
+    let date = chrono::NaiveDate::from_ymd_opt(caps[1].parse().ok()?, caps[2].parse().ok()?, caps[3].parse().ok()?)?;
+    // Reject dates before the web existed or in the future (allowing for time zones)
+    let latest = Utc::now().date_naive().succ_opt()?;
+    if date.year() < 1991 || date > latest {
+        return None;
+    }
+    Some(date.format("%Y-%m-%d").to_string())
+}
+
+/// Days between `today` and the first of `dates` (most reliable first) that parses and isn't in the future
+fn age_days(dates: &[Option<&str>], today: chrono::NaiveDate) -> Option<i64> {
+    dates
+        .iter()
+        .flatten()
+        .filter_map(|raw| crate::search::parse_result_date(raw))
+        .map(|date| (today - date).num_days())
+        .find(|days| *days >= 0)
+}
+
+/// A viewport that adapts to the device width, e.g. "width=device-width, initial-scale=1"
+fn is_responsive_viewport(viewport: &str) -> bool {
+    viewport
+        .split([',', ';'])
+        .filter_map(|part| part.split_once('='))
+        .any(|(key, value)| key.trim().eq_ignore_ascii_case("width") && value.trim().eq_ignore_ascii_case("device-width"))
+}
+
+/// Validate a CSS color (hex, rgb()/hsl() functions, or a basic named color) and return it normalized
+fn normalize_css_color(raw: &str) -> Option<String> {
+    static FUNCTIONAL_RE: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    const NAMED: &[&str] = &[
+        "black", "silver", "gray", "grey", "white", "maroon", "red", "purple", "fuchsia", "green", "lime", "olive",
+        "yellow", "navy", "blue", "teal", "aqua", "orange", "transparent",
+    ];
+    let value = raw.trim().to_ascii_lowercase();
+    if let Some(hex) = value.strip_prefix('#') {
+        return (matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())).then_some(value);
+    }
+    let functional_re = FUNCTIONAL_RE.get_or_init(|| Regex::new(r"^(?:rgba?|hsla?)\(\s*[-+0-9.%\s,/deg]+\)$").unwrap());
+    (functional_re.is_match(&value) || NAMED.contains(&value.as_str())).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn test_rust_scraper() {
+        let scraper = RustScraper::new();
+        
+        // Test with a simple HTML page
+        match scraper.scrape_url("https://httpbin.org/html").await {
+            Ok(content) => {
+                assert!(!content.title.is_empty(), "Title should not be empty");
+                assert!(!content.clean_content.is_empty(), "Content should not be empty");
+                assert_eq!(content.status_code, 200, "Status code should be 200");
+                assert!(content.word_count > 0, "Word count should be greater than 0");
+            }
+            Err(e) => {
+                println!("Rust scraper test failed: {}", e);
+            }
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_scrape_plain_text() {
+        use axum::{http::header, routing::get, Router};
+        let app = Router::new().route("/files/notes.txt", get(|| async {
+            (
+                [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+                "First line of notes.\n\n  indented <b>not markup</b> line\n",
+            )
+        }));
+        let base = crate::test_support::serve(app).await;
+
+        let scraper = crate::test_support::local_scraper();
+        let content = scraper
+            .scrape_url(&format!("{}/files/notes.txt", base))
+            .await
+            .expect("plain text scrape");
+        assert_eq!(content.title, "notes.txt");
+        assert_eq!(content.clean_content, "First line of notes.\n\n  indented <b>not markup</b> line");
+        assert_eq!(content.word_count, 8);
+        assert!(content.headings.is_empty() && content.links.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_oversized_response_headers() {
+        use axum::{http::{HeaderMap, HeaderName, HeaderValue}, response::Html, routing::get, Router};
+        let page = "<html><head><title>Headers</title></head><body><p>Served with unusual headers.</p></body></html>";
+        let app = Router::new()
+            .route("/big-cookie", get(move || async move {
+                let mut headers = HeaderMap::new();
+                headers.insert("set-cookie", HeaderValue::from_str(&format!("session={}", "x".repeat(32 * 1024))).unwrap());
+                (headers, Html(page))
+            }))
+            .route("/many", get(move || async move {
+                let mut headers = HeaderMap::new();
+                for i in 0..150 {
+                    headers.insert(HeaderName::from_bytes(format!("x-debug-{}", i).as_bytes()).unwrap(), HeaderValue::from_static("1"));
+                }
+                (headers, Html(page))
+            }));
+        let base = crate::test_support::serve(app).await;
+        let scraper = crate::test_support::local_scraper();
+
+        // A single large header is within the client's buffer
+        let content = scraper.scrape_url(&format!("{}/big-cookie", base)).await.unwrap();
+        assert_eq!(content.title, "Headers");
+
+        // Too many headers fails with a typed, readable error instead of a bare parse failure
+        let err = scraper.scrape_url(&format!("{}/many", base)).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<ScrapeError>(), Some(ScrapeError::HeadersTooLarge { .. })));
+        assert!(err.to_string().contains("headers too large"));
+    }
+
+    #[test]
+    fn test_nav_heavy_mdbook_content_loses_to_readability() {
+        let links: String = (1..=4).map(|i| format!("<li><a href=\"/topic-{}\">Reference topic number {}</a></li>", i, i)).collect();
+        let body = "Garbage collection pauses were traced to an oversized young generation in the ingest service. ";
+        let html = format!(
+            "<html><head><title>Postmortem</title></head><body><div id=\"content\"><div><ul>{}</ul></div>\
+             <article><h1>Postmortem</h1><p>{}</p><p>{}</p></article></div></body></html>",
+            links, body.repeat(4), body.repeat(3)
+        );
+        let base = Url::parse("https://example.com/postmortem").unwrap();
+        let scraper = RustScraper::new();
+
+        // The container passes the mdBook size bar, but with a link block on top of the article
+        let (md_text, _) = scraper.extract_mdbook_like(&scraper.preprocess_html(&html)).expect("mdBook candidate");
+        assert!(md_text.contains("Reference topic number 3"));
+        let (content, _) = scraper.extract_clean_content(&html, &base);
+        assert!(content.contains("Garbage collection pauses"));
+        assert!(!content.contains("Reference topic number"), "{}", content);
+
+        // A higher size bar skips the mdBook candidate outright
+        let strict = RustScraper::new().with_mdbook_threshold(10_000, 0);
+        assert!(strict.extract_mdbook_like(&strict.preprocess_html(&html)).is_none());
+    }
+
+    #[test]
+    fn test_parallel_extraction_matches_sequential() {
+        let paragraph = "Ownership is a set of rules that govern how a program manages memory while it runs. ";
+        let fixtures = [
+            // mdBook layout
+            format!("<html><body><nav>Chapters</nav><div id=\"content\"><main><h1>Ownership</h1><p>{}</p></main></div></body></html>", paragraph.repeat(12)),
+            // Article that readability handles
+            format!("<html><head><title>News</title></head><body><header>Site menu</header><article><h1>Story</h1><p>{}</p><p>{}</p></article><footer>Contact</footer></body></html>", paragraph.repeat(5), paragraph.repeat(4)),
+            // Content spread over plain divs for the heuristics
+            format!("<html><body><div class=\"sidebar\">Links</div><div role=\"main\"><div>{}</div><div>{}</div></div></body></html>", paragraph.repeat(3), paragraph.repeat(6)),
+            // Almost nothing to extract
+            "<html><body><p>Short.</p></body></html>".to_string(),
+        ];
+        let base = Url::parse("https://example.com/page").unwrap();
+        let sequential = RustScraper::new().with_parallel_extraction(0);
+        let parallel = RustScraper::new().with_parallel_extraction(1);
+        for html in &fixtures {
+            assert_eq!(parallel.extract_clean_content(html, &base).0, sequential.extract_clean_content(html, &base).0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invalid_certs_opt_in() {
+        let page = "<html><head><title>Internal docs</title></head><body><article><p>Served with a self-signed certificate.</p></article></body></html>";
+        let base = crate::test_support::serve_self_signed_tls(page).await;
+
+        let err = crate::test_support::local_scraper().with_invalid_certs(false).scrape_url(&base).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<ScrapeError>(), Some(ScrapeError::Fetch { .. })));
+
+        let content = crate::test_support::local_scraper().with_invalid_certs(true).scrape_url(&base).await.unwrap();
+        assert_eq!(content.title, "Internal docs");
+    }
+
+    #[test]
+    fn test_extract_toc_with_anchors() {
+        let scraper = RustScraper::new();
+        let html = r#"<html><body>
+            <h1 id="intro">Intro</h1>
+            <h2 id="setup">Setup</h2>
+            <h3><a name="linux"></a>On   Linux</h3>
+            <h2>No anchor</h2>
+        </body></html>"#;
+        let toc = scraper.extract_toc(&Html::parse_document(html));
+        assert_eq!(
+            toc,
+            vec![
+                TocEntry { level: 1, text: "Intro".into(), anchor: Some("intro".into()) },
+                TocEntry { level: 2, text: "Setup".into(), anchor: Some("setup".into()) },
+                TocEntry { level: 3, text: "On Linux".into(), anchor: Some("linux".into()) },
+                TocEntry { level: 2, text: "No anchor".into(), anchor: None },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_author_profile_from_person_json_ld() {
+        let html = r#"<html><head><script type="application/ld+json">
+            {"@context": "https://schema.org", "@type": "BlogPosting", "headline": "Shipping faster",
+             "author": {"@type": "Person", "name": "Ada Example", "url": "/authors/ada",
+                        "image": {"@type": "ImageObject", "url": "/img/ada.jpg"},
+                        "sameAs": ["https://github.com/ada-example", "https://x.com/ada_example"]}}
+            </script></head><body>
+            <div class="author-card"><img src="/img/other.png"><a href="https://github.com/ada-example">GitHub</a>
+            <a href="https://www.linkedin.com/in/ada-example">LinkedIn</a><a href="/posts">More posts</a></div>
+            </body></html>"#;
+        let base = Url::parse("https://blog.example.com/2024/shipping-faster").unwrap();
+        let profile = RustScraper::new().extract_author_profile(&Html::parse_document(html), &base).unwrap();
+        assert_eq!(profile.name.as_deref(), Some("Ada Example"));
+        assert_eq!(profile.url.as_deref(), Some("https://blog.example.com/authors/ada"));
+        assert_eq!(profile.image.as_deref(), Some("https://blog.example.com/img/ada.jpg"));
+        assert_eq!(
+            profile.social,
+            vec!["https://github.com/ada-example", "https://x.com/ada_example", "https://www.linkedin.com/in/ada-example"]
+        );
+
+        let bare = "<html><body><p>No author here.</p></body></html>";
+        assert!(RustScraper::new().extract_author_profile(&Html::parse_document(bare), &base).is_none());
+    }
+
+    #[test]
+    fn test_extract_audio_object_json_ld() {
+        let html = r#"<html><head><script type="application/ld+json">
+            {"@context": "https://schema.org", "@type": "PodcastEpisode", "name": "Episode 12",
+             "associatedMedia": {"@type": "AudioObject", "contentUrl": "/media/ep12.mp3",
+                                 "duration": "PT42M10S", "encodingFormat": "audio/mpeg"}}
+            </script><meta property="og:audio" content="https://cdn.example.com/ep12-preview.ogg">
+            </head><body><audio controls><source src="/media/ep12.mp3" type="audio/mpeg"></audio></body></html>"#;
+        let scraper = RustScraper::new();
+        let base = Url::parse("https://podcast.example.com/episodes/12").unwrap();
+        let audio = scraper.extract_audio(&Html::parse_document(html), &base);
+        assert_eq!(
+            audio,
+            vec![
+                AudioMeta {
+                    url: "https://podcast.example.com/media/ep12.mp3".to_string(),
+                    duration: Some("PT42M10S".to_string()),
+                    mime: Some("audio/mpeg".to_string()),
+                },
+                AudioMeta { url: "https://cdn.example.com/ep12-preview.ogg".to_string(), duration: None, mime: None },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_readable_title_drops_site_suffix() {
+        let html = r#"<html><head><title>How Rust Lifetimes Work | Example Engineering Blog</title></head>
+            <body><article><p>Lifetimes describe how long references stay valid.</p></article></body></html>"#;
+        let base = Url::parse("https://blog.example.com/lifetimes").unwrap();
+        let result = RustScraper::new().extract_page(base.as_str(), &base, html.to_string(), 200, "text/html".to_string());
+        assert_eq!(result.title, "How Rust Lifetimes Work | Example Engineering Blog");
+        assert_eq!(result.readable_title.as_deref(), Some("How Rust Lifetimes Work"));
+
+        assert_eq!(readable_title("Example News - Markets rally after rate cut", Some("Example News")).as_deref(), Some("Markets rally after rate cut"));
+        assert_eq!(readable_title("Docs - Install", None).as_deref(), Some("Docs - Install"));
+    }
+
+    #[test]
+    fn test_heading_caps() {
+        let html = format!(
+            "<html><body><h1>{}</h1>{}</body></html>",
+            "Very long heading ".repeat(10),
+            (1..=5).map(|n| format!("<h2>Section {}</h2>", n)).collect::<String>()
+        );
+        let scraper = RustScraper::new().with_heading_limits(3, 20);
+        let (headings, total) = scraper.extract_headings(&Html::parse_document(&html));
+        assert_eq!(total, 6);
+        let texts: Vec<&str> = headings.iter().map(|h| h.text.as_str()).collect();
+        assert_eq!(texts, vec!["Very long heading Ve…", "Section 1", "Section 2"]);
+    }
+
+    #[test]
+    fn test_extract_og_video_with_dimensions() {
+        let html = r#"<html><head>
+            <meta property="og:video" content="/videos/launch.mp4">
+            <meta property="og:video:type" content="video/mp4">
+            <meta property="og:video:width" content="1280">
+            <meta property="og:video:height" content="720">
+            </head><body></body></html>"#;
+        let base = Url::parse("https://media.example.com/posts/launch").unwrap();
+        let video = RustScraper::new().extract_og_video(&Html::parse_document(html), &base);
+        assert_eq!(
+            video,
+            Some(OgVideo {
+                url: "https://media.example.com/videos/launch.mp4".to_string(),
+                width: Some(1280),
+                height: Some(720),
+                mime: Some("video/mp4".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_icon_link_label_fallbacks() {
+        let html = r#"<html><body>
+            <a href="/search" aria-label="Search"><svg><path d="M0 0"/></svg></a>
+            <a href="/help" title="Help center"><i class="icon-help"></i></a>
+            <a href="/home"><img src="/logo.png" alt="Home"></a>
+            <a href="/docs">  Read   the docs </a>
+            </body></html>"#;
+        let base = Url::parse("https://example.com/").unwrap();
+        let links = RustScraper::new().extract_links(&Html::parse_document(html), &base);
+        let labels: Vec<&str> = links.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(labels, vec!["Search", "Help center", "Home", "Read the docs"]);
+    }
+
+    #[test]
+    fn test_raw_urls_keep_relative_hrefs() {
+        let html = r#"<html><body><a href="../guide/intro.html">Intro</a><img src="img/diagram.png" alt="Diagram"></body></html>"#;
+        let base = Url::parse("https://example.com/docs/latest/index.html").unwrap();
+        let document = Html::parse_document(html);
+        let scraper = RustScraper::new().with_raw_urls(true);
+
+        let links = scraper.extract_links(&document, &base);
+        assert_eq!(links[0].url, "https://example.com/docs/guide/intro.html");
+        assert_eq!(links[0].raw_href.as_deref(), Some("../guide/intro.html"));
+        let images = scraper.extract_images(&document, &base);
+        assert_eq!(images[0].src, "https://example.com/docs/latest/img/diagram.png");
+        assert_eq!(images[0].raw_src.as_deref(), Some("img/diagram.png"));
+
+        // Off by default
+        assert!(RustScraper::new().extract_links(&document, &base)[0].raw_href.is_none());
+    }
+
+    #[test]
+    fn test_blocks_are_typed_and_ordered() {
+        let html = r#"<html><head><title>Blocks</title></head><body><nav><a href="/">Home</a></nav><article>
+            <h2>Installing the toolchain</h2>
+            <p>Run the installer from the <a href="/dl">downloads page</a> and follow the prompts until it reports success.</p>
+            <pre><code>curl https://sh.rustup.rs -sSf | sh
+rustc --version</code></pre>
+            <p>The toolchain updates itself, so you rarely need to repeat these steps on the same machine again.</p>
+            <ul><li>Linux and macOS use the shell script</li><li>Windows uses the graphical installer</li></ul>
+            <blockquote><p>Restart your terminal after installing.</p></blockquote>
+        </article></body></html>"#;
+        let base = Url::parse("https://example.com/install").unwrap();
+        let page = RustScraper::new().with_blocks(true).extract_page(base.as_str(), &base, html.to_string(), 200, "text/html".to_string());
+
+        let kinds: Vec<BlockKind> = page.blocks.iter().map(|b| b.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![BlockKind::Heading, BlockKind::Paragraph, BlockKind::Code, BlockKind::Paragraph, BlockKind::ListItem, BlockKind::ListItem, BlockKind::Quote],
+            "{:#?}",
+            page.blocks
+        );
+        assert_eq!(page.blocks[0].text, "Installing the toolchain");
+        assert!(page.blocks[1].text.starts_with("Run the installer from the downloads page and"));
+        assert_eq!(page.blocks[2].text, "curl https://sh.rustup.rs -sSf | sh\nrustc --version");
+        assert_eq!(page.blocks[6].text, "Restart your terminal after installing.");
+
+        // Opt-in only
+        let page = RustScraper::new().extract_page(base.as_str(), &base, html.to_string(), 200, "text/html".to_string());
+        assert!(page.blocks.is_empty());
+    }
+
+    #[test]
+    fn test_vcard_from_contact_page() {
+        let base = Url::parse("https://acme.example/contact").unwrap();
+        let html = r#"<html><head><title>Contact</title>
+            <meta property="og:site_name" content="Acme, Inc.">
+            <script type="application/ld+json">{"@type": "Organization", "name": "Acme, Inc.",
+              "email": "mailto:Sales@Acme.example", "telephone": "+1 (555) 010-0199",
+              "address": {"@type": "PostalAddress", "streetAddress": "1 Main St", "addressLocality": "Springfield",
+                "addressRegion": "IL", "postalCode": "62701", "addressCountry": {"@type": "Country", "name": "US"}}}</script>
+            </head><body><p>Write to <a href="mailto:sales@acme.example?subject=Hi">sales</a>
+            or <a href="MAILTO:support%40acme.example">support</a>, call <a href="tel:+1-555-010-0199">us</a>
+            (not <a href="tel:123">this</a>).</p></body></html>"#;
+        let page = RustScraper::new().with_vcard(true).extract_page(base.as_str(), &base, html.to_string(), 200, "text/html".to_string());
+        let card = page.vcard.expect("contact page yields a vCard");
+        assert_eq!(
+            card,
+            "BEGIN:VCARD\r\nVERSION:3.0\r\nFN:Acme\\, Inc.\r\nN:;;;;\r\nORG:Acme\\, Inc.\r\n\
+             EMAIL;TYPE=INTERNET:sales@acme.example\r\nEMAIL;TYPE=INTERNET:support@acme.example\r\n\
+             TEL;TYPE=VOICE:+15550100199\r\nADR;TYPE=WORK:;;1 Main St;Springfield;IL;62701;US\r\nEND:VCARD\r\n"
+        );
+        assert!(card.split("\r\n").all(|line| line.len() <= 75));
+
+        let person = render_vcard("Ada Example", true, None, &["ada@example.com".to_string()], &[], None);
+        assert!(person.contains("FN:Ada Example\r\nN:Example;Ada;;;\r\n") && !person.contains("ORG:"));
+
+        let bare = "<html><body><p>No way to reach us.</p></body></html>";
+        assert!(RustScraper::new().with_vcard(true).extract_page(base.as_str(), &base, bare.to_string(), 200, "text/html".to_string()).vcard.is_none());
+        assert!(RustScraper::new().extract_page(base.as_str(), &base, html.to_string(), 200, "text/html".to_string()).vcard.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_respect_robots_blocks_disallowed_paths() {
+        use axum::{response::Html, routing::get, Router};
+        let app = Router::new()
+            .route("/robots.txt", get(|| async { "User-agent: *\nDisallow: /private\n" }))
+            .route("/public", get(|| async { Html("<html><head><title>Public</title></head><body><p>Open to crawlers.</p></body></html>") }))
+            .route("/private", get(|| async { Html("<html><head><title>Private</title></head></html>") }));
+        let base = crate::test_support::serve(app).await;
+        let scraper = crate::test_support::local_scraper().with_respect_robots(true);
+
+        assert!(scraper.is_allowed(&format!("{}/public", base), "mcp-server").await.unwrap());
+        assert!(!scraper.is_allowed(&format!("{}/private?x=1", base), "mcp-server").await.unwrap());
+        assert_eq!(scraper.scrape_url(&format!("{}/public", base)).await.unwrap().title, "Public");
+        let err = scraper.scrape_url(&format!("{}/private", base)).await.unwrap_err();
+        assert_eq!(crate::error::error_code(&err), "robots_disallowed");
+        assert!(err.to_string().starts_with("Blocked by robots.txt"));
+        // Off by default
+        assert!(crate::test_support::local_scraper().with_respect_robots(false).scrape_url(&format!("{}/private", base)).await.is_ok());
+
+        // No robots.txt at all allows everything
+        let bare = crate::test_support::serve(Router::new().route("/page", get(|| async { Html("<p>hi</p>") }))).await;
+        assert!(scraper.is_allowed(&format!("{}/page", bare), "mcp-server").await.unwrap());
+
+        // An oversized robots.txt is abandoned at the read limit rather than buffered, and allows for now
+        let huge = crate::test_support::serve(Router::new().route("/robots.txt", get(|| async {
+            format!("User-agent: *\nDisallow: /\n{}", "# padding\n".repeat(MAX_ROBOTS_BYTES / 10 + 1))
+        }))).await;
+        assert!(scraper.is_allowed(&format!("{}/page", huge), "mcp-server").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_long_crawl_delay_fails_instead_of_waiting() {
+        use axum::{response::Html, routing::get, Router};
+        let app = Router::new()
+            .route("/robots.txt", get(|| async { "User-agent: *\nCrawl-delay: 5\n" }))
+            .route("/page", get(|| async { Html("<html><head><title>Paced</title></head><body><p>Slowly please.</p></body></html>") }));
+        let base = crate::test_support::serve(app).await;
+        let scraper = crate::test_support::local_scraper().with_respect_robots(true);
+        let url = format!("{}/page", base);
+
+        assert_eq!(scraper.scrape_url(&url).await.unwrap().title, "Paced");
+        let started = std::time::Instant::now();
+        let err = scraper.scrape_url(&url).await.unwrap_err();
+        assert_eq!(crate::error::error_code(&err), "rate_limited");
+        let retry_after = crate::error::retry_after(&err).unwrap();
+        assert!(retry_after > crate::robots::MAX_CRAWL_WAIT && retry_after <= std::time::Duration::from_secs(5), "{:?}", retry_after);
+        // The fallback scraper is paced by the same queue
+        let err = crate::scrape::scrape_url_fallback(&scraper, &url, scraper.max_body_bytes()).await.unwrap_err();
+        assert_eq!(crate::error::error_code(&err), "rate_limited");
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_link_heavy_block_rejected_for_prose() {
+        let base = Url::parse("https://example.com/post").unwrap();
+        let nav_links: String = (1..=40).map(|i| format!("<li><a href=\"/topic/{i}\">Topic number {i} overview</a></li>")).collect();
+        let html = format!(
+            "<html><body><section class=\"content\"><ul>{}</ul></section>\
+             <article><p>The river rose overnight and crews worked until dawn to shore up the levee near the old mill. \
+             Residents on the east bank were told to expect road closures through the weekend while engineers \
+             inspect the damage.</p><p>See the <a href=\"/map\">closure map</a> for details.</p></article></body></html>",
+            nav_links
+        );
+        let scraper = RustScraper::new();
+        let heuristic = scraper.heuristic_main_extraction(&html);
+        assert!(heuristic.contains("crews worked until dawn") && !heuristic.contains("Topic number"), "{}", heuristic);
+        let page = scraper.extract_page(base.as_str(), &base, html.clone(), 200, "text/html".to_string());
+        assert!(page.clean_content.contains("crews worked until dawn") && !page.clean_content.contains("Topic number 17"), "{}", page.clean_content);
+
+        // With the check disabled the bigger navigation block wins the heuristic pass again
+        assert!(scraper.with_max_link_density(1.0).heuristic_main_extraction(&html).contains("Topic number"));
+    }
+
+    #[test]
+    fn test_adversarial_markup_does_not_escape_extraction() {
+        let nested = format!("<html><body>{}<p>{}</p></body></html>", "<div><table><tr><td>".repeat(40), "Buried text. ".repeat(20));
+        let inputs = [
+            String::new(),
+            "<html".to_string(),
+            "</body></html><html><body><p>Reopened after close</p>".to_string(),
+            "<html><head><base href=\"http://[::1\"></head><body><img src=\"//:\"><a href=\"http://%\">x</a><p>Broken URLs everywhere</p></body></html>".to_string(),
+            "<body><p>\u{0}\u{feff}\u{202e}control characters\u{fffd}</p><script><!--</script><p>unterminated".to_string(),
+            nested,
+        ];
+        let base = Url::parse("https://example.com/adversarial").unwrap();
+        for html in inputs {
+            // Either outcome is fine; a panic would fail the test
+            let _ = readability_extract(&html, &base);
+            let page = RustScraper::new().extract_page(base.as_str(), &base, html, 200, "text/html".to_string());
+            assert_eq!(page.url, base.as_str());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_private_addresses_refused_unless_allowed() {
+        use axum::{response::Html, routing::get, Router};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        let app = Router::new().route("/", get(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async { Html("<html><head><title>Internal</title></head><body><p>Admin panel</p></body></html>") }
+        }));
+        let base = crate::test_support::serve(app).await;
+        let guarded = RustScraper::new().with_allow_private_addresses(false);
+
+        for url in [format!("{}/", base), base.replace("127.0.0.1", "localhost"), "http://169.254.169.254/latest/meta-data/".to_string()] {
+            let err = guarded.scrape_url(&url).await.unwrap_err();
+            assert_eq!(crate::error::error_code(&err), "private_address", "{}", url);
+            assert!(err.to_string().starts_with("Refusing to scrape private address"));
+        }
+        assert!(guarded.fetch_metadata(&format!("{}/", base)).await.is_err());
+        assert_eq!(hits.load(Ordering::SeqCst), 0);
+
+        let page = RustScraper::new().with_allow_private_addresses(true).scrape_url(&format!("{}/", base)).await.unwrap();
+        assert_eq!(page.title, "Internal");
+    }
+
+    #[tokio::test]
+    async fn test_oversized_body_aborts_download() {
+        use axum::{body::{Body, Bytes}, routing::get, Router};
+        use futures::StreamExt;
+        // 64 MiB, streamed without a Content-Length, and 1 MiB declared up front
+        let app = Router::new()
+            .route("/stream", get(|| async {
+                Body::from_stream(futures::stream::repeat_with(|| Ok::<_, std::io::Error>(Bytes::from(vec![b'a'; 64 * 1024]))).take(1024))
+            }))
+            .route("/declared", get(|| async { "b".repeat(1024 * 1024) }));
+        let base = crate::test_support::serve(app).await;
+        let options = ScrapeOptions { max_body_bytes: Some(4096), ..Default::default() };
+
+        for path in ["/stream", "/declared"] {
+            let err = crate::test_support::local_scraper().scrape_url_with_options(&format!("{}{}", base, path), &options).await.unwrap_err();
+            assert_eq!(crate::error::error_code(&err), "body_too_large", "{}", path);
+            assert_eq!(err.to_string(), "response body exceeds the limit of 4096 bytes; download aborted");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scrape_options_override_agent_redirects_and_timeout() {
+        use axum::{http::HeaderMap, response::{Html, Redirect}, routing::get, Router};
+        let app = Router::new()
+            .route("/agent", get(|headers: HeaderMap| async move {
+                let agent = headers.get("user-agent").and_then(|v| v.to_str().ok()).unwrap_or_default().to_string();
+                Html(format!("<html><head><title>{agent}</title></head><body><p>Hello agent</p></body></html>"))
+            }))
+            .route("/hop1", get(|| async { Redirect::temporary("/hop2") }))
+            .route("/hop2", get(|| async { Redirect::temporary("/agent") }))
+            .route("/slow", get(|| async {
+                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                "late"
+            }));
+        let base = crate::test_support::serve(app).await;
+        let scraper = crate::test_support::local_scraper();
+
+        let options = ScrapeOptions { user_agent: Some("test-agent/1.0".to_string()), ..Default::default() };
+        let page = scraper.scrape_url_with_options(&format!("{}/hop1", base), &options).await.unwrap();
+        assert_eq!(page.title, "test-agent/1.0");
+
+        let options = ScrapeOptions { max_redirects: Some(1), ..Default::default() };
+        assert!(scraper.scrape_url_with_options(&format!("{}/hop1", base), &options).await.is_err());
+
+        let options = ScrapeOptions { timeout: Some(std::time::Duration::from_millis(100)), ..Default::default() };
+        let err = scraper.scrape_url_with_options(&format!("{}/slow", base), &options).await.unwrap_err();
+        assert_eq!(crate::error::error_code(&err), "timeout");
+    }
+
+    #[test]
+    fn test_structured_data_capped_by_count_and_size() {
+        let base = Url::parse("https://example.com/list").unwrap();
+        let scripts: String = (0..30)
+            .map(|i| format!(r#"<script type="application/ld+json">{{"@type":"Thing","name":"item {i}"}}</script>"#))
+            .collect();
+        let html = format!(
+            r#"<html><head>{scripts}<script type="application/ld+json">{{"@graph":[{{"@type":"A"}},{{"@type":"B"}}]}}</script></head><body><p>List</p></body></html>"#
+        );
+
+        let page = RustScraper::new()
+            .with_structured_data(true)
+            .with_structured_data_limits(10, 64 * 1024)
+            .extract_page(base.as_str(), &base, html.clone(), 200, "text/html".to_string());
+        assert_eq!(page.structured_data.len(), 10);
+        assert_eq!(page.structured_data[0]["name"], "item 0");
+        assert!(page.structured_data_truncated);
+
+        // Byte budget: the first objects are 34 bytes each, so 110 bytes fits three and no 13-byte `@graph` node after them
+        let page = RustScraper::new()
+            .with_structured_data(true)
+            .with_structured_data_limits(50, 110)
+            .extract_page(base.as_str(), &base, html.clone(), 200, "text/html".to_string());
+        assert_eq!(page.structured_data.len(), 3);
+        assert!(page.structured_data_truncated);
+
+        // Under both caps: every node comes back, `@graph` split into its nodes
+        let page = RustScraper::new()
+            .with_structured_data(true)
+            .with_structured_data_limits(50, 64 * 1024)
+            .extract_page(base.as_str(), &base, html.clone(), 200, "text/html".to_string());
+        assert_eq!(page.structured_data.len(), 32);
+        assert_eq!(page.structured_data[31]["@type"], "B");
+        assert!(!page.structured_data_truncated);
+
+        let page = RustScraper::new().extract_page(base.as_str(), &base, html, 200, "text/html".to_string());
+        assert!(page.structured_data.is_empty() && !page.structured_data_truncated);
+    }
+
+    #[test]
+    fn test_inline_state_blobs() {
+        let html = r#"<html><head><title>App</title></head><body><div id="__next"></div>
+            <script id="__NEXT_DATA__" type="application/json">{"props":{"pageProps":{"post":{"title":"Hydrated","body":"The article text lives here."}}},"page":"/posts/[slug]"}</script>
+            <script>window.__INITIAL_STATE__ = {"user":null,"items":[1,2]}; window.analytics = {"id": 3}; window.__BROKEN__ = {oops: true};</script>
+            <script type="application/json" id="config">{"not":"state"}</script>
+        </body></html>"#;
+        let base = Url::parse("https://example.com/posts/hydrated").unwrap();
+        let scraper = RustScraper::new().with_inline_state(true);
+
+        let page = scraper.extract_page(base.as_str(), &base, html.to_string(), 200, "text/html".to_string());
+        assert_eq!(page.inline_state.len(), 2);
+        assert_eq!(page.inline_state[0]["props"]["pageProps"]["post"]["body"], "The article text lives here.");
+        assert_eq!(page.inline_state[1], serde_json::json!({"user": null, "items": [1, 2]}));
+
+        // Opt-in only
+        let page = RustScraper::new().extract_page(base.as_str(), &base, html.to_string(), 200, "text/html".to_string());
+        assert!(page.inline_state.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_clients_share_http_timeout() {
+        use axum::{routing::get, Router};
+        assert_eq!(RustScraper::new().timeout(), crate::http_timeout());
+        assert_eq!(RustScraper::new().with_heading_limits(1, 1).timeout(), crate::http_timeout());
+        assert_eq!(RustScraper::new().with_invalid_certs(true).timeout(), crate::http_timeout());
+
+        // The main/stdio client builder and the scraper's client both give up on a stalled server at the
+        // timeout they are given; the defaults above pass `http_timeout` the same way
+        let timeout = std::time::Duration::from_millis(200);
+        let app = Router::new().route("/stall", get(|| async {
+            tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+            "late"
+        }));
+        let url = format!("{}/stall", crate::test_support::serve(app).await);
+        let shared = crate::http_client_builder_with_timeout(timeout).build().unwrap();
+        let scraper = crate::test_support::local_scraper().with_timeout(timeout);
+        let timed = |request: reqwest::RequestBuilder| async move {
+            let started = std::time::Instant::now();
+            let err = request.send().await.unwrap_err();
+            (err.is_timeout(), started.elapsed())
+        };
+        let (shared, scraper) = tokio::join!(timed(shared.get(&url)), timed(scraper.client().get(&url)));
+        for (timed_out, elapsed) in [shared, scraper] {
+            assert!(timed_out);
+            assert!(elapsed >= timeout && elapsed < timeout + std::time::Duration::from_secs(5), "{:?}", elapsed);
+        }
+    }
+
+    #[test]
+    fn test_extract_offers_json_ld() {
+        let scraper = RustScraper::new();
+        let html = r#"<html><head><script type="application/ld+json">
+            {"@context": "https://schema.org", "@type": "Product", "name": "Widget",
+             "offers": {"@type": "Offer", "price": "1,299.99", "priceCurrency": "USD",
+                        "availability": "https://schema.org/InStock"}}
+        </script></head><body><span class="price">$5.00</span></body></html>"#;
+        let offers = scraper.extract_offers(&Html::parse_document(html));
+        assert_eq!(offers, vec![Offer { price: 1299.99, currency: "USD".into(), availability: Some("InStock".into()) }]);
+    }
+
+    #[test]
+    fn test_extract_offers_microdata() {
+        let scraper = RustScraper::new();
+        let html = r#"<html><body><div itemscope itemtype="https://schema.org/Product">
+            <div itemprop="offers" itemscope itemtype="https://schema.org/Offer">
+                <meta itemprop="priceCurrency" content="EUR">
+                <span itemprop="price" content="12.50">12,50 €</span>
+                <link itemprop="availability" href="https://schema.org/OutOfStock">
+            </div></div></body></html>"#;
+        let offers = scraper.extract_offers(&Html::parse_document(html));
+        assert_eq!(offers, vec![Offer { price: 12.5, currency: "EUR".into(), availability: Some("OutOfStock".into()) }]);
+    }
+
+    #[test]
+    fn test_extract_offers_price_class_heuristic() {
+        let scraper = RustScraper::new();
+        let html = r#"<html><body><p class="price">£1.234,00</p></body></html>"#;
+        let offers = scraper.extract_offers(&Html::parse_document(html));
+        assert_eq!(offers, vec![Offer { price: 1234.0, currency: "GBP".into(), availability: None }]);
+    }
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        use std::io::Write;
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_scrape_gzipped_text() {
+        use axum::{http::header, routing::get, Router};
+        let body = gzip(b"id,name\n1,alpha\n2,beta\n");
+        let app = Router::new().route("/dumps/data.csv.gz", get(move || {
+            let body = body.clone();
+            async move { ([(header::CONTENT_TYPE, "application/gzip")], body) }
+        }));
+        let base = crate::test_support::serve(app).await;
+
+        let content = crate::test_support::local_scraper()
+            .scrape_url(&format!("{}/dumps/data.csv.gz", base))
+            .await
+            .expect("gzip scrape");
+        assert_eq!(content.clean_content, "id,name\n1,alpha\n2,beta");
+        assert_eq!(content.title, "data.csv.gz");
+    }
+
+    #[test]
+    fn test_decompress_gzip_respects_limit() {
+        let compressed = gzip(&vec![b'a'; 4096]);
+        assert_eq!(decompress_gzip_text(&compressed, 8192).unwrap().len(), 4096);
+        let err = decompress_gzip_text(&compressed, 1024).unwrap_err();
+        assert!(err.to_string().contains("exceeds limit"), "{}", err);
+        assert!(decompress_gzip_text(&gzip(&[0xff, 0xfe, 0x00]), 1024).is_err());
+    }
+
+    #[test]
+    fn test_preserve_whitespace_keeps_indentation() {
+        let text = "Example:\n\ndef f():\n    if x:\n        return 1\n\n\n\n+---+---+\n| a | b |\n";
+
+        let collapsed = RustScraper::new().post_clean_text(text);
+        assert!(!collapsed.contains("    return"));
+
+        let preserved = RustScraper::new().with_preserve_whitespace(true).post_clean_text(text);
+        assert_eq!(preserved, "Example:\n\ndef f():\n    if x:\n        return 1\n\n+---+---+\n| a | b |");
+    }
+
+    #[tokio::test]
+    async fn test_blocking_extraction_matches_direct() {
+        use axum::{response::Html as HtmlResponse, routing::get, Router};
+        const PAGE: &str = "<html lang=\"en\"><head><title>Blocking path</title></head><body>\
+            <article><h1>Heading one</h1><p>The quick brown fox jumps over the lazy dog while the \
+            scraper parses this document on a blocking thread instead of an async worker, which keeps \
+            network tasks responsive under heavy load. Extraction output must be identical either way, \
+            so this paragraph carries enough words to pass the content thresholds.</p>\
+            <h2 id=\"more\">More</h2><a href=\"/next\">Next page</a></article></body></html>";
+        let app = Router::new().route("/page", get(|| async { HtmlResponse(PAGE) }));
+        let base = crate::test_support::serve(app).await;
+        let url = format!("{}/page", base);
+
+        let scraper = crate::test_support::local_scraper();
+        let via_blocking = scraper.scrape_url(&url).await.expect("scrape");
+        let direct = scraper.extract_page(&url, &Url::parse(&url).unwrap(), PAGE.to_string(), 200, "text/html".into());
+
+        assert_eq!(via_blocking.title, "Blocking path");
+        assert_eq!(via_blocking.title, direct.title);
+        assert_eq!(via_blocking.clean_content, direct.clean_content);
+        assert_eq!(via_blocking.word_count, direct.word_count);
+        assert_eq!(via_blocking.toc, direct.toc);
+        assert_eq!(via_blocking.links.len(), 1);
+        assert_eq!(via_blocking.links[0].url, format!("{}/next", base));
+    }
+
+    #[test]
+    fn test_detect_paywall_from_json_ld() {
+        let scraper = RustScraper::new();
+        let base = Url::parse("https://news.example.com/story").unwrap();
+        let paywalled = r#"<html><head>
+            <link rel="amphtml" href="/amp/story">
+            <script type="application/ld+json">
+            {"@context": "https://schema.org", "@type": "NewsArticle", "headline": "Big story",
+             "isAccessibleForFree": false,
+             "hasPart": {"@type": "WebPageElement", "isAccessibleForFree": "False", "cssSelector": ".body"}}
+            </script></head><body><article><p>Only the first paragraph is visible.</p></article></body></html>"#;
+        let page = scraper.extract_page(base.as_str(), &base, paywalled.to_string(), 200, "text/html".into());
+        assert!(page.paywalled);
+        assert_eq!(page.amp_url.as_deref(), Some("https://news.example.com/amp/story"));
+
+        let free = r#"<html><head><script type="application/ld+json">
+            {"@type": "NewsArticle", "isAccessibleForFree": true}</script></head>
+            <body><article><p>Everyone can read this.</p></article></body></html>"#;
+        let page = scraper.extract_page(base.as_str(), &base, free.to_string(), 200, "text/html".into());
+        assert!(!page.paywalled);
+        assert_eq!(page.wall_type, None);
+    }
+
+    #[test]
+    fn test_wall_type_for_consent_and_login_walls() {
+        let scraper = RustScraper::new();
+        let base = Url::parse("https://news.example.com/story").unwrap();
+        let classify = |html: &str| scraper.extract_page(base.as_str(), &base, html.to_string(), 200, "text/html".into()).wall_type;
+
+        let consent = r#"<html><body><div id="onetrust-banner-sdk" class="otFlat">
+            <p>We value your privacy. We and our partners store and access information on your device.</p>
+            <button id="onetrust-accept-btn-handler">Accept all</button><button>Reject all</button>
+            </div></body></html>"#;
+        assert_eq!(classify(consent), Some(WallType::Consent));
+
+        let login = r#"<html><body><main><h1>Members area</h1>
+            <form action="/session" method="post"><label>Email <input type="email" name="email"></label>
+            <label>Password <input type="password" name="password"></label><button>Sign in</button></form>
+            </main></body></html>"#;
+        assert_eq!(classify(login), Some(WallType::Login));
+
+        // A full article keeps its cookie banner and header login form without being called a wall
+        let paragraph = "<p>The council voted on Tuesday to extend the harbour trail past the old ferry landing, \
+            a project residents have asked for since the footbridge closed.</p>";
+        let article = format!(
+            "<html><body><form><input type=\"password\"></form><article><h1>Trail extended</h1>{}</article>{}</body></html>",
+            paragraph.repeat(12),
+            consent
+        );
+        assert_eq!(classify(&article), None);
+    }
+
+    #[test]
+    fn test_as_document_keeps_heading_hierarchy() {
+        let html = r#"<html><body><header class="site-header">Site nav</header><article>
+            <h2>Installation</h2><p>Download the installer for your platform.</p>
+            <h3>From source</h3><p>Clone the repository and run make.</p>
+            <div class="share-buttons">Share this</div>
+            <h2>Usage</h2><p>Run the binary with --help.</p>
+            </article></body></html>"#;
+        let base = Url::parse("https://docs.example.com/guide").unwrap();
+        let page = RustScraper::new()
+            .with_document(true)
+            .extract_page(base.as_str(), &base, html.to_string(), 200, "text/html".into());
+        assert_eq!(
+            page.document.as_deref(),
+            Some("## Installation\n\nDownload the installer for your platform.\n\n### From source\n\nClone the repository and run make.\n\n## Usage\n\nRun the binary with --help.")
+        );
+
+        let plain = RustScraper::new().extract_page(base.as_str(), &base, html.to_string(), 200, "text/html".into());
+        assert!(plain.document.is_none());
+    }
+
+    #[test]
+    fn test_markdown_content_keeps_structure_and_links() {
+        let html = r#"<html><head><title>Setup</title></head><body><nav><a href="/">Home</a></nav><article>
+            <h2>Installing the toolchain</h2>
+            <p>Follow the <a href="../install/linux">Linux instructions</a> to get a working compiler on your machine today.</p>
+            <ul><li>Download the archive for your platform</li><li>Unpack it somewhere on your path</li></ul>
+            <pre><code class="language-sh">tar xf toolchain.tar.gz</code></pre>
+            <p>Once installed, the compiler and package manager are both available from any terminal window.</p>
+            </article></body></html>"#;
+        let base = Url::parse("https://docs.example.com/guide/setup").unwrap();
+        let page = RustScraper::new()
+            .with_markdown(true)
+            .extract_page(base.as_str(), &base, html.to_string(), 200, "text/html".into());
+        let markdown = page.markdown_content.expect("markdown requested");
+        assert!(markdown.contains("## Installing the toolchain"), "{}", markdown);
+        assert!(markdown.contains("[Linux instructions](https://docs.example.com/install/linux)"), "{}", markdown);
+        assert!(markdown.contains("- Download the archive") && markdown.contains("```\ntar xf toolchain.tar.gz\n```"), "{}", markdown);
+        assert!(!page.clean_content.contains("]("));
+
+        let plain = RustScraper::new().extract_page(base.as_str(), &base, html.to_string(), 200, "text/html".into());
+        assert!(plain.markdown_content.is_none());
+    }
+
+    #[test]
+    fn test_extract_theme_color() {
+        let scraper = RustScraper::new();
+        let doc = Html::parse_document(
+            r##"<head><meta name="theme-color" media="(prefers-color-scheme: dark)" content="#000">
+            <meta name="theme-color" content=" #1A73E8 "></head>"##,
+        );
+        assert_eq!(scraper.extract_theme_color(&doc).as_deref(), Some("#1a73e8"));
+
+        let doc = Html::parse_document(r#"<head><meta name="theme-color" content="url(evil)"></head>"#);
+        assert_eq!(scraper.extract_theme_color(&doc), None);
+        assert_eq!(normalize_css_color("rgb(26, 115, 232)").as_deref(), Some("rgb(26, 115, 232)"));
+    }
+
+    #[test]
+    fn test_viewport_mobile_friendly() {
+        let scraper = RustScraper::new();
+        let base = Url::parse("https://example.com/").unwrap();
+        let responsive = r#"<html><head><meta name="viewport" content="width=device-width, initial-scale=1"></head><body><p>Hi</p></body></html>"#;
+        let page = scraper.extract_page(base.as_str(), &base, responsive.to_string(), 200, "text/html".into());
+        assert_eq!(page.viewport.as_deref(), Some("width=device-width, initial-scale=1"));
+        assert_eq!(page.mobile_friendly, Some(true));
+
+        let fixed = r#"<html><head><meta name="viewport" content="width=1024"></head><body><p>Hi</p></body></html>"#;
+        let page = scraper.extract_page(base.as_str(), &base, fixed.to_string(), 200, "text/html".into());
+        assert_eq!(page.mobile_friendly, Some(false));
+
+        let page = scraper.extract_page(base.as_str(), &base, "<p>No head</p>".to_string(), 200, "text/html".into());
+        assert_eq!((page.viewport, page.mobile_friendly), (None, Some(false)));
+    }
+
+    #[test]
+    fn test_age_days_from_published_date() {
+        let today = Utc::now().date_naive();
+        let month_ago = (Utc::now() - chrono::Duration::days(30)).to_rfc3339();
+        let html = format!(r#"<html><head><meta property="article:published_time" content="{}"></head><body><p>Post</p></body></html>"#, month_ago);
+        let base = Url::parse("https://blog.example.com/posts/monthly-update").unwrap();
+        let page = RustScraper::new().extract_page(base.as_str(), &base, html, 200, "text/html".into());
+        assert!(matches!(page.age_days, Some(29..=31)), "age_days = {:?}", page.age_days);
+
+        // Future and unparseable dates are skipped in favour of the next signal, or give None
+        let future = (today + chrono::Duration::days(10)).to_string();
+        assert_eq!(age_days(&[Some(future.as_str()), Some("2020-01-01")], chrono::NaiveDate::from_ymd_opt(2020, 1, 31).unwrap()), Some(30));
+        assert_eq!(age_days(&[Some("last Tuesday"), None], today), None);
+        assert_eq!(age_days(&[Some(future.as_str())], today), None);
+    }
+
+    #[test]
+    fn test_date_from_url() {
+        let date = |u: &str| date_from_url_path(&Url::parse(u).unwrap());
+        assert_eq!(date("https://blog.example.com/2023/05/12/shipping-it/").as_deref(), Some("2023-05-12"));
+        assert_eq!(date("https://example.com/posts/2021-11-03-release-notes").as_deref(), Some("2021-11-03"));
+        assert_eq!(date("https://example.com/2023/02/30/not-a-day"), None);
+        assert_eq!(date("https://example.com/2999/01/01/future"), None);
+        assert_eq!(date("https://example.com/products/12345/"), None);
+
+        let base = Url::parse("https://blog.example.com/2023/05/12/shipping-it/").unwrap();
+        let page = RustScraper::new().extract_page(base.as_str(), &base, "<p>Post</p>".into(), 200, "text/html".into());
+        assert_eq!(page.published_at.as_deref(), Some("2023-05-12"));
+    }
+
+    #[test]
+    fn test_garbage_lines_match_whole_line_only() {
+        let text = "Share\nAccept\nAccept all\nReject all cookies\nAllow cookies\n\
+                    Please share your feedback with the team before Friday.\n» Read more →\n\
+                    Read our privacy policy to learn how the study data is stored.\nPrivacy Policy\nTrending now";
+        let scraper = RustScraper::new();
+        assert_eq!(
+            scraper.post_clean_text(text),
+            "Please share your feedback with the team before Friday. \
+             Read our privacy policy to learn how the study data is stored. Trending now"
+        );
+
+        let custom = scraper.with_garbage_patterns(&["trending( now)?".to_string(), "(unclosed".to_string()]);
+        assert!(!custom.post_clean_text(text).contains("Trending"));
+    }
+
+    #[test]
+    fn test_clean_text() {
+        let scraper = RustScraper::new();
+        let text = "  This   is    \n\n\n   some    text   \n\n  ";
+        let cleaned = scraper.clean_text(text);
+        assert_eq!(cleaned, "This is some text");
+    }
+    
+    #[test]
+    fn test_word_count() {
         let scraper = RustScraper::new();
         let text = "This is a test with five words";
     assert_eq!(scraper.count_words(text), 7);
     }
+
+    #[test]
+    fn test_word_and_char_counts_for_english_and_chinese() {
+        let english = "Rust is a systems programming language.";
+        assert_eq!(count_words(english), 6);
+        assert_eq!(count_chars(english), 34);
+
+        let chinese = "我们喜欢学习编程语言。";
+        assert_eq!(count_words(chinese), 10);
+        assert_eq!(count_chars(chinese), 11);
+
+        assert_eq!(count_words("Rust 编程语言"), 5);
+    }
+
+    #[tokio::test]
+    async fn test_file_scheme_only_when_allowed() {
+        let dir = std::env::temp_dir().join(format!("scrape-fixture-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("fixture.html");
+        std::fs::write(
+            &path,
+            "<html><head><title>Local fixture</title></head><body><article>\
+             <p>This fixture page is read straight from disk for offline ingestion tests.</p></article></body></html>",
+        )
+        .unwrap();
+        let url = Url::from_file_path(&path).unwrap().to_string();
+
+        let result = RustScraper::new().with_file_scheme(true).scrape_url(&url).await.unwrap();
+        assert_eq!(result.title, "Local fixture");
+        assert!(result.clean_content.contains("read straight from disk"), "{}", result.clean_content);
+        assert_eq!(result.status_code, 200);
+
+        let err = RustScraper::new().with_file_scheme(false).scrape_url(&url).await.unwrap_err();
+        assert_eq!(crate::error::error_code(&err), "invalid_url");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_metadata_from_ranged_partial_response() {
+        use axum::{http::{header, HeaderMap, StatusCode}, routing::get, Router};
+        use std::sync::Mutex;
+        let page = format!(
+            "<html><head><title>Ranged page</title><meta name=\"description\" content=\"Only the head was sent\"></head><body>{}</body></html>",
+            "<p>filler</p>".repeat(5000)
+        );
+        let seen_ranges = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&seen_ranges);
+        let app = Router::new().route("/page", get(move |headers: HeaderMap| {
+            let page = page.clone();
+            let recorded = Arc::clone(&recorded);
+            async move {
+                let range = headers.get(header::RANGE).and_then(|v| v.to_str().ok()).map(str::to_string);
+                recorded.lock().unwrap().push(range.clone());
+                match range.as_deref().and_then(|r| r.strip_prefix("bytes=0-")).and_then(|end| end.parse::<usize>().ok()) {
+                    Some(end) => (StatusCode::PARTIAL_CONTENT, page[..=end.min(page.len() - 1)].to_string()),
+                    None => (StatusCode::OK, page),
+                }
+            }
+        }));
+        let base = crate::test_support::serve(app).await;
+
+        let metadata = crate::test_support::local_scraper().with_metadata_range(1024).fetch_metadata(&format!("{}/page", base)).await.unwrap();
+        assert_eq!(metadata.title, "Ranged page");
+        assert_eq!(metadata.description.as_deref(), Some("Only the head was sent"));
+        assert_eq!(*seen_ranges.lock().unwrap(), vec![Some("bytes=0-1023".to_string())]);
+
+        // A range too small to hold the head falls back to a full GET
+        let metadata = crate::test_support::local_scraper().with_metadata_range(16).fetch_metadata(&format!("{}/page", base)).await.unwrap();
+        assert_eq!(metadata.title, "Ranged page");
+        assert_eq!(seen_ranges.lock().unwrap()[1..], [Some("bytes=0-15".to_string()), None]);
+    }
+
+    #[test]
+    fn test_meta_charset_overrides_wrong_header_charset() {
+        let html = "<html><head><meta charset=\"windows-1252\"><title>Café</title></head>\
+                    <body><p>Crème brûlée à la carte, déjà vu.</p></body></html>";
+        let (bytes, _, _) = encoding_rs::WINDOWS_1252.encode(html);
+
+        let decoded = decode_body(&bytes, "text/html; charset=utf-8");
+        assert!(decoded.contains("Crème brûlée à la carte, déjà vu."), "{}", decoded);
+        assert!(!decoded.contains('\u{FFFD}'));
+
+        // Correctly labelled UTF-8 is left alone
+        assert_eq!(decode_body(html.as_bytes(), "text/html; charset=UTF-8"), html);
+    }
 }
\ No newline at end of file
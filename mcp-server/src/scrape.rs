@@ -1,75 +1,637 @@
 use crate::types::*;
+use crate::error::ScrapeError;
+use crate::debug_sample::DebugSampler;
+use crate::pagination;
 use crate::AppState;
 use anyhow::{anyhow, Result};
-use backoff::future::retry;
+use crate::retry::{self, retry_capped};
 use backoff::ExponentialBackoffBuilder;
+use futures::StreamExt;
 use std::sync::Arc;
-use tracing::info;
+use tracing::{debug, info, warn};
 use select::predicate::Predicate;
-use crate::rust_scraper::RustScraper;
+use crate::cache_ttl::{self, CachedScrape};
+use crate::rust_scraper::{RustScraper, ScrapeOptions};
+
+#[derive(Debug, Default, Clone)]
+pub struct ScrapeParamOverrides {
+    pub preserve_whitespace: Option<bool>, // keep indentation/line structure in clean_content
+    pub as_document: Option<bool>,         // also render the main content as a markdown document
+    pub max_paragraphs: Option<usize>,     // keeps paragraph breaks; the cut is applied after caching
+    pub keep_paragraphs: Option<bool>,     // keep paragraph breaks without a limit (line-based diffs)
+    pub follow_next: Option<usize>,        // merge up to N chapters linked by rel=next (docs books); applied after caching
+    pub language_segments: Option<bool>,   // split clean_content by per-paragraph language; applied after caching
+    pub raw_urls: Option<bool>,            // keep original hrefs/srcs next to the resolved URLs
+    pub scraper_order: Option<ScraperOrder>, // native/fallback order; defaults to SCRAPER_ORDER
+    pub inline_state: Option<bool>,        // parse JSON state embedded in scripts into inline_state
+    pub blocks: Option<bool>,              // also return the readability output as typed blocks
+    pub vcard: Option<bool>,               // assemble contact details into a vCard
+    pub structured_data: Option<bool>,     // return the page's JSON-LD objects, capped
+    pub markdown: Option<bool>,            // also render the main content as markdown with links (markdown_content)
+    pub login: Option<FormLogin>,          // form login before fetching; disables caching (Debug redacts credentials)
+    pub options: ScrapeOptions,            // per-call timeout, redirect, body size and User-Agent for the native fetch
+}
+
+/// Default and maximum chapters merged when following `rel=next`
+pub const DEFAULT_BOOK_PAGES: usize = 10;
+pub const MAX_BOOK_PAGES: usize = 50;
+
+/// Default and maximum search results whose titles are resolved with `resolve_titles`
+pub const DEFAULT_RESOLVED_TITLES: usize = 5;
+pub const MAX_RESOLVED_TITLES: usize = 10;
+
+/// Default and maximum search results given an `excerpt`, and the excerpt length in characters
+pub const DEFAULT_EXCERPTS: usize = 3;
+pub const MAX_EXCERPTS: usize = 10;
+pub const EXCERPT_MAX_CHARS: usize = 300;
+
+impl ScrapeParamOverrides {
+    /// Apply per-request options to a scraper
+    fn configure(&self, scraper: RustScraper) -> RustScraper {
+        let scraper = scraper
+            .with_preserve_whitespace(self.preserve_whitespace.unwrap_or(false))
+            .with_document(self.as_document.unwrap_or(false))
+            .with_paragraphs(self.keeps_paragraphs())
+            .with_raw_urls(self.raw_urls.unwrap_or(false))
+            .with_inline_state(self.inline_state.unwrap_or(false))
+            .with_blocks(self.blocks.unwrap_or(false))
+            .with_vcard(self.vcard.unwrap_or(false))
+            .with_structured_data(self.structured_data.unwrap_or(false))
+            .with_markdown(self.markdown.unwrap_or(false));
+        match &self.login {
+            Some(login) => scraper.with_login(login.clone()),
+            None => scraper,
+        }
+    }
+
+    /// Whether the fetch is bounded by a per-call timeout, redirect or body limit; a cached page
+    /// fetched without them can't stand in for one that has to honour them
+    fn limits_fetch(&self) -> bool {
+        let options = &self.options;
+        options.timeout.is_some() || options.max_redirects.is_some() || options.max_body_bytes.is_some()
+    }
+
+    fn keeps_paragraphs(&self) -> bool {
+        self.max_paragraphs.is_some() || self.keep_paragraphs.unwrap_or(false) || self.language_segments.unwrap_or(false)
+    }
+}
+
+/// Cache key for a scrape; default options use the bare URL so the local index stays keyed by URL
+pub fn scrape_cache_key(url: &str, overrides: Option<&ScrapeParamOverrides>) -> String {
+    let Some(ov) = overrides else { return url.to_string() };
+    let mut key = url.to_string();
+    if ov.preserve_whitespace.unwrap_or(false) {
+        key.push_str("|ws=1");
+    }
+    if ov.as_document.unwrap_or(false) {
+        key.push_str("|doc=1");
+    }
+    if ov.keeps_paragraphs() {
+        // Paragraph-preserving extraction; the limit itself is applied after the cache
+        key.push_str("|para=1");
+    }
+    if ov.raw_urls.unwrap_or(false) {
+        key.push_str("|raw=1");
+    }
+    if ov.inline_state.unwrap_or(false) {
+        key.push_str("|state=1");
+    }
+    if ov.blocks.unwrap_or(false) {
+        key.push_str("|blocks=1");
+    }
+    if ov.vcard.unwrap_or(false) {
+        key.push_str("|vcard=1");
+    }
+    if ov.structured_data.unwrap_or(false) {
+        key.push_str("|sd=1");
+    }
+    if ov.markdown.unwrap_or(false) {
+        key.push_str("|md=1");
+    }
+    if let Some(agent) = &ov.options.user_agent {
+        // Sites may serve another page to another agent
+        key.push_str("|ua=");
+        key.push_str(agent);
+    }
+    match ov.scraper_order {
+        Some(ScraperOrder::FallbackFirst) => key.push_str("|order=fallback"),
+        Some(ScraperOrder::NativeOnly) => key.push_str("|order=native"),
+        _ => {}
+    }
+    key
+}
 
 pub async fn scrape_url(state: &Arc<AppState>, url: &str) -> Result<ScrapeResponse> {
+    scrape_url_with_params(state, url, None).await
+}
+
+pub async fn scrape_url_with_params(
+    state: &Arc<AppState>,
+    url: &str,
+    overrides: Option<ScrapeParamOverrides>,
+) -> Result<ScrapeResponse> {
+    let max_paragraphs = overrides.as_ref().and_then(|ov| ov.max_paragraphs);
+    let segment_languages = overrides.as_ref().and_then(|ov| ov.language_segments).unwrap_or(false);
+    let book_pages = overrides.as_ref().and_then(|ov| ov.follow_next).map(|n| n.min(MAX_BOOK_PAGES));
+    let chapter_overrides = overrides.clone().map(|ov| ScrapeParamOverrides { follow_next: None, ..ov });
+    let mut result = fetch_scrape(state, url, overrides).await?;
+    if let Some(n) = book_pages.filter(|n| *n > 1) {
+        merge_chapters(state, &mut result, n, chapter_overrides).await;
+    }
+    if let Some(n) = max_paragraphs {
+        truncate_paragraphs(&mut result, n);
+    }
+    if segment_languages {
+        result.language_segments = crate::rust_scraper::language_segments(&result.clean_content);
+    }
+    Ok(result)
+}
+
+/// Scrape a page fresh and diff its paragraph-preserving content against a previous snapshot.
+/// The returned `content` is the snapshot to pass as `previous_content` next time.
+pub async fn scrape_diff(state: &Arc<AppState>, url: &str, previous_content: &str) -> Result<ScrapeDiffResponse> {
+    let overrides = ScrapeParamOverrides { keep_paragraphs: Some(true), ..Default::default() };
+    // Monitoring needs the current page, not a cached copy
+    state.scrape_cache.invalidate(&scrape_cache_key(url, Some(&overrides))).await;
+    let current = scrape_url_with_params(state, url, Some(overrides)).await?;
+    Ok(diff_content(url, previous_content, &current.clean_content))
+}
+
+/// Line-based diff of two content snapshots; blank lines and trailing whitespace are not changes
+pub fn diff_content(url: &str, previous: &str, current: &str) -> ScrapeDiffResponse {
+    let previous = normalize_snapshot(previous);
+    let current = normalize_snapshot(current);
+    let diff = similar::TextDiff::from_lines(&previous, &current);
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    for change in diff.iter_all_changes() {
+        let line = change.value().trim_end_matches('\n').to_string();
+        match change.tag() {
+            similar::ChangeTag::Insert => added.push(line),
+            similar::ChangeTag::Delete => removed.push(line),
+            similar::ChangeTag::Equal => {}
+        }
+    }
+    ScrapeDiffResponse {
+        url: url.to_string(),
+        changed: !added.is_empty() || !removed.is_empty(),
+        diff: diff.unified_diff().context_radius(2).header("previous", "current").to_string(),
+        added,
+        removed,
+        content: current,
+    }
+}
+
+fn normalize_snapshot(text: &str) -> String {
+    text.lines()
+        .map(str::trim_end)
+        .filter(|l| !l.is_empty())
+        .map(|l| format!("{}\n", l))
+        .collect()
+}
+
+/// Link-preview metadata for a URL via the scraper's fast path, bounded by the outbound limit
+pub async fn fetch_metadata(state: &Arc<AppState>, url: &str) -> Result<PageMetadata> {
+    let _permit = state
+        .acquire_outbound(Some(url))
+        .await
+        .map_err(|retry_after| ScrapeError::Overloaded { retry_after })?;
+    RustScraper::new().with_allow_private_addresses(state.allow_private_addresses).fetch_metadata(url).await
+}
+
+/// Fetch metadata for many URLs concurrently; each URL succeeds or fails on its own
+pub async fn fetch_metadata_batch(state: &Arc<AppState>, urls: Vec<String>) -> Vec<BatchMetadataItem> {
+    let tasks = urls.into_iter().map(|url| {
+        let state = Arc::clone(state);
+        async move {
+            match fetch_metadata(&state, &url).await {
+                Ok(metadata) => BatchMetadataItem { url, metadata: Some(metadata), error: None },
+                Err(e) => BatchMetadataItem { url, metadata: None, error: Some(e.to_string()) },
+            }
+        }
+    });
+    futures::future::join_all(tasks).await
+}
+
+/// Scrape many URLs with at most `max_concurrency` in flight (each still bounded by the outbound and
+/// per-host limits). Results come back in `urls` order and each URL succeeds or fails on its own.
+pub async fn scrape_urls(state: &Arc<AppState>, urls: &[String], max_concurrency: usize) -> Vec<Result<ScrapeResponse>> {
+    let tasks = urls.iter().cloned().map(|url| {
+        let state = Arc::clone(state);
+        async move { scrape_url(&state, &url).await.and_then(reject_error_status) }
+    });
+    futures::stream::iter(tasks)
+        .buffered(max_concurrency.max(1))
+        .collect()
+        .await
+}
+
+/// Fill `resolved_title`/`favicon_url` on the first `top` results from a concurrent metadata fetch.
+/// A page that fails or has no title leaves its result as the engine returned it.
+pub async fn resolve_result_titles(state: &Arc<AppState>, results: &mut [SearchResult], top: usize) {
+    let top = top.min(MAX_RESOLVED_TITLES).min(results.len());
+    let tasks = results[..top].iter().map(|r| fetch_metadata(state, &r.url));
+    let fetched = futures::future::join_all(tasks).await;
+    for (result, metadata) in results.iter_mut().zip(fetched) {
+        match metadata {
+            Ok(metadata) => {
+                result.resolved_title = Some(metadata.title).filter(|t| !t.is_empty() && t != "No Title");
+                result.favicon_url = metadata.favicon;
+            }
+            Err(e) => debug!("Could not resolve title for {}: {}", result.url, e),
+        }
+    }
+}
+
+/// Attach to the first `top` results the passage of their page that best matches `query`. Pages are
+/// scraped concurrently through the cache, so a later scrape of the same result is free; a page that
+/// fails or has no matching passage keeps the engine snippet alone.
+pub async fn attach_excerpts(state: &Arc<AppState>, query: &str, results: &mut [SearchResult], top: usize) {
+    let top = top.min(MAX_EXCERPTS).min(results.len());
+    let tasks = results[..top].iter().map(|r| scrape_url(state, &r.url));
+    let fetched = futures::future::join_all(tasks).await;
+    for (result, page) in results.iter_mut().zip(fetched) {
+        match page {
+            Ok(page) => result.excerpt = crate::rerank::best_passage(query, &page.clean_content, EXCERPT_MAX_CHARS),
+            Err(e) => debug!("No excerpt for {}: {}", result.url, e),
+        }
+    }
+}
+
+/// Follow the `rel=next` chain from a scraped page (same origin only), concatenating up to `max_pages`
+/// chapters under `# <title>` headings. Each chapter goes through the normal extraction pipeline,
+/// so mdBook/GitBook bodies use the mdBook extractor; the chain stops at the first failure or loop.
+async fn merge_chapters(state: &Arc<AppState>, result: &mut ScrapeResponse, max_pages: usize, overrides: Option<ScrapeParamOverrides>) {
+    let Ok(start) = url::Url::parse(&result.url) else { return };
+    let chapter_heading = |r: &ScrapeResponse| r.readable_title.clone().unwrap_or_else(|| r.title.clone());
+    let mut sections = vec![format!("# {}\n\n{}", chapter_heading(result), result.clean_content)];
+    let mut visited = vec![result.url.clone()];
+    let mut word_count = result.word_count;
+    let mut next = crate::rust_scraper::next_page_url(&result.content, &start);
+    while let Some(url) = next.take() {
+        if visited.len() >= max_pages || visited.contains(&url) {
+            break;
+        }
+        if url::Url::parse(&url).map(|u| u.origin()) != Ok(start.origin()) {
+            debug!("Not following cross-origin rel=next {}", url);
+            break;
+        }
+        let chapter = match fetch_scrape(state, &url, overrides.clone()).await {
+            Ok(chapter) => chapter,
+            Err(e) => {
+                warn!("Stopping chapter chain at {}: {}", url, e);
+                break;
+            }
+        };
+        sections.push(format!("# {}\n\n{}", chapter_heading(&chapter), chapter.clean_content));
+        word_count += chapter.word_count;
+        next = url::Url::parse(&chapter.url).ok().and_then(|base| crate::rust_scraper::next_page_url(&chapter.content, &base));
+        visited.push(url);
+    }
+    if visited.len() > 1 {
+        result.clean_content = sections.join("\n\n");
+        result.word_count = word_count;
+        result.reading_time_minutes = Some(((word_count as f64 / 200.0).ceil() as u32).max(1));
+        result.chapter_urls = visited;
+    }
+}
+
+/// Keep the first `n` blank-line-separated paragraphs of `clean_content`
+pub fn truncate_paragraphs(response: &mut ScrapeResponse, n: usize) {
+    let paragraphs: Vec<&str> = response
+        .clean_content
+        .split("\n\n")
+        .filter(|p| !p.trim().is_empty())
+        .collect();
+    if paragraphs.len() > n {
+        response.clean_content = paragraphs[..n].join("\n\n");
+        response.content_truncated = true;
+    }
+}
+
+async fn fetch_scrape(
+    state: &Arc<AppState>,
+    url: &str,
+    overrides: Option<ScrapeParamOverrides>,
+) -> Result<ScrapeResponse> {
     info!("Scraping URL: {}", url);
+    let overrides = overrides.unwrap_or_default();
+    let cache_key = scrape_cache_key(url, Some(&overrides));
     
     // Validate URL
-    if !url.starts_with("http://") && !url.starts_with("https://") {
-        return Err(anyhow!("Invalid URL: must start with http:// or https://"));
+    let local_file = url.starts_with("file://") && crate::rust_scraper::file_scheme_enabled();
+    if !url.starts_with("http://") && !url.starts_with("https://") && !local_file {
+        return Err(ScrapeError::InvalidUrl("Invalid URL: must start with http:// or https://".into()).into());
     }
 
-    // Check cache
-    if let Some(cached) = state.scrape_cache.get(url).await {
-        if cached.word_count == 0 || cached.clean_content.trim().is_empty() {
+    // Check cache; pages fetched with a login session are private to the caller and never cached,
+    // and fetches with their own limits go to the network
+    let cacheable = state.cache_enabled && overrides.login.is_none() && !overrides.limits_fetch();
+    let cached = if cacheable { state.scrape_cache.get(&cache_key).await } else { None };
+    if let Some(cached) = cached {
+        if cached.response.word_count == 0 || cached.response.clean_content.trim().is_empty() {
             // Invalidate poor/empty cache entries and recompute
-            state.scrape_cache.invalidate(url).await;
+            state.scrape_cache.invalidate(&cache_key).await;
         } else {
-            return Ok(cached);
+            return Ok(cached.response);
         }
     }
 
     // Concurrency control
-    let _permit = state.outbound_limit.acquire().await.expect("semaphore closed");
+    let _permit = state
+        .acquire_outbound(Some(url))
+        .await
+        .map_err(|retry_after| ScrapeError::Overloaded { retry_after })?;
 
-    // Only use Rust-native scraper with retries
-    let rust_scraper = RustScraper::new();
+    let started = std::time::Instant::now();
+    let rust_scraper = overrides.configure(
+        RustScraper::new()
+            .with_extractors(Arc::clone(&state.extractors))
+            .with_allow_private_addresses(state.allow_private_addresses),
+    );
+    let order = overrides.scraper_order.unwrap_or_else(ScraperOrder::from_env);
+    // The fallback scraper and the web cache/render recoveries don't consult robots.txt, so check up
+    // front; the address check also fails fast before any of them fetch
+    if let Ok(parsed) = url::Url::parse(url) {
+        rust_scraper.check_address(&parsed).await?;
+    }
+    rust_scraper.check_robots(url).await?;
+    // Callers may tighten the body limit for one scrape but not raise it past SCRAPE_MAX_BODY_BYTES
+    let max_body_bytes = overrides.options.max_body_bytes.map_or(rust_scraper.max_body_bytes(), |n| n.min(rust_scraper.max_body_bytes()));
+    let fallback_result = if order == ScraperOrder::FallbackFirst {
+        match scrape_url_fallback(&rust_scraper, url, max_body_bytes).await {
+            Ok(r) if r.word_count > 0 && !r.clean_content.trim().is_empty() => Some(r),
+            Ok(_) => {
+                info!("Fallback scraper found no content for {}, trying the native scraper", url);
+                None
+            }
+            Err(e) => {
+                info!("Fallback scraper failed for {}: {}, trying the native scraper", url, e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let mut result = match fallback_result {
+        Some(result) => result,
+        None => {
+            let options = ScrapeOptions { max_body_bytes: Some(max_body_bytes), ..overrides.options.clone() };
+            scrape_native(state, &rust_scraper, url, &options).await?
+        }
+    };
+    if result.word_count == 0 || result.clean_content.trim().is_empty() {
+        if has_metadata(&result) {
+            // The fallback scraper won't find content either; keep the metadata for link previews
+            info!("No readable content for {}, returning metadata only", url);
+            result.clean_content.clear();
+            result.extraction_failed = true;
+        } else if order == ScraperOrder::NativeFirst {
+            info!("Rust-native scraper returned empty content, using fallback for {}", url);
+            result = scrape_url_fallback(&rust_scraper, url, max_body_bytes).await?;
+        } else {
+            info!("No content for {} and the fallback scraper is not run again ({:?})", url, order);
+        }
+    } else {
+        info!("Scrape succeeded for {}", url);
+    }
+    if result.paywalled && paywall_fallback_enabled() {
+        try_paywall_fallback(&rust_scraper, &mut result).await;
+    }
+    if let Some(rule) = url::Url::parse(&result.url).ok().and_then(|u| state.pagination_rules.rule_for(&u).cloned()) {
+        result.pages_merged = pagination::merge_pages(&rust_scraper, &rule, &mut result).await;
+    }
+    DebugSampler::from_env().maybe_dump(&result);
+    if let Some(log) = &state.extraction_log {
+        log.record(&result, started.elapsed());
+    }
+    // Adapt the entry's TTL to how often this page's content has changed across refetches
+    let previous = state.scrape_history.get(&cache_key).await;
+    let history = cache_ttl::next_history(previous.as_ref(), cache_ttl::content_hash(&result), &state.scrape_ttl);
+    debug!("Scrape cache TTL for {}: {:?} after {} changes", url, history.ttl, history.changes);
+    state.scrape_history.insert(cache_key.clone(), history.clone()).await;
+    if cacheable {
+        state.scrape_cache.insert(cache_key.clone(), CachedScrape { response: result.clone(), history }).await;
+    }
+    if cacheable && cache_key == url {
+        state.local_index.insert(&result);
+    }
+    Ok(result)
+}
+
+/// Fetch with the native scraper: retries on transient failures and empty bodies, then the
+/// optional web cache and render service recoveries
+async fn scrape_native(state: &Arc<AppState>, rust_scraper: &RustScraper, url: &str, options: &ScrapeOptions) -> Result<ScrapeResponse> {
     let url_owned = url.to_string();
-    let mut result = retry(
+    let min_body = min_body_bytes();
+    // Last empty-bodied response, returned as-is if every attempt comes back empty
+    let empty_response = std::sync::Mutex::new(None);
+    let direct = retry_capped(
         ExponentialBackoffBuilder::new()
             .with_initial_interval(std::time::Duration::from_millis(200))
             .with_max_interval(std::time::Duration::from_secs(2))
             .with_max_elapsed_time(Some(std::time::Duration::from_secs(6)))
             .build(),
+        retry::max_attempts(),
         || async {
-            match rust_scraper.scrape_url(&url_owned).await {
+            match rust_scraper.scrape_url_with_options(&url_owned, options).await {
+                // Some servers intermittently send 200 with an empty body; a retry usually gets the page
+                Ok(r) if (200..300).contains(&r.status_code) && r.content.trim().len() < min_body => {
+                    *empty_response.lock().unwrap() = Some(r);
+                    Err(backoff::Error::transient(anyhow!("empty response body")))
+                }
                 Ok(r) => Ok(r),
-                Err(e) => {
-                    // Treat network/temporary HTML parse errors as transient
-                    Err(backoff::Error::transient(anyhow!("{}", e)))
+                // Invalid input, a header block the client can't accept, a rejected login, robots.txt, a private address, an oversized body or a Crawl-delay queue won't change on retry
+                Err(e) if e.downcast_ref::<ScrapeError>().is_some_and(|se| matches!(se, ScrapeError::InvalidUrl(_) | ScrapeError::HeadersTooLarge { .. } | ScrapeError::LoginFailed { .. } | ScrapeError::RobotsDisallowed { .. } | ScrapeError::PrivateAddress { .. } | ScrapeError::BodyTooLarge { .. } | ScrapeError::RateLimited { .. })) => {
+                    Err(backoff::Error::permanent(e))
                 }
+                // Treat network/temporary HTML parse errors as transient
+                Err(e) => Err(backoff::Error::transient(e)),
             }
         },
-    ).await?;
-    if result.word_count == 0 || result.clean_content.trim().is_empty() {
-        info!("Rust-native scraper returned empty content, using fallback for {}", url);
-        result = scrape_url_fallback(state, &url_owned).await?;
-    } else {
-        info!("Rust-native scraper succeeded for {}", url);
+    ).await;
+    let direct = direct.or_else(|e| empty_response.into_inner().unwrap().ok_or(e));
+    let mut result = recover_from_web_cache(rust_scraper, url, direct, cache_fallback_template().as_deref()).await?;
+    if let Some(render) = RenderService::from_env() {
+        if result.word_count < render.min_words {
+            try_render_fallback(rust_scraper, &state.http_client, &render, &mut result).await;
+        }
     }
-    state.scrape_cache.insert(url.to_string(), result.clone()).await;
     Ok(result)
 }
 
+/// 2xx bodies shorter than this many bytes (after trimming) are retried as transient glitches,
+/// bounded by the retry attempt cap (`SCRAPE_MIN_BODY_BYTES`, default 32; 0 disables)
+fn min_body_bytes() -> usize {
+    std::env::var("SCRAPE_MIN_BODY_BYTES")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(32)
+}
+
+/// Web cache URL template (`{url}` is replaced) when `SCRAPER_USE_CACHE_FALLBACK=1`;
+/// `SCRAPER_CACHE_FALLBACK_URL` overrides the Google cache default
+fn cache_fallback_template() -> Option<String> {
+    if !matches!(std::env::var("SCRAPER_USE_CACHE_FALLBACK").as_deref(), Ok("1") | Ok("true")) {
+        return None;
+    }
+    Some(
+        std::env::var("SCRAPER_CACHE_FALLBACK_URL")
+            .unwrap_or_else(|_| "https://webcache.googleusercontent.com/search?q=cache:{url}".to_string()),
+    )
+}
+
+/// Turn a final blocked/not-found status into a typed error, for callers that report failures by kind
+pub fn reject_error_status(result: ScrapeResponse) -> Result<ScrapeResponse> {
+    match ScrapeError::from_status(result.status_code) {
+        Some(e) => Err(e.into()),
+        None => Ok(result),
+    }
+}
+
+/// A direct fetch that errored (timeouts, resets) or was refused by the site
+fn is_blocked(direct: &Result<ScrapeResponse>) -> bool {
+    match direct {
+        Err(_) => true,
+        Ok(r) => matches!(r.status_code, 401 | 403 | 429 | 451 | 503),
+    }
+}
+
+/// As a last resort for blocked/failed fetches, scrape the page's web cache copy.
+/// Returns the direct result unchanged when no template is configured or the cache has nothing better.
+async fn recover_from_web_cache(
+    scraper: &RustScraper,
+    url: &str,
+    direct: Result<ScrapeResponse>,
+    template: Option<&str>,
+) -> Result<ScrapeResponse> {
+    let Some(template) = template else { return direct };
+    if !is_blocked(&direct) {
+        return direct;
+    }
+    let encoded: String = url::form_urlencoded::byte_serialize(url.as_bytes()).collect();
+    let cache_url = template.replace("{url}", &encoded);
+    info!("Direct scrape of {} failed or was blocked, trying web cache", url);
+    match scraper.scrape_url(&cache_url).await {
+        Ok(mut cached) if cached.status_code < 400 && cached.word_count > 0 => {
+            cached.url = url.to_string();
+            cached.from_cache_fallback = true;
+            Ok(cached)
+        }
+        Ok(cached) => {
+            debug!("Web cache copy of {} unusable (status {})", url, cached.status_code);
+            direct
+        }
+        Err(e) => {
+            debug!("Web cache fetch for {} failed: {}", url, e);
+            direct
+        }
+    }
+}
+
+/// External headless-render service for JS-heavy pages (`RENDER_SERVICE_URL`), e.g. a Splash
+/// `render.html` or Browserless `/content` endpoint that accepts `{"url": ...}` and returns HTML
+pub struct RenderService {
+    pub endpoint: String,
+    /// Native extractions with fewer words than this are re-tried via the service (`RENDER_MIN_WORDS`, default 50)
+    pub min_words: usize,
+}
+
+impl RenderService {
+    fn from_env() -> Option<Self> {
+        let endpoint = std::env::var("RENDER_SERVICE_URL").ok().filter(|v| !v.trim().is_empty())?;
+        let min_words = std::env::var("RENDER_MIN_WORDS")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(50);
+        Some(Self { endpoint, min_words })
+    }
+}
+
+/// Re-extract a thin page from rendered HTML, keeping the result only if it has more content
+async fn try_render_fallback(scraper: &RustScraper, client: &reqwest::Client, render: &RenderService, result: &mut ScrapeResponse) {
+    let rendered_html = async {
+        let response = client
+            .post(&render.endpoint)
+            .json(&serde_json::json!({ "url": result.url }))
+            .send()
+            .await?
+            .error_for_status()?;
+        response.text().await
+    };
+    let html = match rendered_html.await {
+        Ok(html) => html,
+        Err(e) => {
+            warn!("Render service failed for {}: {}", result.url, e);
+            return;
+        }
+    };
+    let Ok(parsed_url) = url::Url::parse(&result.url) else { return };
+    match scraper.extract_html(&result.url, parsed_url, html, result.status_code, "text/html".to_string()).await {
+        Ok(mut rendered) if rendered.word_count > result.word_count => {
+            info!("Render service recovered {} words for {}", rendered.word_count, result.url);
+            rendered.rendered = true;
+            *result = rendered;
+        }
+        Ok(_) => debug!("Rendered HTML for {} had no more content", result.url),
+        Err(e) => warn!("Extraction of rendered HTML failed for {}: {}", result.url, e),
+    }
+}
+
+/// Whether paywalled pages should be retried via their AMP/canonical variants (`SCRAPER_PAYWALL_FALLBACK=1`)
+fn paywall_fallback_enabled() -> bool {
+    matches!(std::env::var("SCRAPER_PAYWALL_FALLBACK").as_deref(), Ok("1") | Ok("true"))
+}
+
+/// Try the AMP and canonical variants of a paywalled page and keep the fuller text if one is free.
+/// Metadata and the `paywalled` flag of the original page are preserved.
+async fn try_paywall_fallback(scraper: &RustScraper, result: &mut ScrapeResponse) {
+    let candidates: Vec<String> = [result.amp_url.clone(), result.canonical_url.clone()]
+        .into_iter()
+        .flatten()
+        .filter(|u| u != &result.url)
+        .collect();
+    for candidate in candidates {
+        match scraper.scrape_url(&candidate).await {
+            Ok(alt) if !alt.paywalled && alt.word_count > result.word_count => {
+                info!("Paywall fallback via {} recovered {} words", candidate, alt.word_count);
+                result.clean_content = alt.clean_content;
+                result.word_count = alt.word_count;
+                result.reading_time_minutes = alt.reading_time_minutes;
+                return;
+            }
+            Ok(_) => debug!("Paywall fallback via {} did not improve content", candidate),
+            Err(e) => debug!("Paywall fallback via {} failed: {}", candidate, e),
+        }
+    }
+}
+
 // Fallback scraper using direct HTTP request (legacy simple mode) -- optional; keeping for troubleshooting
-pub async fn scrape_url_fallback(state: &Arc<AppState>, url: &str) -> Result<ScrapeResponse> {
+/// Whether a scrape found any page metadata worth returning without content
+fn has_metadata(result: &ScrapeResponse) -> bool {
+    let title = result.title.trim();
+    (!title.is_empty() && title != "No Title")
+        || !result.meta_description.trim().is_empty()
+        || result.og_title.is_some()
+        || result.og_description.is_some()
+}
+
+/// Simpler extraction of `url`, reading at most `max_body_bytes` of its body
+pub async fn scrape_url_fallback(scraper: &RustScraper, url: &str, max_body_bytes: usize) -> Result<ScrapeResponse> {
     info!("Using fallback scraper for: {}", url);
     
-    // Make direct HTTP request
-    let response = state
-        .http_client
+    // Make direct HTTP request through the scraper's client, so resolved names and redirects are
+    // held to the same SSRF guard as the native fetch
+    let parsed = url::Url::parse(url).map_err(|e| ScrapeError::InvalidUrl(format!("Invalid URL '{}': {}", url, e)))?;
+    scraper.check_address(&parsed).await?;
+    scraper.wait_crawl_delay(&parsed).await?;
+    let response = scraper
+        .client()
         .get(url)
         .header("User-Agent", "Mozilla/5.0 (compatible; MCP-Server/1.0)")
         .send()
         .await
-        .map_err(|e| anyhow!("Failed to fetch URL: {}", e))?;
+        .map_err(ScrapeError::from_fetch)?;
     
     let status_code = response.status().as_u16();
     let content_type = response
@@ -79,10 +641,8 @@ pub async fn scrape_url_fallback(state: &Arc<AppState>, url: &str) -> Result<Scr
         .unwrap_or("text/html")
         .to_string();
     
-    let html = response
-        .text()
-        .await
-        .map_err(|e| anyhow!("Failed to read response body: {}", e))?;
+    let body = crate::rust_scraper::read_body_limited(response, max_body_bytes).await?;
+    let html = crate::rust_scraper::decode_body(&body, &content_type);
     
     let document = select::document::Document::from(html.as_str());
     
@@ -113,7 +673,8 @@ pub async fn scrape_url_fallback(state: &Arc<AppState>, url: &str) -> Result<Scr
         .unwrap_or_else(|| html.clone());
     
     let clean_content = html2text::from_read(body_html.as_bytes(), 80);
-    let word_count = clean_content.split_whitespace().count();
+    let word_count = crate::rust_scraper::count_words(&clean_content);
+    let char_count = crate::rust_scraper::count_chars(&clean_content);
     
     let headings: Vec<Heading> = document
         .find(select::predicate::Name("h1")
@@ -134,6 +695,7 @@ pub async fn scrape_url_fallback(state: &Arc<AppState>, url: &str) -> Result<Scr
             n.attr("href").map(|href| Link {
                 url: href.to_string(),
                 text: n.text(),
+                raw_href: None,
             })
         })
         .collect();
@@ -145,6 +707,7 @@ pub async fn scrape_url_fallback(state: &Arc<AppState>, url: &str) -> Result<Scr
                 src: src.to_string(),
                 alt: n.attr("alt").unwrap_or("").to_string(),
                 title: n.attr("title").unwrap_or("").to_string(),
+                raw_src: None,
             })
         })
         .collect();
@@ -156,6 +719,7 @@ pub async fn scrape_url_fallback(state: &Arc<AppState>, url: &str) -> Result<Scr
         clean_content,
         meta_description,
         meta_keywords,
+        headings_total: headings.len(),
         headings,
         links,
         images,
@@ -163,15 +727,10 @@ pub async fn scrape_url_fallback(state: &Arc<AppState>, url: &str) -> Result<Scr
         status_code,
         content_type,
         word_count,
-    language: "unknown".to_string(),
-    canonical_url: None,
-    site_name: None,
-    author: None,
-    published_at: None,
-    og_title: None,
-    og_description: None,
-    og_image: None,
-    reading_time_minutes: None,
+        char_count,
+        language: "unknown".to_string(),
+        extraction: Some(ExtractionReport { strategy: "fallback_scraper", candidate_words: Vec::new() }),
+        ..Default::default()
     };
     
     info!("Fallback scraper extracted {} words", result.word_count);
@@ -185,12 +744,7 @@ mod tests {
     
     #[tokio::test]
     async fn test_scrape_url_fallback() {
-        let state = Arc::new(AppState::new(
-            "http://localhost:8888".to_string(),
-            reqwest::Client::new(),
-        ));
-        
-        let result = scrape_url_fallback(&state, "https://httpbin.org/html").await;
+        let result = scrape_url_fallback(&RustScraper::new(), "https://httpbin.org/html", RustScraper::new().max_body_bytes()).await;
         
         match result {
             Ok(content) => {
@@ -203,4 +757,475 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_max_paragraphs_returns_lead_paragraphs() {
+        use axum::{response::Html, routing::get, Router};
+        let paragraph = |n: usize| format!(
+            "<p>Paragraph {} explains one part of the topic in enough detail that it reads like real article text, \
+             with several clauses and a complete sentence structure.</p>", n
+        );
+        let page = format!(
+            "<html><head><title>Article</title></head><body><article><h1>Article</h1>{}</article></body></html>",
+            (1..=5).map(paragraph).collect::<String>()
+        );
+        let app = Router::new().route("/article", get(move || async move { Html(page) }));
+        let base = crate::test_support::serve(app).await;
+        let state = Arc::new(crate::test_support::local_state("http://127.0.0.1:9"));
+
+        let overrides = ScrapeParamOverrides { max_paragraphs: Some(2), ..Default::default() };
+        let result = scrape_url_with_params(&state, &format!("{}/article", base), Some(overrides)).await.unwrap();
+        let paragraphs: Vec<&str> = result.clean_content.split("\n\n").collect();
+        assert_eq!(paragraphs.len(), 2, "{:?}", result.clean_content);
+        assert!(paragraphs[1].starts_with("Paragraph 1 "), "{:?}", paragraphs);
+        assert!(result.content_truncated);
+    }
+
+    #[tokio::test]
+    async fn test_blocked_scrape_falls_back_to_web_cache() {
+        use axum::{extract::Query, http::StatusCode, response::Html, routing::get, Router};
+        use std::collections::HashMap;
+        let app = Router::new()
+            .route("/blocked", get(|| async { (StatusCode::FORBIDDEN, Html("<h1>Access denied</h1>")) }))
+            .route("/cache", get(|Query(q): Query<HashMap<String, String>>| async move {
+                Html(format!("<html><head><title>Cached copy</title></head><body><p>Archived text for {}</p></body></html>", q["q"]))
+            }));
+        let base = crate::test_support::serve(app).await;
+        let blocked_url = format!("{}/blocked", base);
+        let template = format!("{}/cache?q=cache:{{url}}", base);
+        let scraper = crate::test_support::local_scraper();
+
+        let direct = scraper.scrape_url(&blocked_url).await;
+        let recovered = recover_from_web_cache(&scraper, &blocked_url, direct, Some(&template)).await.unwrap();
+        assert!(recovered.from_cache_fallback);
+        assert_eq!(recovered.url, blocked_url);
+        assert_eq!(recovered.title, "Cached copy");
+        assert!(recovered.clean_content.contains(&format!("cache:{}", blocked_url)));
+
+        // Disabled: the blocked direct response is returned as-is
+        let direct = scraper.scrape_url(&blocked_url).await;
+        let unchanged = recover_from_web_cache(&scraper, &blocked_url, direct, None).await.unwrap();
+        assert_eq!(unchanged.status_code, 403);
+        assert!(!unchanged.from_cache_fallback);
+    }
+
+    #[tokio::test]
+    async fn test_thin_page_uses_render_service() {
+        use axum::{response::Html, routing::{get, post}, Json, Router};
+        let app = Router::new()
+            .route("/spa", get(|| async {
+                Html(r#"<html><head><title>App</title></head><body><div id="root"></div><script src="/app.js"></script></body></html>"#)
+            }))
+            .route("/render", post(|Json(body): Json<serde_json::Value>| async move {
+                Html(format!(
+                    "<html><head><title>Rendered app</title></head><body><article><h1>Dashboard</h1>\
+                     <p>Rendered content for {} with plenty of words produced by client side scripts.</p></article></body></html>",
+                    body["url"].as_str().unwrap_or_default()
+                ))
+            }));
+        let base = crate::test_support::serve(app).await;
+        let scraper = crate::test_support::local_scraper();
+        let render = RenderService { endpoint: format!("{}/render", base), min_words: 50 };
+
+        let mut result = scraper.scrape_url(&format!("{}/spa", base)).await.unwrap();
+        assert!(result.word_count < render.min_words);
+        try_render_fallback(&scraper, &reqwest::Client::new(), &render, &mut result).await;
+        assert!(result.rendered);
+        assert_eq!(result.title, "Rendered app");
+        assert!(result.clean_content.contains(&format!("Rendered content for {}/spa", base)));
+    }
+
+    #[tokio::test]
+    async fn test_scrape_diff_reports_changed_lines() {
+        use axum::{response::Html, routing::get, Router};
+        let app = Router::new().route("/page", get(|| async {
+            Html("<html><head><title>Status</title></head><body><article>\
+                  <p>All systems are operating normally across every region we monitor today.</p>\
+                  <p>The next maintenance window is scheduled for Saturday at noon UTC time.</p>\
+                  </article></body></html>")
+        }));
+        let base = crate::test_support::serve(app).await;
+        let state = Arc::new(crate::test_support::local_state("http://127.0.0.1:9"));
+        let url = format!("{}/page", base);
+
+        let previous = "All systems are operating normally across every region we monitor today.\n\n\
+                        Maintenance is not currently planned.";
+        let diff = scrape_diff(&state, &url, previous).await.unwrap();
+        assert!(diff.changed);
+        assert_eq!(diff.removed, vec!["Maintenance is not currently planned."]);
+        assert_eq!(diff.added, vec!["The next maintenance window is scheduled for Saturday at noon UTC time."]);
+        assert!(diff.diff.contains("+The next maintenance window"), "{}", diff.diff);
+
+        let unchanged = scrape_diff(&state, &url, &diff.content).await.unwrap();
+        assert!(!unchanged.changed);
+        assert!(unchanged.added.is_empty() && unchanged.removed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_follow_next_merges_book_chapters() {
+        use axum::{response::Html, routing::get, Router};
+        let chapter = |title: &'static str, body: &'static str, next: Option<&'static str>| {
+            let nav = next.map(|href| format!("<a rel=\"next\" href=\"{}\">Next</a>", href)).unwrap_or_default();
+            format!(
+                "<html><head><title>{} - The Example Book</title></head><body><nav>Contents</nav>\
+                 <div id=\"content\"><main><h1>{}</h1><p>{}</p></main></div>{}</body></html>",
+                title, title, body.repeat(12), nav
+            )
+        };
+        let intro = chapter("Introduction", "This book explains the example system step by step. ", Some("/ch2.html"));
+        let ch2 = chapter("Getting Started", "Install the tools and create your first project. ", Some("/ch1.html"));
+        let app = Router::new()
+            .route("/ch1.html", get(move || async move { Html(intro) }))
+            .route("/ch2.html", get(move || async move { Html(ch2) }));
+        let base = crate::test_support::serve(app).await;
+        let state = Arc::new(crate::test_support::local_state("http://127.0.0.1:9"));
+
+        let overrides = ScrapeParamOverrides { follow_next: Some(5), ..Default::default() };
+        let book = scrape_url_with_params(&state, &format!("{}/ch1.html", base), Some(overrides)).await.unwrap();
+        assert_eq!(book.chapter_urls, vec![format!("{}/ch1.html", base), format!("{}/ch2.html", base)]);
+        let intro_at = book.clean_content.find("# Introduction").expect("first chapter heading");
+        let ch2_at = book.clean_content.find("# Getting Started").expect("second chapter heading");
+        assert!(intro_at < ch2_at);
+        assert!(book.clean_content.contains("create your first project"));
+
+        // Without the option only the first chapter is returned
+        let single = scrape_url(&state, &format!("{}/ch1.html", base)).await.unwrap();
+        assert!(single.chapter_urls.is_empty());
+        assert!(!single.clean_content.contains("create your first project"));
+    }
+
+    #[tokio::test]
+    async fn test_language_segments_split_mixed_page() {
+        use axum::{response::Html, routing::get, Router};
+        let page = "<html><head><title>Guide / Guía</title></head><body><article>\
+            <p>This guide explains how to install the command line tools on your computer and how to keep them updated.</p>\
+            <p>Run the installer from a terminal and follow the instructions that appear on the screen until it finishes.</p>\
+            <p>Esta guía explica cómo instalar las herramientas de línea de comandos en su ordenador y cómo mantenerlas actualizadas.</p>\
+            <p>Ejecute el instalador desde una terminal y siga las instrucciones que aparecen en la pantalla hasta que termine.</p>\
+            </article></body></html>";
+        let app = Router::new().route("/guide", get(move || async move { Html(page) }));
+        let base = crate::test_support::serve(app).await;
+        let state = Arc::new(crate::test_support::local_state("http://127.0.0.1:9"));
+
+        let overrides = ScrapeParamOverrides { language_segments: Some(true), ..Default::default() };
+        let result = scrape_url_with_params(&state, &format!("{}/guide", base), Some(overrides)).await.unwrap();
+        let languages: Vec<&str> = result.language_segments.iter().map(|(lang, _)| lang.as_str()).collect();
+        assert_eq!(languages, vec!["en", "es"]);
+        assert!(result.language_segments[0].1.contains("Run the installer"));
+        assert!(result.language_segments[1].1.starts_with("Esta guía"));
+        assert!(result.language_segments[1].1.contains("Ejecute el instalador"));
+
+        let plain = scrape_url(&state, &format!("{}/guide", base)).await.unwrap();
+        assert!(plain.language_segments.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_disabled_cache_refetches_scrape() {
+        use axum::{response::Html, routing::get, Router};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        let app = Router::new().route("/page", get(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async { Html("<html><head><title>Fresh</title></head><body><article><p>Always fetched from the origin server.</p></article></body></html>") }
+        }));
+        let base = crate::test_support::serve(app).await;
+        let url = format!("{}/page", base);
+
+        let mut state = crate::test_support::local_state("http://127.0.0.1:9");
+        state.cache_enabled = false;
+        let state = Arc::new(state);
+        scrape_url(&state, &url).await.unwrap();
+        scrape_url(&state, &url).await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        // With the cache on, the second scrape is served from it
+        let cached = Arc::new(crate::test_support::local_state("http://127.0.0.1:9"));
+        scrape_url(&cached, &url).await.unwrap();
+        scrape_url(&cached, &url).await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+        // ...unless the call sets its own fetch limits, which a cached page never had to honour
+        let limited = ScrapeParamOverrides { options: ScrapeOptions { max_body_bytes: Some(64), ..Default::default() }, ..Default::default() };
+        let err = scrape_url_with_params(&cached, &url, Some(limited)).await.unwrap_err();
+        assert_eq!(crate::error::error_code(&err), "body_too_large");
+        assert_eq!(hits.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_js_shell_returns_metadata_only() {
+        use axum::{response::Html, routing::get, Router};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        let app = Router::new().route("/app", get(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async {
+                Html("<html><head><title>Team Dashboard</title>\
+                      <meta name=\"description\" content=\"Track your team's projects in one place.\">\
+                      <meta property=\"og:title\" content=\"Team Dashboard\"></head>\
+                      <body><div id=\"root\"></div><script src=\"/static/app.js\"></script></body></html>")
+            }
+        }));
+        let base = crate::test_support::serve(app).await;
+        let state = Arc::new(crate::test_support::local_state("http://127.0.0.1:9"));
+
+        let result = scrape_url(&state, &format!("{}/app", base)).await.unwrap();
+        assert!(result.extraction_failed);
+        assert!(result.clean_content.is_empty());
+        assert_eq!(result.title, "Team Dashboard");
+        assert_eq!(result.meta_description, "Track your team's projects in one place.");
+        // Served from the first fetch, without a second request by the fallback scraper
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_extraction_log_line_per_scrape() {
+        use axum::{response::Html, routing::get, Router};
+        let article = "<html><head><title>Logged</title></head><body><article><p>Every scrape appends one line of extraction metrics to the log file.</p><p>Maintainers replay the log to see which pass won on real pages.</p></article></body></html>";
+        let app = Router::new()
+            .route("/one", get(move || async move { Html(article) }))
+            .route("/two", get(move || async move { Html(article) }));
+        let base = crate::test_support::serve(app).await;
+        let path = std::env::temp_dir().join(format!("extraction-log-{}.jsonl", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let mut state = crate::test_support::local_state("http://127.0.0.1:9");
+        state.extraction_log = Some(crate::extraction_log::ExtractionLog::open(path.clone()));
+        let state = Arc::new(state);
+
+        for page in ["one", "two"] {
+            scrape_url(&state, &format!("{}/{}", base, page)).await.unwrap();
+        }
+        // Served from cache: no extraction, no line
+        scrape_url(&state, &format!("{}/one", base)).await.unwrap();
+
+        let mut lines = Vec::new();
+        for _ in 0..100 {
+            lines = std::fs::read_to_string(&path).unwrap_or_default().lines().map(str::to_string).collect();
+            if lines.len() >= 2 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert_eq!(lines.len(), 2, "{:?}", lines);
+        let record: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(record["url"], format!("{}/one", base));
+        assert_eq!(record["strategy"], "readability");
+        assert!(record["candidate_words"]["heuristic"].is_u64());
+        assert!(record["word_count"].as_u64().unwrap() > 5);
+        assert!(record["elapsed_ms"].is_u64());
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_scraper_order() {
+        use axum::{response::Html, routing::get, Router};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let hits = Arc::new(AtomicUsize::new(0));
+        let (empty_hits, article_hits) = (Arc::clone(&hits), Arc::clone(&hits));
+        let app = Router::new()
+            .route("/empty", get(move || {
+                empty_hits.fetch_add(1, Ordering::SeqCst);
+                async { Html("<html><body><div id=\"app\"></div><div class=\"spacer\"></div></body></html>") }
+            }))
+            .route("/article", get(move || {
+                article_hits.fetch_add(1, Ordering::SeqCst);
+                async { Html("<html><body><p>The simple fallback reads this paragraph without any help from readability at all.</p></body></html>") }
+            }));
+        let base = crate::test_support::serve(app).await;
+        let state = Arc::new(crate::test_support::local_state("http://127.0.0.1:9"));
+        let with_order = |order| Some(ScrapeParamOverrides { scraper_order: Some(order), ..Default::default() });
+
+        // Native-first falls back on empty content: two fetches
+        let result = scrape_url_with_params(&state, &format!("{}/empty", base), with_order(ScraperOrder::NativeFirst)).await.unwrap();
+        assert_eq!(result.word_count, 0);
+        assert_eq!(hits.swap(0, Ordering::SeqCst), 2);
+
+        // Native-only returns the empty extraction without running the fallback
+        let result = scrape_url_with_params(&state, &format!("{}/empty", base), with_order(ScraperOrder::NativeOnly)).await.unwrap();
+        assert_eq!(result.word_count, 0);
+        assert_ne!(result.extraction.map(|e| e.strategy), Some("fallback_scraper"));
+        assert_eq!(hits.swap(0, Ordering::SeqCst), 1);
+
+        // Fallback-first keeps the fallback's text and never reaches the native scraper
+        let result = scrape_url_with_params(&state, &format!("{}/article", base), with_order(ScraperOrder::FallbackFirst)).await.unwrap();
+        assert!(result.clean_content.contains("simple fallback"));
+        assert_eq!(result.extraction.map(|e| e.strategy), Some("fallback_scraper"));
+        assert_eq!(hits.swap(0, Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fallback_first_respects_body_limit() {
+        use axum::{body::{Body, Bytes}, routing::get, Router};
+        // 64 MiB of paragraphs, streamed without a Content-Length
+        let app = Router::new().route("/huge", get(|| async {
+            let chunk = Bytes::from("<p>word word word word word word word</p>".repeat(1_600));
+            Body::from_stream(futures::stream::repeat_with(move || Ok::<_, std::io::Error>(chunk.clone())).take(1_000))
+        }));
+        let url = format!("{}/huge", crate::test_support::serve(app).await);
+        let state = Arc::new(crate::test_support::local_state("http://127.0.0.1:9"));
+
+        let err = scrape_url_fallback(&crate::test_support::local_scraper(), &url, 4096).await.unwrap_err();
+        assert_eq!(crate::error::error_code(&err), "body_too_large");
+
+        let overrides = ScrapeParamOverrides {
+            scraper_order: Some(ScraperOrder::FallbackFirst),
+            options: ScrapeOptions { max_body_bytes: Some(4096), ..Default::default() },
+            ..Default::default()
+        };
+        let err = scrape_url_with_params(&state, &url, Some(overrides)).await.unwrap_err();
+        assert_eq!(crate::error::error_code(&err), "body_too_large");
+    }
+
+    #[tokio::test]
+    async fn test_form_login_session_cookie() {
+        use axum::{http::{header, HeaderMap, StatusCode}, response::{Html, IntoResponse}, routing::{get, post}, Form, Router};
+        use std::collections::HashMap;
+        let app = Router::new()
+            .route("/login", post(|Form(form): Form<HashMap<String, String>>| async move {
+                let valid = form.get("user").map(String::as_str) == Some("alice")
+                    && form.get("pass").map(String::as_str) == Some("s3cret")
+                    && form.get("remember").map(String::as_str) == Some("1");
+                if valid {
+                    ([(header::SET_COOKIE, "session=abc123; Path=/; HttpOnly")], "welcome").into_response()
+                } else {
+                    (StatusCode::UNAUTHORIZED, "bad credentials").into_response()
+                }
+            }))
+            .route("/members/guide", get(|headers: HeaderMap| async move {
+                let cookie = headers.get(header::COOKIE).and_then(|v| v.to_str().ok()).unwrap_or("");
+                if cookie.contains("session=abc123") {
+                    Html("<html><head><title>Members guide</title></head><body><article><p>Only logged-in members can read this guide to the internal tooling.</p><p>It covers setup, releases and on-call.</p></article></body></html>").into_response()
+                } else {
+                    (StatusCode::FORBIDDEN, Html("<html><body>Please log in</body></html>")).into_response()
+                }
+            }));
+        let base = crate::test_support::serve(app).await;
+        let state = Arc::new(crate::test_support::local_state("http://127.0.0.1:9"));
+        let url = format!("{}/members/guide", base);
+        let login = |password: &str| FormLogin {
+            login_url: format!("{}/login", base),
+            username_field: "user".to_string(),
+            password_field: "pass".to_string(),
+            username: "alice".to_string(),
+            password: password.to_string(),
+            extra_fields: [("remember".to_string(), "1".to_string())].into(),
+        };
+        let with_login = |password: &str| ScrapeParamOverrides { login: Some(login(password)), ..Default::default() };
+
+        let result = scrape_url_with_params(&state, &url, Some(with_login("s3cret"))).await.unwrap();
+        assert_eq!(result.title, "Members guide");
+        assert!(result.clean_content.contains("logged-in members"));
+        // Never cached or indexed, and credentials stay out of debug output
+        assert!(state.scrape_cache.get(&url).await.is_none());
+        assert!(state.local_index.is_empty());
+        let debug = format!("{:?}", with_login("s3cret"));
+        assert!(!debug.contains("s3cret") && !debug.contains("alice"), "{}", debug);
+
+        let err = scrape_url_with_params(&state, &url, Some(with_login("wrong"))).await.unwrap_err();
+        assert_eq!(crate::error::error_code(&err), "login_failed");
+        assert!(!err.to_string().contains("wrong"));
+
+        // Without a login the page is behind the wall
+        let anonymous = scrape_url(&state, &url).await.and_then(reject_error_status).unwrap_err();
+        assert_eq!(crate::error::error_code(&anonymous), "blocked");
+    }
+
+    #[tokio::test]
+    async fn test_excerpts_carry_query_terms() {
+        use axum::{response::Html, routing::get, Router};
+        let article = "<html><head><title>Guide</title></head><body><article>\
+            <p>Welcome to the handbook. This chapter covers the history of the project and its many contributors over the years.</p>\
+            <p>Lifetimes tell the borrow checker how long a reference stays valid, so the compiler can reject dangling references before the program ever runs.</p>\
+            <p>The appendix lists keyboard shortcuts for the editor plugins that ship with the toolchain and how to configure them.</p>\
+            </article></body></html>";
+        let app = Router::new()
+            .route("/guide", get(move || async move { Html(article) }))
+            .route("/gone", get(|| async { (axum::http::StatusCode::NOT_FOUND, "missing") }));
+        let base = crate::test_support::serve(app).await;
+        let state = Arc::new(crate::test_support::local_state("http://127.0.0.1:9"));
+        let result = |path: &str| SearchResult { url: format!("{}{}", base, path), ..Default::default() };
+        let mut results = vec![result("/guide"), result("/gone"), result("/guide?untouched")];
+
+        attach_excerpts(&state, "borrow checker lifetimes", &mut results, 2).await;
+        let excerpt = results[0].excerpt.as_deref().expect("excerpt for the guide");
+        assert!(excerpt.contains("borrow checker") && excerpt.contains("Lifetimes"), "{}", excerpt);
+        assert!(!excerpt.contains("keyboard shortcuts"));
+        assert!(results[1].excerpt.is_none() && results[2].excerpt.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_scrapes_to_one_host_respect_per_host_concurrency() {
+        use axum::{response::Html, routing::get, Router};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (current, max) = (Arc::clone(&in_flight), Arc::clone(&peak));
+        let app = Router::new().route("/page/:id", get(move || {
+            let (current, max) = (Arc::clone(&current), Arc::clone(&max));
+            async move {
+                let now = current.fetch_add(1, Ordering::SeqCst) + 1;
+                max.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                current.fetch_sub(1, Ordering::SeqCst);
+                Html("<html><head><title>Page</title></head><body><article><p>Enough text on the page to count as an article body.</p></article></body></html>")
+            }
+        }));
+        let base = crate::test_support::serve(app).await;
+        let mut state = crate::test_support::local_state("http://127.0.0.1:9");
+        state.host_limiter = Arc::new(crate::host_limiter::HostLimiter::new(2));
+        let state = Arc::new(state);
+
+        let scrapes = (0..12).map(|i| {
+            let (state, url) = (Arc::clone(&state), format!("{}/page/{}", base, i));
+            tokio::spawn(async move { scrape_url(&state, &url).await })
+        });
+        for scrape in futures::future::join_all(scrapes).await {
+            assert!(scrape.unwrap().is_ok());
+        }
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_default_state_refuses_private_addresses() {
+        use axum::{response::Html, routing::get, Router};
+        let app = Router::new().route("/", get(|| async {
+            Html("<html><head><title>Internal</title></head><body><p>Admin panel with internal details</p></body></html>")
+        }));
+        let base = crate::test_support::serve(app).await;
+        let state = Arc::new(AppState::new("http://127.0.0.1:9".to_string(), reqwest::Client::new()));
+        assert!(!state.allow_private_addresses);
+
+        let err = scrape_url(&state, &format!("{}/", base)).await.unwrap_err();
+        assert_eq!(crate::error::error_code(&err), "private_address");
+        let err = scrape_url_fallback(&RustScraper::new(), &format!("{}/", base), 4096).await.unwrap_err();
+        assert_eq!(crate::error::error_code(&err), "private_address");
+        let page = scrape_url(&Arc::new(crate::test_support::local_state("http://127.0.0.1:9")), &format!("{}/", base)).await.unwrap();
+        assert_eq!(page.title, "Internal");
+    }
+
+    #[tokio::test]
+    async fn test_empty_body_is_retried() {
+        use axum::{response::Html, routing::get, Router};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        let app = Router::new().route("/flaky", get(move || {
+            let attempt = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if attempt == 0 {
+                    Html(String::new())
+                } else {
+                    Html("<html><head><title>Recovered</title></head><body><article><p>The real page content arrived on the second attempt.</p></article></body></html>".to_string())
+                }
+            }
+        }));
+        let base = crate::test_support::serve(app).await;
+        let state = Arc::new(crate::test_support::local_state("http://127.0.0.1:9"));
+
+        let result = scrape_url(&state, &format!("{}/flaky", base)).await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        assert_eq!(result.title, "Recovered");
+        assert!(result.clean_content.contains("second attempt"));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,149 @@
+//! Server-side request forgery guard: scrapes may not reach loopback, private, link-local or
+//! unique-local addresses unless `SCRAPER_ALLOW_PRIVATE_ADDRESSES` is set. The scraper client resolves
+//! names through [`PublicResolver`] and follows redirects through [`redirect_policy`], so every hop
+//! is checked against the address actually connected to, not just the URL the caller passed.
+
+use crate::error::ScrapeError;
+use std::net::{IpAddr, SocketAddr};
+use url::{Host, Url};
+
+/// Redirects followed before giving up unless a scrape asks otherwise, as with `Policy::limited(10)`
+pub const DEFAULT_MAX_REDIRECTS: usize = 10;
+
+/// Whether scrapes may reach private addresses (`SCRAPER_ALLOW_PRIVATE_ADDRESSES=1`); off by default
+pub fn allow_private_addresses() -> bool {
+    matches!(std::env::var("SCRAPER_ALLOW_PRIVATE_ADDRESSES").as_deref(), Ok("1") | Ok("true"))
+}
+
+/// Loopback, RFC 1918 private, link-local (including cloud metadata at 169.254.169.254), unique-local,
+/// carrier-grade NAT, unspecified and broadcast addresses; IPv4-mapped IPv6 is judged by its IPv4 address
+pub fn is_private_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b))
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_private_ip(IpAddr::V4(v4));
+            }
+            let first = v6.segments()[0];
+            v6.is_loopback() || v6.is_unspecified() || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+/// Whether the URL's host is a private IP literal or a `localhost` name, without resolving anything
+pub fn is_private_host(url: &Url) -> bool {
+    match url.host() {
+        Some(Host::Ipv4(ip)) => is_private_ip(IpAddr::V4(ip)),
+        Some(Host::Ipv6(ip)) => is_private_ip(IpAddr::V6(ip)),
+        Some(Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+            domain == "localhost" || domain.ends_with(".localhost")
+        }
+        None => false,
+    }
+}
+
+/// Reject `url` when its host is, or resolves to, a private address. A name that doesn't resolve
+/// passes, so the fetch fails with its usual error.
+pub async fn check_url(url: &Url) -> Result<(), ScrapeError> {
+    if is_private_host(url) {
+        return Err(ScrapeError::PrivateAddress { url: url.to_string() });
+    }
+    if let Some(Host::Domain(domain)) = url.host() {
+        let port = url.port_or_known_default().unwrap_or(80);
+        if let Ok(mut addrs) = tokio::net::lookup_host((domain, port)).await {
+            if addrs.any(|addr| is_private_ip(addr.ip())) {
+                return Err(ScrapeError::PrivateAddress { url: url.to_string() });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// DNS resolver for the scraper client that fails a name with any private address, so a public
+/// hostname can't be pointed (or re-pointed) at an internal service
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: hyper::client::connect::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let host = name.as_str();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, 0)).await?.collect();
+            if addrs.iter().any(|addr| is_private_ip(addr.ip())) {
+                return Err(Box::new(ScrapeError::PrivateAddress { url: host.to_string() }) as Box<dyn std::error::Error + Send + Sync>);
+            }
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// Follow up to `max_redirects` redirects, refusing any to a private IP literal or `localhost` (names are
+/// checked by [`PublicResolver`] when the redirect target is connected to)
+pub fn redirect_policy(allow_private: bool, max_redirects: usize) -> reqwest::redirect::Policy {
+    reqwest::redirect::Policy::custom(move |attempt| {
+        if attempt.previous().len() > max_redirects {
+            attempt.error("too many redirects")
+        } else if !allow_private && is_private_host(attempt.url()) {
+            let url = attempt.url().to_string();
+            attempt.error(ScrapeError::PrivateAddress { url })
+        } else {
+            attempt.follow()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_private_ip_classification() {
+        for private in ["10.0.0.1", "192.168.1.1", "172.16.5.4", "127.0.0.1", "169.254.169.254", "100.64.0.1", "0.0.0.0", "::1", "fc00::1", "fd12:3456::1", "fe80::1", "::ffff:127.0.0.1"] {
+            assert!(is_private_ip(private.parse().unwrap()), "{} should be private", private);
+        }
+        for public in ["93.184.216.34", "8.8.8.8", "172.32.0.1", "100.128.0.1", "2606:4700:4700::1111", "::ffff:93.184.216.34"] {
+            assert!(!is_private_ip(public.parse().unwrap()), "{} should be public", public);
+        }
+        assert!(is_private_host(&Url::parse("http://localhost:8080/").unwrap()));
+        assert!(is_private_host(&Url::parse("http://[::1]/").unwrap()));
+        assert!(!is_private_host(&Url::parse("https://example.com/").unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_redirect_to_loopback_is_refused() {
+        use axum::{response::Redirect, routing::get, Router};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let secret_hits = std::sync::Arc::new(AtomicUsize::new(0));
+        let hits = std::sync::Arc::clone(&secret_hits);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let app = Router::new()
+            .route("/start", get(move || async move { Redirect::temporary(&format!("http://127.0.0.1:{}/secret", port)) }))
+            .route("/secret", get(move || async move {
+                hits.fetch_add(1, Ordering::SeqCst);
+                "internal"
+            }));
+        tokio::spawn(async move { axum::serve(listener, app).await.ok() });
+
+        // The first hop stands in for a public page; only the redirect target is judged here
+        let client = reqwest::Client::builder().redirect(redirect_policy(false, DEFAULT_MAX_REDIRECTS)).build().unwrap();
+        let err = client.get(format!("http://127.0.0.1:{}/start", port)).send().await.unwrap_err();
+        let err = anyhow::Error::from(ScrapeError::from_fetch(err));
+        assert_eq!(crate::error::error_code(&err), "private_address");
+        assert_eq!(secret_hits.load(Ordering::SeqCst), 0);
+
+        let client = reqwest::Client::builder().redirect(redirect_policy(true, DEFAULT_MAX_REDIRECTS)).build().unwrap();
+        let body = client.get(format!("http://127.0.0.1:{}/start", port)).send().await.unwrap().text().await.unwrap();
+        assert_eq!(body, "internal");
+    }
+}
\ No newline at end of file
@@ -25,18 +25,10 @@ impl McpService {
         info!("SearXNG URL: {}", searxng_url);
 
         // Create HTTP client
-        let http_client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()?;
+        let http_client = crate::http_client_builder().build()?;
 
         // Create application state
-        let state = Arc::new(AppState {
-            searxng_url,
-            http_client,
-            search_cache: moka::future::Cache::builder().max_capacity(10_000).time_to_live(std::time::Duration::from_secs(60 * 10)).build(),
-            scrape_cache: moka::future::Cache::builder().max_capacity(10_000).time_to_live(std::time::Duration::from_secs(60 * 30)).build(),
-            outbound_limit: Arc::new(tokio::sync::Semaphore::new(32)),
-        });
+        let state = Arc::new(AppState::new(searxng_url, http_client));
 
         Ok(Self { state })
     }
@@ -56,7 +48,6 @@ impl rmcp::ServerHandler for McpService {
             capabilities: ServerCapabilities::builder()
                 .enable_tools()
                 .build(),
-            ..Default::default()
         }
     }
 
@@ -78,7 +69,15 @@ impl rmcp::ServerHandler for McpService {
                         "language": {"type": "string", "description": "Language code (e.g., en, en-US)"},
                         "safesearch": {"type": "integer", "minimum": 0, "maximum": 2, "description": "0=off, 1=moderate, 2=strict"},
                         "time_range": {"type": "string", "description": "Filter by time (e.g., day, week, month, year)"},
-                        "pageno": {"type": "integer", "minimum": 1, "description": "Page number (1..N)"}
+                        "pageno": {"type": "integer", "minimum": 1, "description": "Page number (1..N)"},
+                        "max_pages": {"type": "integer", "minimum": 1, "maximum": 5, "description": "Fetch this many pages from pageno concurrently and merge them"},
+                        "follow_up_below": {"type": "integer", "minimum": 1, "description": "With fewer results than this, also search SearXNG's top suggestion once and append its results"},
+                        "normalize_urls": {"type": "boolean", "description": "Normalize result URLs (lowercase host, no fragment); originals kept as raw_url"},
+                        "timeout_limit": {"type": "number", "exclusiveMinimum": 0, "description": "Seconds SearXNG waits for slow engines before returning partial results"},
+                        "published_after": {"type": "string", "description": "Only results published on/after this date (YYYY-MM-DD); undated results pass"},
+                        "published_before": {"type": "string", "description": "Only results published on/before this date (YYYY-MM-DD); undated results pass"},
+                        "drop_undated": {"type": "boolean", "description": "With a date filter, also drop results without a publication date"},
+                        "template_filter": {"type": "string", "description": "Only return results rendered with this SearXNG template (e.g., images.html, videos.html)"}
                     },
                     "required": ["query"]
                 }) {
@@ -97,6 +96,50 @@ impl rmcp::ServerHandler for McpService {
                         "url": {
                             "type": "string",
                             "description": "The URL to scrape content from"
+                        },
+                        "preserve_whitespace": {
+                            "type": "boolean",
+                            "description": "Keep indentation and line breaks (code, ASCII tables, poetry) instead of collapsing whitespace"
+                        },
+                        "as_document": {
+                            "type": "boolean",
+                            "description": "Return the main content as a markdown document that keeps the heading hierarchy"
+                        },
+                        "format": {
+                            "type": "string",
+                            "enum": ["text", "markdown"],
+                            "description": "Content format: plain text preview (default) or markdown with headings, links, lists and code blocks"
+                        },
+                        "max_paragraphs": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Only return the first N paragraphs of the content (lead/preview)"
+                        },
+                        "follow_next": {
+                            "type": "boolean",
+                            "description": "Follow rel=next links and merge a multi-chapter docs book into one document"
+                        },
+                        "max_pages": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Chapters to merge with follow_next, including the first (default 10, max 50)"
+                        },
+                        "preview_chars": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": crate::mcp::MAX_PREVIEW_CHARS,
+                            "description": "Characters of content to include in the preview, cut at a word boundary (default 2000, max 50000)"
+                        },
+                        "timeout_secs": {
+                            "type": "number",
+                            "exclusiveMinimum": 0,
+                            "maximum": crate::mcp::MAX_SCRAPE_TIMEOUT_SECS,
+                            "description": "Give up on the page after this many seconds (default 30, max 120)"
+                        },
+                        "max_body_bytes": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Abort the download once the page body passes this many bytes (at most the server's limit, 10 MiB by default)"
                         }
                     },
                     "required": ["url"]
@@ -107,6 +150,16 @@ impl rmcp::ServerHandler for McpService {
                 output_schema: None,
                 annotations: None,
             },
+            Tool {
+                name: Cow::Borrowed("scrape_urls"),
+                description: Some(Cow::Borrowed("Scrape several URLs concurrently. Returns one JSON block per URL, in order, with the page summary or the error for that URL.")),
+                input_schema: match crate::mcp::scrape_urls_schema() {
+                    serde_json::Value::Object(map) => std::sync::Arc::new(map),
+                    _ => std::sync::Arc::new(serde_json::Map::new()),
+                },
+                output_schema: None,
+                annotations: None,
+            },
         ];
 
         Ok(ListToolsResult {
@@ -147,20 +200,33 @@ impl rmcp::ServerHandler for McpService {
                 let time_range = args.get("time_range").and_then(|v| v.as_str()).map(|s| s.to_string());
                 let safesearch = args.get("safesearch").and_then(|v| v.as_i64()).and_then(|n| if (0..=2).contains(&n) { Some(n as u8) } else { None });
                 let pageno = args.get("pageno").and_then(|v| v.as_u64()).map(|n| n as u32);
+                let max_pages = args.get("max_pages").and_then(|v| v.as_u64()).map(|n| n as u32);
+                let follow_up_below = args.get("follow_up_below").and_then(|v| v.as_u64()).map(|n| n as usize);
+                let normalize_urls = args.get("normalize_urls").and_then(|v| v.as_bool());
+                let timeout_limit = args.get("timeout_limit").and_then(|v| v.as_f64());
+                let published_after = args.get("published_after").and_then(|v| v.as_str()).map(|s| s.to_string());
+                let published_before = args.get("published_before").and_then(|v| v.as_str()).map(|s| s.to_string());
+                let drop_undated = args.get("drop_undated").and_then(|v| v.as_bool());
+                let template_filter = args.get("template_filter").and_then(|v| v.as_str()).map(|s| s.to_string());
 
-                let overrides = crate::search::SearchParamOverrides { engines, categories, language, safesearch, time_range, pageno };
+                let overrides = crate::search::SearchParamOverrides {
+                    engines, categories, language, safesearch, time_range, pageno, normalize_urls, timeout_limit,
+                    published_after, published_before, drop_undated, template_filter, max_pages, follow_up_below,
+                };
 
-                match search::search_web_with_params(&self.state, query, Some(overrides)).await {
-                    Ok(results) => {
+                match search::search_web_outcome(&self.state, query, Some(overrides)).await {
+                    Ok(outcome) => {
+                        let results = &outcome.results;
                         let content_text = if results.is_empty() {
-                            format!("No search results found for query: {}", query)
+                            crate::mcp::no_results_text(query, &outcome)
                         } else {
                             let mut text = format!("Found {} search results for '{}':\n\n", results.len(), query);
                             for (i, result) in results.iter().enumerate() {
                                 text.push_str(&format!(
-                                    "{}. **{}**\n   URL: {}\n   Snippet: {}\n\n",
+                                    "{}. **{}**{}\n   URL: {}\n   Snippet: {}\n\n",
                                     i + 1,
                                     result.title,
+                                    result.follow_up_query.as_ref().map(|q| format!(" (via suggestion: {})", q)).unwrap_or_default(),
                                     result.url,
                                     result.content.chars().take(200).collect::<String>()
                                 ));
@@ -172,7 +238,8 @@ impl rmcp::ServerHandler for McpService {
                     }
                     Err(e) => {
                         error!("Search tool error: {}", e);
-                        Ok(CallToolResult::success(vec![Content::text(format!("Search failed: {}", e))]))
+                        let texts = crate::mcp::tool_error_texts("Search failed", &e);
+                        Ok(CallToolResult::error(texts.into_iter().map(Content::text).collect()))
                     }
                 }
             }
@@ -191,47 +258,51 @@ impl rmcp::ServerHandler for McpService {
                         "Missing required parameter: url",
                         None,
                     ))?;
+                let markdown = crate::mcp::markdown_format(args.get("format"))
+                    .map_err(|message| ErrorData::new(ErrorCode::INVALID_PARAMS, message, None))?;
+                let options = crate::mcp::scrape_options(args.get("timeout_secs"), args.get("max_body_bytes"))
+                    .map_err(|message| ErrorData::new(ErrorCode::INVALID_PARAMS, message, None))?;
                 
+                let overrides = scrape::ScrapeParamOverrides {
+                    preserve_whitespace: args.get("preserve_whitespace").and_then(|v| v.as_bool()),
+                    markdown: Some(markdown),
+                    as_document: args.get("as_document").and_then(|v| v.as_bool()),
+                    max_paragraphs: args.get("max_paragraphs").and_then(|v| v.as_u64()).map(|n| n as usize),
+                    follow_next: args.get("follow_next").and_then(|v| v.as_bool()).unwrap_or(false).then(|| {
+                        args.get("max_pages").and_then(|v| v.as_u64()).map_or(scrape::DEFAULT_BOOK_PAGES, |n| n as usize)
+                    }),
+                    options,
+                    ..Default::default()
+                };
+
                 // Force cache invalidation for this URL to ensure fresh scrape
-                self.state.scrape_cache.invalidate(url).await;
+                self.state.scrape_cache.invalidate(&scrape::scrape_cache_key(url, Some(&overrides))).await;
                 
                 // Perform scraping
-                match scrape::scrape_url(&self.state, url).await {
+                match scrape::scrape_url_with_params(&self.state, url, Some(overrides)).await.and_then(scrape::reject_error_status) {
                     Ok(content) => {
                         // Debug: log the actual content length and word count
                         info!("Scraped content: {} words, {} chars clean_content", content.word_count, content.clean_content.len());
                         
-                        let content_preview = if content.clean_content.is_empty() {
-                            "[No content extracted - this may indicate a parsing issue]".to_string()
-                        } else {
-                            content.clean_content.chars().take(2000).collect::<String>()
-                        };
-                        
-                        let content_text = format!(
-                            "**{}**\n\nURL: {}\nWord Count: {}\nLanguage: {}\n\n**Content:**\n{}\n\n**Metadata:**\n- Description: {}\n- Keywords: {}\n\n**Headings:**\n{}\n\n**Links Found:** {}\n**Images Found:** {}",
-                            content.title,
-                            content.url,
-                            content.word_count,
-                            content.language,
-                            content_preview,
-                            content.meta_description,
-                            content.meta_keywords,
-                            content.headings.iter()
-                                .map(|h| format!("- {} {}", h.level.to_uppercase(), h.text))
-                                .collect::<Vec<_>>()
-                                .join("\n"),
-                            content.links.len(),
-                            content.images.len()
-                        );
+                        let content_text = crate::mcp::scrape_text(&content, crate::mcp::preview_chars(args.get("preview_chars")));
                         
                         Ok(CallToolResult::success(vec![Content::text(content_text)]))
                     }
                     Err(e) => {
                         error!("Scrape tool error: {}", e);
-                        Ok(CallToolResult::success(vec![Content::text(format!("Scraping failed: {}", e))]))
+                        let texts = crate::mcp::tool_error_texts("Scraping failed", &e);
+                        Ok(CallToolResult::error(texts.into_iter().map(Content::text).collect()))
                     }
                 }
             }
+            "scrape_urls" => {
+                let args = serde_json::Value::Object(request.arguments.clone().unwrap_or_default());
+                let (urls, max_concurrency) = crate::mcp::scrape_urls_args(&args)
+                    .map_err(|message| ErrorData::new(ErrorCode::INVALID_PARAMS, message, None))?;
+                let preview_chars = crate::mcp::preview_chars(args.get("preview_chars"));
+                let texts = crate::mcp::scrape_urls_texts(&self.state, &urls, max_concurrency, preview_chars).await;
+                Ok(CallToolResult::success(texts.into_iter().map(Content::text).collect()))
+            }
             _ => Err(ErrorData::new(
                 ErrorCode::METHOD_NOT_FOUND,
                 format!("Unknown tool: {}", request.name),
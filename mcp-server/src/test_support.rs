@@ -0,0 +1,107 @@
+//! Shared helpers for unit tests: a throwaway local HTTP server and fixture builders.
+
+use crate::rust_scraper::RustScraper;
+use crate::types::*;
+use crate::AppState;
+use axum::{extract::Query, routing::get, Json, Router};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Serve `app` on an ephemeral localhost port and return its base URL
+pub async fn serve(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind test listener");
+    let addr = listener.local_addr().expect("local addr");
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.ok();
+    });
+    format!("http://{}", addr)
+}
+
+/// Scraper allowed to reach the fixtures `serve` starts on 127.0.0.1
+pub fn local_scraper() -> RustScraper {
+    RustScraper::new().with_allow_private_addresses(true)
+}
+
+/// State whose scrapes may reach the fixtures `serve` starts on 127.0.0.1
+pub fn local_state(searxng_url: &str) -> AppState {
+    let mut state = AppState::new(searxng_url.to_string(), reqwest::Client::new());
+    state.allow_private_addresses = true;
+    state
+}
+
+/// A `ScrapeResponse` with every field empty, for tests that only care about a few fields
+pub fn empty_scrape_response(url: &str) -> ScrapeResponse {
+    ScrapeResponse {
+        url: url.to_string(),
+        status_code: 200,
+        content_type: "text/html".to_string(),
+        ..Default::default()
+    }
+}
+
+
+/// Query parameters received by a mock endpoint, one map per request
+pub type RecordedParams = Arc<Mutex<Vec<HashMap<String, String>>>>;
+
+/// Serve a mock SearXNG `/search` endpoint answering every request with `payload`
+pub async fn mock_searxng(payload: serde_json::Value) -> (String, RecordedParams) {
+    let recorded: RecordedParams = Arc::default();
+    let log = Arc::clone(&recorded);
+    let app = Router::new().route(
+        "/search",
+        get(move |Query(params): Query<HashMap<String, String>>| {
+            log.lock().unwrap().push(params);
+            let payload = payload.clone();
+            async move { Json(payload) }
+        }),
+    );
+    (serve(app).await, recorded)
+}
+
+/// Serve `body` as HTML over HTTPS with a freshly generated self-signed certificate and return the base URL
+pub async fn serve_self_signed_tls(body: &'static str) -> String {
+    use openssl::{asn1::Asn1Time, hash::MessageDigest, pkey::PKey, rsa::Rsa, x509::{X509NameBuilder, X509}};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let key = PKey::from_rsa(Rsa::generate(2048).expect("rsa key")).expect("pkey");
+    let mut name = X509NameBuilder::new().expect("name builder");
+    name.append_entry_by_text("CN", "localhost").expect("common name");
+    let name = name.build();
+    let mut cert = X509::builder().expect("x509 builder");
+    cert.set_version(2).expect("version");
+    cert.set_subject_name(&name).expect("subject");
+    cert.set_issuer_name(&name).expect("issuer");
+    cert.set_pubkey(&key).expect("pubkey");
+    cert.set_not_before(&Asn1Time::days_from_now(0).expect("not before")).expect("not before");
+    cert.set_not_after(&Asn1Time::days_from_now(1).expect("not after")).expect("not after");
+    cert.sign(&key, MessageDigest::sha256()).expect("sign");
+    let identity = native_tls::Identity::from_pkcs8(
+        &cert.build().to_pem().expect("cert pem"),
+        &key.private_key_to_pem_pkcs8().expect("key pem"),
+    )
+    .expect("identity");
+    let acceptor = tokio_native_tls::TlsAcceptor::from(native_tls::TlsAcceptor::new(identity).expect("tls acceptor"));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("bind tls listener");
+    let addr = listener.local_addr().expect("local addr");
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                let Ok(mut tls) = acceptor.accept(stream).await else { return };
+                let mut buf = [0u8; 4096];
+                let _ = tls.read(&mut buf).await;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = tls.write_all(response.as_bytes()).await;
+                let _ = tls.shutdown().await;
+            });
+        }
+    });
+    format!("https://{}", addr)
+}
\ No newline at end of file
@@ -1,30 +1,269 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct SearchRequest {
     pub query: String,
+    /// Return SearXNG suggestions instead of a bare empty list when nothing matched (default true)
+    #[serde(default)]
+    pub suggestions_on_empty: Option<bool>,
+    /// Normalize result URLs (absolute, lowercase host, no fragment); originals are kept in `raw_url`
+    #[serde(default)]
+    pub normalize_urls: Option<bool>,
+    /// Keep results published on/after this date (YYYY-MM-DD or RFC 3339)
+    #[serde(default)]
+    pub published_after: Option<String>,
+    /// Keep results published on/before this date
+    #[serde(default)]
+    pub published_before: Option<String>,
+    /// With a date window, also drop results that have no publication date
+    #[serde(default)]
+    pub drop_undated: Option<bool>,
+    /// Keep only results rendered with this SearXNG template (e.g. `images.html`)
+    #[serde(default)]
+    pub template_filter: Option<String>,
+    /// Fetch each top result's page title and favicon into `resolved_title`/`favicon_url`
+    #[serde(default)]
+    pub resolve_titles: Option<bool>,
+    /// Results to resolve with `resolve_titles` (default 5, max 10)
+    #[serde(default)]
+    pub resolve_top: Option<usize>,
+    /// Fetch each top result's page and attach its most query-relevant passage as `excerpt`
+    #[serde(default)]
+    pub excerpts: Option<bool>,
+    /// Results to fetch excerpts for with `excerpts` (default 3, max 10)
+    #[serde(default)]
+    pub excerpt_top: Option<usize>,
+    /// Fetch this many SearXNG result pages concurrently and merge them in ranking order (default 1, max 5)
+    #[serde(default)]
+    pub max_pages: Option<u32>,
+    /// With fewer results than this, also search SearXNG's top suggestion once and append its results,
+    /// each labeled with `follow_up_query`
+    #[serde(default)]
+    pub follow_up_below: Option<usize>,
+    /// Return the SearXNG request that would be sent instead of running the search
+    #[serde(default)]
+    pub debug_request: Option<bool>,
+    /// Respond with a bare JSON array of result URLs
+    #[serde(default)]
+    pub urls_only: Option<bool>,
+    /// Only include these fields in each result (e.g. `["url", "title"]`)
+    #[serde(default)]
+    pub fields: Option<Vec<String>>,
+}
+
+/// Dry-run view of a SearXNG request; credentials are redacted
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchDebugRequest {
+    pub method: String,
+    pub url: String,
+    pub params: std::collections::BTreeMap<String, String>,
+    pub headers: std::collections::BTreeMap<String, String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SearchResponse {
     pub results: Vec<SearchResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub corrections: Vec<String>,
+    /// SearXNG's answer to the query itself (calculator, unit conversion, infobox summary)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direct_answer: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Search results together with SearXNG's query suggestions, spelling corrections and direct answer
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SearchOutcome {
+    pub results: Vec<SearchResult>,
+    pub suggestions: Vec<String>,
+    pub corrections: Vec<String>,
+    pub direct_answer: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct SearchResult {
     pub url: String,
     pub title: String,
     pub content: String,
     pub engine: Option<String>,
+    /// Every engine that returned this URL (cross-engine agreement); `[engine]` when SearXNG gives no list
+    #[serde(default)]
+    pub engines: Vec<String>,
     pub score: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_date: Option<String>,
+    /// SearXNG result template (`default.html`, `images.html`, `videos.html`, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Page `<title>` (or og:title) fetched with `resolve_titles`; engine titles can be stale or truncated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub favicon_url: Option<String>,
+    /// The page passage most relevant to the query, fetched with `excerpts` to stand in for the engine snippet
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excerpt: Option<String>,
+    /// The SearXNG suggestion whose follow-up search (`follow_up_below`) returned this result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub follow_up_query: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct ScrapeRequest {
     pub url: String,
+    #[serde(default)]
+    pub preserve_whitespace: Option<bool>,
+    #[serde(default)]
+    pub as_document: Option<bool>,
+    /// Keep only the first N paragraphs of `clean_content`
+    #[serde(default)]
+    pub max_paragraphs: Option<usize>,
+    /// Follow `rel=next` links and merge the chapters of a docs book into one document
+    #[serde(default)]
+    pub follow_next: Option<bool>,
+    /// Chapters to merge with `follow_next`, including the first (default 10, max 50)
+    #[serde(default)]
+    pub max_pages: Option<usize>,
+    /// Also return `language_segments`, the content split into runs by detected language
+    #[serde(default)]
+    pub language_segments: Option<bool>,
+    /// Also return each link's and image's original (possibly relative) URL as `raw_href`/`raw_src`
+    #[serde(default)]
+    pub raw_urls: Option<bool>,
+    /// Which scraper runs first, or whether the simple fallback runs at all (default `SCRAPER_ORDER`)
+    #[serde(default)]
+    pub scraper_order: Option<ScraperOrder>,
+    /// Also return `inline_state`, JSON blobs SPAs embed in `<script>` (e.g. `__NEXT_DATA__`)
+    #[serde(default)]
+    pub inline_state: Option<bool>,
+    /// Also return `blocks`, the main content as typed blocks in document order
+    #[serde(default)]
+    pub blocks: Option<bool>,
+    /// Also return `vcard`, the page's contact details (mailto/tel links, JSON-LD) as a vCard 3.0 string
+    #[serde(default)]
+    pub vcard: Option<bool>,
+    /// Also return `structured_data`, the page's JSON-LD objects, capped in number and total size
+    #[serde(default)]
+    pub structured_data: Option<bool>,
+    /// `markdown` also returns `markdown_content`, the main content with headings, links, lists and code kept
+    #[serde(default)]
+    pub format: Option<ContentFormat>,
+    /// Log in through a form first and scrape with the session cookie; such scrapes are never cached
+    #[serde(default)]
+    pub login: Option<FormLogin>,
+    /// Respond with a schema.org `Article` JSON-LD object instead of the scrape result
+    #[serde(default)]
+    pub as_article: Option<bool>,
+    /// Only include these response fields (e.g. `["title", "clean_content"]`)
+    #[serde(default)]
+    pub fields: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// A unit of extracted content; `blocks` lists them in document order for rendering and chunking
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Block {
+    pub kind: BlockKind,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockKind {
+    Heading,
+    Paragraph,
+    ListItem,
+    Code,
+    Quote,
+}
+
+/// Requested representation of a page's main content
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentFormat {
+    #[default]
+    Text,
+    Markdown,
+}
+
+/// Interstitial a page is mostly made of; such pages carry little of their real content
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WallType {
+    Login,
+    Consent,
+    Age,
+    Paywall,
+}
+
+/// Form-based login performed before a scrape. The password is never serialized or logged.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FormLogin {
+    /// URL the login form posts to
+    pub login_url: String,
+    #[serde(default = "default_username_field")]
+    pub username_field: String,
+    #[serde(default = "default_password_field")]
+    pub password_field: String,
+    pub username: String,
+    #[serde(skip_serializing)]
+    pub password: String,
+    /// Other form fields to submit as-is (e.g. `remember_me`)
+    #[serde(default)]
+    pub extra_fields: std::collections::BTreeMap<String, String>,
+}
+
+fn default_username_field() -> String {
+    "username".to_string()
+}
+
+fn default_password_field() -> String {
+    "password".to_string()
+}
+
+impl std::fmt::Debug for FormLogin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FormLogin")
+            .field("login_url", &self.login_url)
+            .field("username_field", &self.username_field)
+            .field("password_field", &self.password_field)
+            .field("username", &"<redacted>")
+            .field("password", &"<redacted>")
+            .finish_non_exhaustive()
+    }
+}
+
+/// Order of the native `RustScraper` and the simple fallback scraper, mostly for comparing their
+/// output on a site. `native_first` gets full metadata and readability-based text and only pays for
+/// the fallback on empty pages. `fallback_first` is cheaper on pages the plain text pass handles, but
+/// its responses have no metadata, headings or language, and it skips retries and the web cache/render
+/// recovery unless its text comes back empty. `native_only` never runs the fallback, so an empty
+/// extraction is returned as-is.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScraperOrder {
+    #[default]
+    NativeFirst,
+    FallbackFirst,
+    NativeOnly,
+}
+
+impl ScraperOrder {
+    /// `SCRAPER_ORDER` (`native_first`, `fallback_first` or `native_only`); unset or unknown is native-first
+    pub fn from_env() -> Self {
+        match std::env::var("SCRAPER_ORDER").as_deref().map(str::trim) {
+            Ok("fallback_first") => Self::FallbackFirst,
+            Ok("native_only") => Self::NativeOnly,
+            _ => Self::NativeFirst,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct ScrapeResponse {
     pub url: String,
     pub title: String,
@@ -33,12 +272,18 @@ pub struct ScrapeResponse {
     pub meta_description: String,
     pub meta_keywords: String,
     pub headings: Vec<Heading>,
+    /// Headings found on the page before the `headings` cap was applied
+    #[serde(default)]
+    pub headings_total: usize,
     pub links: Vec<Link>,
     pub images: Vec<Image>,
     pub timestamp: String,
     pub status_code: u16,
     pub content_type: String,
     pub word_count: usize,
+    /// Non-whitespace characters in `clean_content`; a better size measure than words for CJK text
+    #[serde(default)]
+    pub char_count: usize,
     pub language: String,
     // Optional enriched metadata
     #[serde(default)]
@@ -47,16 +292,105 @@ pub struct ScrapeResponse {
     pub site_name: Option<String>,
     #[serde(default)]
     pub author: Option<String>,
+    /// Richer author data (profile URL, avatar, social links) from JSON-LD, `rel=author` or an author card
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_profile: Option<AuthorProfile>,
     #[serde(default)]
     pub published_at: Option<String>,
     #[serde(default)]
     pub og_title: Option<String>,
+    /// Document title with the " | Site Name" style suffix/prefix removed, for citations
+    #[serde(default)]
+    pub readable_title: Option<String>,
     #[serde(default)]
     pub og_description: Option<String>,
     #[serde(default)]
     pub og_image: Option<String>,
     #[serde(default)]
+    pub og_video: Option<OgVideo>,
+    #[serde(default)]
     pub reading_time_minutes: Option<u32>,
+    #[serde(default)]
+    pub toc: Vec<TocEntry>,
+    #[serde(default)]
+    pub offers: Vec<Offer>,
+    #[serde(default)]
+    pub audio: Vec<AudioMeta>,
+    #[serde(default)]
+    pub paywalled: bool,
+    /// What stands between the reader and the content, when the page is mostly a wall
+    #[serde(default)]
+    pub wall_type: Option<WallType>,
+    #[serde(default)]
+    pub amp_url: Option<String>,
+    #[serde(default)]
+    pub theme_color: Option<String>,
+    /// Date parsed from a dated permalink (YYYY-MM-DD); fills `published_at` when metadata has none
+    #[serde(default)]
+    pub date_from_url: Option<String>,
+    /// Whole days since the page's most reliable date (published, then URL date, then modified);
+    /// `None` when no date parses or it lies in the future
+    #[serde(default)]
+    pub age_days: Option<i64>,
+    #[serde(default)]
+    pub viewport: Option<String>,
+    /// `width=device-width` viewport declared; `None` for non-HTML resources
+    #[serde(default)]
+    pub mobile_friendly: Option<bool>,
+    /// `clean_content` was cut short by `max_paragraphs`; `word_count` still describes the full text
+    #[serde(default)]
+    pub content_truncated: bool,
+    /// Content came from a web cache copy because the site blocked or failed the direct fetch
+    #[serde(default)]
+    pub from_cache_fallback: bool,
+    /// Content was extracted from HTML rendered by the external render service
+    #[serde(default)]
+    pub rendered: bool,
+    /// No readable content could be extracted (e.g. a JavaScript app shell); metadata is still filled in
+    #[serde(default)]
+    pub extraction_failed: bool,
+    /// Extra pages merged from the site's JSON pagination API (per-domain `SCRAPE_PAGINATION_RULES`)
+    #[serde(default)]
+    pub pages_merged: u32,
+    /// Chapter URLs merged into `clean_content` when following `rel=next` (empty otherwise)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chapter_urls: Vec<String>,
+    /// `(language code, text)` runs of `clean_content`, one per change of paragraph language
+    /// (only when `language_segments` is requested)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub language_segments: Vec<(String, String)>,
+    /// JSON state embedded by SPAs (`__NEXT_DATA__`, `window.__INITIAL_STATE__`); only when `inline_state` is requested
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inline_state: Vec<serde_json::Value>,
+    /// The main content as typed blocks (heading, paragraph, list item, code, quote); only when `blocks` is requested
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocks: Vec<Block>,
+    /// Contact details as a vCard 3.0 string; only when `vcard` is requested and an email, phone or address is found
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vcard: Option<String>,
+    /// JSON-LD objects in page order, with top-level arrays and `@graph` split into their nodes; only when
+    /// `structured_data` is requested, capped by `SCRAPE_MAX_STRUCTURED_DATA` and `SCRAPE_MAX_STRUCTURED_DATA_BYTES`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub structured_data: Vec<serde_json::Value>,
+    /// Some structured-data objects were left out by the count or size cap
+    #[serde(default)]
+    pub structured_data_truncated: bool,
+    /// Main content as markdown with absolute links and images; only when `format` is `markdown`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub markdown_content: Option<String>,
+    /// Markdown rendering of the main content keeping heading hierarchy (only when `as_document` is requested)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub document: Option<String>,
+    /// How the generic pipeline chose `clean_content`, for the extraction log (`None` for custom extractors)
+    #[serde(skip)]
+    pub extraction: Option<ExtractionReport>,
+}
+
+/// The extraction pass that produced `clean_content` and the word count of each candidate it beat
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExtractionReport {
+    pub strategy: &'static str,
+    pub candidate_words: Vec<(&'static str, usize)>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -65,10 +399,55 @@ pub struct Heading {
     pub text: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TocEntry {
+    pub level: u8,
+    pub text: String,
+    pub anchor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Offer {
+    pub price: f64,
+    pub currency: String,
+    pub availability: Option<String>,
+}
+
+/// Open Graph video preview (`og:video` and its structured properties)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct OgVideo {
+    pub url: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub mime: Option<String>,
+}
+
+/// Audio enclosure (podcast episode, embedded player) with an absolute URL
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AudioMeta {
+    pub url: String,
+    /// As published, typically ISO 8601 (`PT42M10S`)
+    pub duration: Option<String>,
+    pub mime: Option<String>,
+}
+
+/// Author of the page with absolute profile, avatar and social URLs
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct AuthorProfile {
+    pub name: Option<String>,
+    pub url: Option<String>,
+    pub image: Option<String>,
+    #[serde(default)]
+    pub social: Vec<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Link {
     pub url: String,
     pub text: String,
+    /// `href` as written in the page, before resolving against the base URL (only with `raw_urls`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_href: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -76,11 +455,20 @@ pub struct Image {
     pub src: String,
     pub alt: String,
     pub title: String,
+    /// `src` as written in the page, before resolving against the base URL (only with `raw_urls`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_src: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ChatRequest {
     pub query: String,
+    /// Drop scraped pages at least this similar (0-1) to a longer one; defaults to `CHAT_DEDUP_SIMILARITY`
+    #[serde(default)]
+    pub dedup_similarity: Option<f64>,
+    /// Answer with SearXNG's direct answer when there is one, without scraping; defaults to `CHAT_DIRECT_ANSWERS`
+    #[serde(default)]
+    pub direct_answer: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -88,6 +476,162 @@ pub struct ChatResponse {
     pub response: String,
     pub search_results: Vec<SearchResult>,
     pub scraped_content: Vec<ScrapeResponse>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direct_answer: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LocalSearchRequest {
+    pub query: String,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LocalSearchHit {
+    pub url: String,
+    pub title: String,
+    pub snippet: String,
+    pub score: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LocalSearchResponse {
+    pub results: Vec<LocalSearchHit>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RerankRequest {
+    pub query: String,
+    #[serde(default)]
+    pub results: Vec<SearchResult>,
+}
+
+/// A search result with its BM25 relevance to the rerank query
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RankedResult {
+    #[serde(flatten)]
+    pub result: SearchResult,
+    pub relevance: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RerankResponse {
+    pub results: Vec<RankedResult>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScrapeDiffRequest {
+    pub url: String,
+    /// Content from an earlier snapshot (the `content` of a previous diff response diffs best)
+    #[serde(default)]
+    pub previous_content: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScrapeDiffResponse {
+    pub url: String,
+    pub changed: bool,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Unified diff of previous vs current content
+    pub diff: String,
+    /// Current content snapshot, one paragraph per line
+    pub content: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WatchRequest {
+    pub url: String,
+    /// Seconds between checks (default 300, at least `WATCH_MIN_INTERVAL_SECS`)
+    #[serde(default)]
+    pub interval_secs: Option<f64>,
+}
+
+/// State of a watched URL: the latest content and when it last changed
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct WatchSnapshot {
+    pub id: String,
+    pub url: String,
+    pub interval_secs: f64,
+    /// Title and `clean_content` of the latest successful check
+    pub title: Option<String>,
+    pub content: Option<String>,
+    /// RFC 3339 time of the latest check, successful or not
+    pub checked_at: Option<String>,
+    /// RFC 3339 time the content was first seen or last changed
+    pub changed_at: Option<String>,
+    pub checks: u64,
+    /// Checks whose content differed from the previous snapshot
+    pub changes: u64,
+    /// Checks the server answered `304 Not Modified`
+    pub not_modified: u64,
+    /// Error of the latest check, cleared by the next successful one
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NormalizeUrlRequest {
+    pub url: String,
+    /// Resolve a relative `url` against this page URL
+    #[serde(default)]
+    pub base: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NormalizeUrlResponse {
+    pub url: String,
+    pub normalized: String,
+}
+
+/// Link-preview metadata, produced by the metadata fast path (no content extraction)
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct PageMetadata {
+    pub url: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub og_image: Option<String>,
+    pub favicon: Option<String>,
+    pub publisher: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchMetadataRequest {
+    pub urls: Vec<String>,
+}
+
+/// Per-URL outcome; exactly one of `metadata` and `error` is set
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchMetadataItem {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<PageMetadata>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchMetadataResponse {
+    pub results: Vec<BatchMetadataItem>,
+}
+
+/// Runtime stats served by `/status`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StatusResponse {
+    pub version: String,
+    pub uptime_ms: u64,
+    pub outbound_available: usize,
+    pub outbound_in_flight: usize,
+    pub outbound_queued: usize,
+    pub search_cache_entries: u64,
+    pub scrape_cache_entries: u64,
+    /// Approximate bytes held by each cache (serialized size)
+    pub search_cache_bytes: u64,
+    pub scrape_cache_bytes: u64,
+    /// Entries evicted to stay within `SEARCH_CACHE_MAX_BYTES` / `SCRAPE_CACHE_MAX_BYTES`
+    pub search_cache_evictions: u64,
+    pub scrape_cache_evictions: u64,
+    pub local_index_docs: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
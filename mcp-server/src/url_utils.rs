@@ -0,0 +1,96 @@
+//! URL canonicalization shared by search result dedup, link extraction and `/normalize_url`.
+
+use crate::error::ScrapeError;
+use anyhow::Result;
+use url::Url;
+
+/// Query parameters that only carry click/campaign tracking
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid", "gclid", "dclid", "msclkid", "yclid", "igshid", "mc_cid", "mc_eid", "_ga", "_gl", "ref_src", "spm",
+];
+
+fn is_tracking_param(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.starts_with("utm_") || TRACKING_PARAMS.contains(&name.as_str())
+}
+
+/// Canonical form of an absolute (or protocol-relative, taken as https) URL: lowercase host,
+/// no default port, no fragment, no tracking parameters, and no trailing slash except on the root path
+pub fn normalize_url(raw: &str) -> Result<String> {
+    let raw = raw.trim();
+    let absolute = if raw.starts_with("//") { format!("https:{}", raw) } else { raw.to_string() };
+    let url = Url::parse(&absolute).map_err(|e| ScrapeError::InvalidUrl(format!("Invalid URL '{}': {}", raw, e)))?;
+    Ok(canonicalize(url))
+}
+
+/// A search result URL fit to scrape: protocol-relative URLs get `https:`, surrounding whitespace is
+/// trimmed, and anything that is not then an http(s) URL with a host is `None`. Unlike `normalize_url`
+/// the URL is otherwise left as the engine sent it.
+pub fn repair_result_url(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let absolute = if raw.starts_with("//") { format!("https:{}", raw) } else { raw.to_string() };
+    let url = Url::parse(&absolute).ok()?;
+    let fetchable = matches!(url.scheme(), "http" | "https") && url.host_str().is_some_and(|h| !h.is_empty());
+    fetchable.then_some(absolute)
+}
+
+/// Resolve `href` against `base`, then normalize it
+pub fn normalize_url_with_base(base: &Url, href: &str) -> Result<String> {
+    let href = href.trim();
+    let url = base.join(href).map_err(|e| ScrapeError::InvalidUrl(format!("Invalid URL '{}': {}", href, e)))?;
+    Ok(canonicalize(url))
+}
+
+fn canonicalize(mut url: Url) -> String {
+    // The url crate already lowercases hosts of special schemes and drops default ports
+    url.set_fragment(None);
+    if url.query().is_some() {
+        let kept: Vec<(String, String)> = url
+            .query_pairs()
+            .filter(|(name, _)| !is_tracking_param(name))
+            .map(|(name, value)| (name.into_owned(), value.into_owned()))
+            .collect();
+        if kept.is_empty() {
+            url.set_query(None);
+        } else {
+            url.query_pairs_mut().clear().extend_pairs(kept);
+        }
+    }
+    if url.path().len() > 1 && url.path().ends_with('/') {
+        let trimmed = url.path().trim_end_matches('/').to_string();
+        url.set_path(if trimmed.is_empty() { "/" } else { &trimmed });
+    }
+    url.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_url_cases() {
+        let cases = [
+            ("https://Docs.Example.COM:443/guide/#install", "https://docs.example.com/guide"),
+            ("//cdn.example.org/a", "https://cdn.example.org/a"),
+            ("https://example.com/post?utm_source=x&id=7&fbclid=abc", "https://example.com/post?id=7"),
+            ("https://example.com/post?utm_medium=social", "https://example.com/post"),
+            ("http://example.com", "http://example.com/"),
+            ("https://example.com/a//", "https://example.com/a"),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(normalize_url(raw).unwrap(), expected, "{}", raw);
+        }
+        let base = Url::parse("https://example.com/docs/intro").unwrap();
+        assert_eq!(normalize_url_with_base(&base, "../blog/?utm_campaign=z").unwrap(), "https://example.com/blog");
+        assert!(crate::error::is_invalid_input(&normalize_url("not a url").unwrap_err()));
+    }
+
+    #[test]
+    fn test_repair_result_url() {
+        assert_eq!(repair_result_url("//cdn.example.org/a?b=1").as_deref(), Some("https://cdn.example.org/a?b=1"));
+        assert_eq!(repair_result_url(" https://Example.com/A ").as_deref(), Some("https://Example.com/A"));
+        for garbage in ["", "not a url", "/relative/path", "javascript:void(0)", "mailto:a@example.com", "http://"] {
+            assert_eq!(repair_result_url(garbage), None, "{}", garbage);
+        }
+    }
+}
\ No newline at end of file
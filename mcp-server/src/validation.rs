@@ -0,0 +1,187 @@
+//! Early request validation for the HTTP API: over-long queries and URLs, and URLs that can't be
+//! scraped, are rejected with 400 before any search or fetch (and before SearXNG answers 414).
+
+use crate::types::*;
+use axum::{
+    async_trait,
+    extract::{FromRequest, Request},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use serde::de::DeserializeOwned;
+
+/// Default limits (`MAX_QUERY_CHARS`, `MAX_URL_CHARS`)
+pub const DEFAULT_MAX_QUERY_CHARS: usize = 2_000;
+pub const DEFAULT_MAX_URL_CHARS: usize = 8_192;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RequestLimits {
+    pub max_query_chars: usize,
+    pub max_url_chars: usize,
+}
+
+impl RequestLimits {
+    pub fn from_env() -> Self {
+        let limit = |name: &str, default: usize| {
+            std::env::var(name).ok().and_then(|v| v.parse::<usize>().ok()).filter(|n| *n > 0).unwrap_or(default)
+        };
+        Self {
+            max_query_chars: limit("MAX_QUERY_CHARS", DEFAULT_MAX_QUERY_CHARS),
+            max_url_chars: limit("MAX_URL_CHARS", DEFAULT_MAX_URL_CHARS),
+        }
+    }
+
+    fn check_query(&self, query: &str) -> Result<(), String> {
+        let chars = query.chars().count();
+        if chars > self.max_query_chars {
+            return Err(format!("query is {} characters long, the limit is {}", chars, self.max_query_chars));
+        }
+        Ok(())
+    }
+
+    /// Length limit plus an http(s) URL with a host (or `file://` when enabled)
+    fn check_url(&self, url: &str) -> Result<(), String> {
+        if url.len() > self.max_url_chars {
+            return Err(format!("URL is {} characters long, the limit is {}", url.len(), self.max_url_chars));
+        }
+        let parsed = url::Url::parse(url.trim()).map_err(|e| format!("Invalid URL '{}': {}", url, e))?;
+        match parsed.scheme() {
+            "http" | "https" if parsed.host_str().is_some_and(|h| !h.is_empty()) => Ok(()),
+            "file" if crate::rust_scraper::file_scheme_enabled() => Ok(()),
+            "http" | "https" => Err(format!("Invalid URL '{}': missing host", url)),
+            _ => Err(format!("Invalid URL '{}': must start with http:// or https://", url)),
+        }
+    }
+}
+
+/// Requests whose fields are checked against `RequestLimits` before reaching a handler
+pub trait Validate {
+    fn validate(&self, limits: &RequestLimits) -> Result<(), String>;
+}
+
+impl Validate for SearchRequest {
+    fn validate(&self, limits: &RequestLimits) -> Result<(), String> {
+        limits.check_query(&self.query)
+    }
+}
+
+impl Validate for ChatRequest {
+    fn validate(&self, limits: &RequestLimits) -> Result<(), String> {
+        limits.check_query(&self.query)
+    }
+}
+
+impl Validate for LocalSearchRequest {
+    fn validate(&self, limits: &RequestLimits) -> Result<(), String> {
+        limits.check_query(&self.query)
+    }
+}
+
+impl Validate for RerankRequest {
+    fn validate(&self, limits: &RequestLimits) -> Result<(), String> {
+        limits.check_query(&self.query)?;
+        if self.results.len() > crate::rerank::MAX_RERANK_RESULTS {
+            return Err(format!("{} results to rerank, the limit is {}", self.results.len(), crate::rerank::MAX_RERANK_RESULTS));
+        }
+        Ok(())
+    }
+}
+
+impl Validate for ScrapeRequest {
+    fn validate(&self, limits: &RequestLimits) -> Result<(), String> {
+        limits.check_url(&self.url)
+    }
+}
+
+impl Validate for WatchRequest {
+    fn validate(&self, limits: &RequestLimits) -> Result<(), String> {
+        limits.check_url(&self.url)?;
+        match self.interval_secs {
+            Some(secs) if !secs.is_finite() || secs <= 0.0 => Err(format!("interval_secs must be positive, got {}", secs)),
+            Some(secs) if secs > crate::watch::MAX_WATCH_INTERVAL_SECS => {
+                Err(format!("interval_secs is {}, the limit is {}", secs, crate::watch::MAX_WATCH_INTERVAL_SECS))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Validate for ScrapeDiffRequest {
+    fn validate(&self, limits: &RequestLimits) -> Result<(), String> {
+        limits.check_url(&self.url)
+    }
+}
+
+/// JSON body extractor that answers 400 with an `ErrorResponse` when validation fails
+#[derive(Debug)]
+pub struct Validated<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for Validated<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state).await.map_err(IntoResponse::into_response)?;
+        value
+            .validate(&RequestLimits::from_env())
+            .map_err(|error| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error })).into_response())?;
+        Ok(Self(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Router};
+
+    async fn echo(Validated(request): Validated<ScrapeRequest>) -> String {
+        request.url
+    }
+
+    async fn search(Validated(request): Validated<SearchRequest>) -> String {
+        request.query
+    }
+
+    async fn watch(Validated(request): Validated<WatchRequest>) -> String {
+        request.url
+    }
+
+    async fn post_json(base: &str, path: &str, body: serde_json::Value) -> (StatusCode, String) {
+        let response = reqwest::Client::new().post(format!("{}{}", base, path)).json(&body).send().await.unwrap();
+        (StatusCode::from_u16(response.status().as_u16()).unwrap(), response.text().await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_rejects_long_queries_and_malformed_urls() {
+        let app = crate::test_support::serve(Router::new().route("/scrape", post(echo)).route("/search", post(search))).await;
+
+        let (status, body) = post_json(&app, "/search", serde_json::json!({ "query": "rust ".repeat(1_000) })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("the limit is 2000"), "{}", body);
+        assert_eq!(post_json(&app, "/search", serde_json::json!({ "query": "rust" })).await.0, StatusCode::OK);
+
+        let too_long = format!("https://example.com/?q={}", "a".repeat(9_000));
+        for url in ["not a url", "ftp://example.com/file", "http://", &too_long] {
+            let (status, body) = post_json(&app, "/scrape", serde_json::json!({ "url": url })).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", url);
+            assert!(body.contains("\"error\""), "{}", body);
+        }
+        assert_eq!(post_json(&app, "/scrape", serde_json::json!({ "url": "https://example.com/a" })).await, (StatusCode::OK, "https://example.com/a".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_oversized_watch_interval() {
+        let app = crate::test_support::serve(Router::new().route("/watch", post(watch))).await;
+        for interval in [1e300, crate::watch::MAX_WATCH_INTERVAL_SECS + 1.0, -5.0] {
+            let (status, body) = post_json(&app, "/watch", serde_json::json!({ "url": "https://example.com/", "interval_secs": interval })).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", interval);
+            assert!(body.contains("interval_secs"), "{}", body);
+        }
+        let ok = serde_json::json!({ "url": "https://example.com/", "interval_secs": 3600 });
+        assert_eq!(post_json(&app, "/watch", ok).await.0, StatusCode::OK);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,284 @@
+//! Change monitoring for `/watch`: each watched URL is re-fetched on an interval by its own background
+//! task, with `If-None-Match`/`If-Modified-Since` so unchanged pages cost a `304`. The latest snapshot
+//! and when its content last changed are kept in memory only, for the lifetime of the process.
+
+use crate::cache_ttl::content_hash;
+use crate::error::{ScrapeError, WatchError};
+use crate::rust_scraper::{decode_body, read_body_limited, RustScraper};
+use crate::types::*;
+use crate::AppState;
+use anyhow::Result;
+use reqwest::header::{CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::Duration;
+use tracing::debug;
+
+/// Check interval when a watch doesn't set one
+pub const DEFAULT_WATCH_INTERVAL_SECS: f64 = 300.0;
+
+/// Longest check interval a watch may ask for (one week)
+pub const MAX_WATCH_INTERVAL_SECS: f64 = 7.0 * 24.0 * 3600.0;
+
+/// Shortest interval accepted unless `WATCH_MIN_INTERVAL_SECS` says otherwise; shorter ones are raised to it
+pub const DEFAULT_MIN_WATCH_INTERVAL_SECS: f64 = 10.0;
+
+/// Watches running at once unless `WATCH_MAX_WATCHES` says otherwise
+pub const DEFAULT_MAX_WATCHES: usize = 100;
+
+struct WatchEntry {
+    snapshot: Arc<RwLock<WatchSnapshot>>,
+    task: tokio::task::AbortHandle,
+}
+
+/// The running watches, bounded in number. Dropping the registry stops their tasks.
+pub struct WatchRegistry {
+    watches: Mutex<HashMap<String, WatchEntry>>,
+    max_watches: usize,
+    min_interval: Duration,
+}
+
+impl WatchRegistry {
+    pub fn new(max_watches: usize, min_interval: Duration) -> Self {
+        Self { watches: Mutex::default(), max_watches, min_interval }
+    }
+
+    pub fn from_env() -> Self {
+        let max_watches = std::env::var("WATCH_MAX_WATCHES").ok().and_then(|v| v.parse::<usize>().ok()).unwrap_or(DEFAULT_MAX_WATCHES);
+        let min_interval = std::env::var("WATCH_MIN_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|s| s.is_finite() && *s >= 0.0)
+            .unwrap_or(DEFAULT_MIN_WATCH_INTERVAL_SECS);
+        Self::new(max_watches, Duration::from_secs_f64(min_interval))
+    }
+
+    pub fn len(&self) -> usize {
+        self.watches.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The latest snapshot of watch `id`
+    pub fn get(&self, id: &str) -> Option<WatchSnapshot> {
+        let watches = self.watches.lock().unwrap();
+        watches.get(id).map(|w| w.snapshot.read().unwrap().clone())
+    }
+
+    /// Stop and forget watch `id`; false when there was none
+    pub fn remove(&self, id: &str) -> bool {
+        match self.watches.lock().unwrap().remove(id) {
+            Some(watch) => {
+                watch.task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl std::fmt::Debug for WatchRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WatchRegistry")
+            .field("watches", &self.len())
+            .field("max_watches", &self.max_watches)
+            .field("min_interval", &self.min_interval)
+            .finish()
+    }
+}
+
+impl Drop for WatchRegistry {
+    fn drop(&mut self) {
+        if let Ok(watches) = self.watches.get_mut() {
+            for watch in watches.values() {
+                watch.task.abort();
+            }
+        }
+    }
+}
+
+/// Start watching `url`, checking it right away and then every `interval_secs` (default 300, raised
+/// to the registry minimum and lowered to `MAX_WATCH_INTERVAL_SECS`). The task holds the state weakly and ends with it.
+pub fn start_watch(state: &Arc<AppState>, url: &str, interval_secs: Option<f64>) -> Result<WatchSnapshot, WatchError> {
+    let registry = &state.watches;
+    // NaN survives `clamp`; the fallible conversion turns it into the default instead of a panic
+    let secs = interval_secs.unwrap_or(DEFAULT_WATCH_INTERVAL_SECS).clamp(0.0, MAX_WATCH_INTERVAL_SECS);
+    let interval = Duration::try_from_secs_f64(secs)
+        .unwrap_or(Duration::from_secs_f64(DEFAULT_WATCH_INTERVAL_SECS))
+        .max(registry.min_interval);
+    let mut watches = registry.watches.lock().unwrap();
+    if watches.len() >= registry.max_watches {
+        return Err(WatchError::LimitReached { max: registry.max_watches });
+    }
+    let snapshot = WatchSnapshot {
+        id: uuid::Uuid::new_v4().to_string(),
+        url: url.to_string(),
+        interval_secs: interval.as_secs_f64(),
+        ..Default::default()
+    };
+    let shared = Arc::new(RwLock::new(snapshot.clone()));
+    let task = tokio::spawn(run_watch(Arc::downgrade(state), url.to_string(), interval, Arc::clone(&shared)));
+    watches.insert(snapshot.id.clone(), WatchEntry { snapshot: shared, task: task.abort_handle() });
+    Ok(snapshot)
+}
+
+/// Validators from the last full response, sent back so the server can answer `304`
+#[derive(Debug, Default)]
+struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+async fn run_watch(state: Weak<AppState>, url: String, interval: Duration, snapshot: Arc<RwLock<WatchSnapshot>>) {
+    let mut validators = Validators::default();
+    let mut last_hash = None;
+    loop {
+        let Some(state) = state.upgrade() else { return };
+        let checked = check(&state, &url, &mut validators).await;
+        drop(state);
+        let now = chrono::Utc::now().to_rfc3339();
+        {
+            let mut snapshot = snapshot.write().unwrap();
+            snapshot.checks += 1;
+            snapshot.checked_at = Some(now.clone());
+            match checked {
+                Ok(Some(page)) => {
+                    let hash = content_hash(&page);
+                    if last_hash != Some(hash) {
+                        if last_hash.is_some() {
+                            snapshot.changes += 1;
+                        }
+                        last_hash = Some(hash);
+                        snapshot.changed_at = Some(now);
+                        snapshot.title = Some(page.title);
+                        snapshot.content = Some(page.clean_content);
+                    }
+                    snapshot.last_error = None;
+                }
+                Ok(None) => {
+                    snapshot.not_modified += 1;
+                    snapshot.last_error = None;
+                }
+                Err(e) => {
+                    debug!("Watch check of {} failed: {}", url, e);
+                    snapshot.last_error = Some(e.to_string());
+                }
+            }
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// One conditional fetch of `url`: `None` when the server says it is unchanged, else the extracted page
+async fn check(state: &Arc<AppState>, url: &str, validators: &mut Validators) -> Result<Option<ScrapeResponse>> {
+    let parsed = url::Url::parse(url).map_err(|e| ScrapeError::InvalidUrl(format!("Invalid URL '{}': {}", url, e)))?;
+    let scraper = RustScraper::new()
+        .with_extractors(Arc::clone(&state.extractors))
+        .with_allow_private_addresses(state.allow_private_addresses);
+    scraper.check_address(&parsed).await?;
+    scraper.check_robots(url).await?;
+    let _permit = state
+        .acquire_outbound(Some(url))
+        .await
+        .map_err(|retry_after| ScrapeError::Overloaded { retry_after })?;
+    let mut request = scraper.browser_request(url);
+    if let Some(etag) = &validators.etag {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+    if let Some(last_modified) = &validators.last_modified {
+        request = request.header(IF_MODIFIED_SINCE, last_modified);
+    }
+    let response = request.send().await.map_err(ScrapeError::from_fetch)?;
+    let status = response.status().as_u16();
+    if status == 304 {
+        return Ok(None);
+    }
+    if let Some(e) = ScrapeError::from_status(status) {
+        return Err(e.into());
+    }
+    let header = |name| response.headers().get(name).and_then(|v: &reqwest::header::HeaderValue| v.to_str().ok()).map(str::to_string);
+    let fresh = Validators { etag: header(ETAG), last_modified: header(LAST_MODIFIED) };
+    let content_type = header(CONTENT_TYPE).unwrap_or_else(|| "text/html".to_string());
+    let body = read_body_limited(response, scraper.max_body_bytes()).await?;
+    let html = decode_body(&body, &content_type);
+    let page = scraper.extract_html(url, parsed, html, status, content_type).await?;
+    // Only a page actually recorded may be revalidated against, or a failed read would turn into 304s
+    *validators = fresh;
+    Ok(Some(page))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::{header, HeaderMap, StatusCode}, response::IntoResponse, routing::get, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    async fn wait_for(state: &AppState, id: &str, done: impl Fn(&WatchSnapshot) -> bool) -> WatchSnapshot {
+        for _ in 0..100 {
+            let snapshot = state.watches.get(id).expect("watch exists");
+            if done(&snapshot) {
+                return snapshot;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("watch {} never reached the expected state: {:?}", id, state.watches.get(id));
+    }
+
+    #[tokio::test]
+    async fn test_watch_snapshot_updates() {
+        let version = Arc::new(AtomicUsize::new(1));
+        let current = Arc::clone(&version);
+        let app = Router::new().route(
+            "/page",
+            get(move |headers: HeaderMap| {
+                let v = current.load(Ordering::SeqCst);
+                async move {
+                    let last_modified = format!("Mon, 0{} Jan 2024 00:00:00 GMT", v);
+                    if headers.get(header::IF_MODIFIED_SINCE).is_some_and(|h| h.to_str().ok() == Some(last_modified.as_str())) {
+                        return StatusCode::NOT_MODIFIED.into_response();
+                    }
+                    let body = format!("<html><head><title>Release {v}</title></head><body><article><p>Version {v} of the changelog lists every fix in this release.</p></article></body></html>");
+                    ([(header::LAST_MODIFIED, last_modified)], axum::response::Html(body)).into_response()
+                }
+            }),
+        );
+        let url = format!("{}/page", crate::test_support::serve(app).await);
+        let mut state = crate::test_support::local_state("http://127.0.0.1:9");
+        state.watches = Arc::new(WatchRegistry::new(1, Duration::ZERO));
+        let state = Arc::new(state);
+
+        let watch = start_watch(&state, &url, Some(0.05)).unwrap();
+        assert!(matches!(start_watch(&state, &url, None), Err(WatchError::LimitReached { max: 1 })));
+
+        // Unchanged pages are answered 304 and keep the first snapshot
+        let first = wait_for(&state, &watch.id, |s| s.not_modified >= 2).await;
+        assert_eq!(first.title.as_deref(), Some("Release 1"));
+        assert!(first.content.as_deref().is_some_and(|c| c.contains("Version 1")));
+        assert_eq!(first.changes, 0);
+
+        version.store(2, Ordering::SeqCst);
+        let second = wait_for(&state, &watch.id, |s| s.changes == 1).await;
+        assert!(second.content.as_deref().is_some_and(|c| c.contains("Version 2")));
+        assert!(second.changed_at > first.changed_at);
+        assert!(second.last_error.is_none());
+
+        assert!(state.watches.remove(&watch.id));
+        assert!(state.watches.get(&watch.id).is_none() && state.watches.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_read_keeps_previous_validators() {
+        let app = Router::new().route("/huge", get(|| async {
+            ([(header::ETAG, "\"v2\"")], "x".repeat(RustScraper::new().max_body_bytes() + 1))
+        }));
+        let url = format!("{}/huge", crate::test_support::serve(app).await);
+        let state = Arc::new(crate::test_support::local_state("http://127.0.0.1:9"));
+        let mut validators = Validators { etag: Some("\"v1\"".to_string()), last_modified: None };
+
+        let err = check(&state, &url, &mut validators).await.unwrap_err();
+        assert_eq!(crate::error::error_code(&err), "body_too_large");
+        assert_eq!(validators.etag.as_deref(), Some("\"v1\""));
+    }
+}
\ No newline at end of file